@@ -0,0 +1,99 @@
+use crate::*;
+
+/// Decides whether a new bit may be allocated, see [`AtomicBitSet::set_next_free_bit_with`].
+///
+/// Useful to keep back-pressure logic (occupancy thresholds, rate limits, ...)
+/// next to the allocation instead of racing with it.
+///
+/// The policy is asked before the bit is allocated, so rejected allocations never touch
+/// the set. A policy that reserves a resource in [`admit`](AdmissionPolicy::admit)
+/// (e.g. increments a counter against a limit) can't be overtaken by concurrent
+/// allocations: the reservation is given back with [`release`](AdmissionPolicy::release)
+/// if the set turns out to be full.
+///
+/// Any `Fn(&[AtomicUsize]) -> bool` closure is also an admission policy.
+pub trait AdmissionPolicy {
+    /// Returns `true` if a bit may be allocated from `bitset`.
+    fn admit(&self, bitset: &[AtomicUsize]) -> bool;
+
+    /// Called when a bit admitted by the policy is released, see
+    /// [`AtomicBitSet::release_with`], or when the admitted allocation found no free bit.
+    #[inline]
+    fn release(&self) {}
+}
+
+impl<F> AdmissionPolicy for F
+where
+    F: Fn(&[AtomicUsize]) -> bool,
+{
+    #[inline]
+    fn admit(&self, bitset: &[AtomicUsize]) -> bool {
+        self(bitset)
+    }
+}
+
+/// Rejects allocations once the given number of bits are admitted and not yet released.
+///
+/// The occupancy is tracked by the policy, instead of counting the bits of the set on
+/// every allocation, so the bits must be released with [`AtomicBitSet::release_with`].
+/// Concurrent allocations never go past the limit.
+///
+/// # Example
+///
+/// ```rust
+/// use index_set::{AtomicBitSet, MaxOccupancy};
+///
+/// let bitset: AtomicBitSet<1> = AtomicBitSet::new();
+/// let policy = MaxOccupancy::new(2);
+///
+/// assert_eq!(bitset.set_next_free_bit_with(&policy), Some(0));
+/// assert_eq!(bitset.set_next_free_bit_with(&policy), Some(1));
+/// assert_eq!(bitset.set_next_free_bit_with(&policy), None);
+///
+/// assert!(bitset.release_with(0, &policy));
+/// assert_eq!(policy.occupancy(), 1);
+/// assert_eq!(bitset.set_next_free_bit_with(&policy), Some(0));
+/// ```
+#[derive(Debug)]
+pub struct MaxOccupancy {
+    limit: usize,
+    occupancy: AtomicUsize,
+}
+
+impl MaxOccupancy {
+    /// Creates a policy admitting at most `limit` bits at a time.
+    #[inline]
+    pub const fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            occupancy: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of admitted bits that were not released yet.
+    #[inline]
+    pub fn occupancy(&self) -> usize {
+        self.occupancy.load(Ordering::Relaxed)
+    }
+}
+
+impl AdmissionPolicy for MaxOccupancy {
+    #[inline]
+    fn admit(&self, _: &[AtomicUsize]) -> bool {
+        self.occupancy
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |occupancy| {
+                (occupancy < self.limit).then_some(occupancy + 1)
+            })
+            .is_ok()
+    }
+
+    #[inline]
+    fn release(&self) {
+        // ignores the releases of bits that were never admitted
+        let _ = self
+            .occupancy
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |occupancy| {
+                occupancy.checked_sub(1)
+            });
+    }
+}
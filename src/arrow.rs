@@ -0,0 +1,114 @@
+use crate::*;
+
+/// Loads the validity of the first `len` rows of `buffer` into `slots`,
+/// and returns the number of nulls.
+///
+/// Validity bitmaps are byte buffers where bit `i` (least significant bit first) is set
+/// if row `i` is valid, so value `i` of `slots` is set if row `i` is valid.
+/// The padding bits past `len`, that may hold anything, are cleared.
+///
+/// Returns `None` if `buffer` or `slots` is too short for `len` rows.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{arrow, BitSet};
+///
+/// // rows 0, 2, 4, 5 and 9 are valid, the last 6 bits are padding
+/// let buffer = [0b0011_0101, 0b1111_1110];
+/// let mut slots = [0_u64; 1];
+///
+/// assert_eq!(arrow::read_validity(&buffer, 10, &mut slots), Some(5));
+/// assert!(slots.iter_ones().eq([0, 2, 4, 5, 9]));
+/// ```
+pub fn read_validity<T: Word>(buffer: &[u8], len: usize, slots: &mut [T]) -> Option<usize> {
+    let bytes = len.div_ceil(8);
+    let slot_bytes = (T::BITS / 8) as usize;
+    if buffer.len() < bytes || slots.len().checked_mul(T::BITS as usize)? < len {
+        return None;
+    }
+    let mut valid = 0;
+    for (slot_idx, slot) in slots.iter_mut().enumerate() {
+        let start = slot_idx * slot_bytes;
+        let mut word = 0_u128;
+        for (i, &byte) in buffer[bytes.min(start)..bytes.min(start + slot_bytes)]
+            .iter()
+            .enumerate()
+        {
+            word |= (byte as u128) << (8 * i);
+        }
+        // clear the padding past `len`
+        let rows = len
+            .saturating_sub(slot_idx * T::BITS as usize)
+            .min(T::BITS as usize);
+        if rows < 128 {
+            word &= (1 << rows) - 1;
+        }
+        valid += word.count_ones() as usize;
+        *slot = T::from_u128(word);
+    }
+    Some(len - valid)
+}
+
+/// Stores the first `len` values of `slots` as the validity of `len` rows in `buffer`,
+/// and returns the number of nulls.
+///
+/// Row `i` is valid if value `i` is in `slots`. The padding bits of the last byte are
+/// cleared, and the bytes past it are left untouched.
+///
+/// Returns `None` if `buffer` or `slots` is too short for `len` rows.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{arrow, BitSetMut};
+///
+/// let mut slots = [0_u32; 1];
+/// for row in [0, 2, 9, 20] {
+///     slots.insert(row);
+/// }
+/// let mut buffer = [0xff; 2];
+/// assert_eq!(arrow::write_validity(&slots, 10, &mut buffer), Some(7));
+/// assert_eq!(buffer, [0b0000_0101, 0b0000_0010]);
+/// ```
+pub fn write_validity<T: Word>(slots: &[T], len: usize, buffer: &mut [u8]) -> Option<usize> {
+    let bytes = len.div_ceil(8);
+    let bits = T::BITS as usize;
+    if buffer.len() < bytes || slots.len().checked_mul(bits)? < len {
+        return None;
+    }
+    let mut valid = 0;
+    for (i, byte) in buffer[..bytes].iter_mut().enumerate() {
+        let (slot_idx, bit) = util::split_index(i * 8, T::BITS);
+        let mut value = (slots[slot_idx].to_u128() >> bit) as u8;
+        // clear the padding past `len`
+        let rows = len - i * 8;
+        if rows < 8 {
+            value &= (1 << rows) - 1;
+        }
+        valid += value.count_ones() as usize;
+        *byte = value;
+    }
+    Some(len - valid)
+}
+
+/// Returns the number of nulls among the first `len` rows of `buffer`,
+/// or `None` if it is too short for `len` rows.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::arrow;
+///
+/// assert_eq!(arrow::null_count(&[0b0011_0101, 0b1111_1110], 10), Some(5));
+/// assert_eq!(arrow::null_count(&[0xff], 10), None);
+/// ```
+pub fn null_count(buffer: &[u8], len: usize) -> Option<usize> {
+    let buffer = buffer.get(..len.div_ceil(8))?;
+    let mut valid: usize = buffer.iter().map(|byte| byte.count_ones() as usize).sum();
+    if let (Some(last), rows @ 1..) = (buffer.last(), len % 8) {
+        // don't count the padding
+        valid -= (last >> rows).count_ones() as usize;
+    }
+    Some(len - valid)
+}
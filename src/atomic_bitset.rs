@@ -1,13 +1,59 @@
 use crate::*;
 
 /// Same as `[AtomicUsize; N]`, but with an additional functionality.
+///
+/// The allocator metadata lives on its own cache line, so that allocations don't
+/// invalidate the cache line of the first slots for the readers of the set.
 pub struct AtomicBitSet<const N: usize> {
     bitset: [AtomicUsize; N],
+    meta: Metadata,
+}
+
+// The state of the allocators, written by allocations but never by readers.
+// Aligned to 128 bytes, since some CPUs prefetch cache lines in pairs.
+#[repr(align(128))]
+struct Metadata {
     // used for optimizing the search to find the next free bit
     rotation: AtomicUsize,
+    // bit index where the next cyclic allocation starts searching
+    cursor: AtomicUsize,
+    // position in the permutation where the next shuffled allocation starts searching
+    step: AtomicUsize,
+    // seed of the permutation, see `AtomicBitSet::with_shuffle_seed`
+    seed: u64,
+    // ignore the rotation hint, see `AtomicBitSet::deterministic`
+    deterministic: bool,
+}
+
+impl Metadata {
+    const fn new(deterministic: bool) -> Self {
+        Self {
+            rotation: AtomicUsize::new(0),
+            cursor: AtomicUsize::new(0),
+            step: AtomicUsize::new(0),
+            seed: 0,
+            deterministic,
+        }
+    }
 }
 
 impl<const N: usize> AtomicBitSet<N> {
+    /// An empty `AtomicBitSet`, usable in array-repeat expressions.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, SharedBitSet, BitSet};
+    ///
+    /// static POOLS: [AtomicBitSet<4>; 16] = [AtomicBitSet::EMPTY; 16];
+    ///
+    /// POOLS[3].insert(7);
+    /// assert!(POOLS[3].has(7));
+    /// assert!(!POOLS[4].has(7));
+    /// ```
+    #[allow(clippy::declare_interior_mutable_const)]
+    pub const EMPTY: Self = Self::new();
+
     /// Creates a new `AtomicBitSet` with the specified number of slots.
     /// Each slot can hold 32/64 bits depending on the architecture.
     ///
@@ -23,10 +69,70 @@ impl<const N: usize> AtomicBitSet<N> {
     pub const fn new() -> Self {
         Self {
             bitset: [const { AtomicUsize::new(0) }; N],
-            rotation: AtomicUsize::new(0),
+            meta: Metadata::new(false),
         }
     }
 
+    /// Creates a new `AtomicBitSet`, whose [`set_next_free_bit`](Self::set_next_free_bit)
+    /// always returns the lowest free bit, ignoring the rotation hint.
+    ///
+    /// In single-threaded use, the allocation order then only depends on the
+    /// sequence of operations, which makes snapshot tests of systems embedding
+    /// the allocator reproducible.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, SharedBitSet};
+    ///
+    /// let bitset: AtomicBitSet<4> = AtomicBitSet::deterministic();
+    /// bitset.set_rotation(2);
+    ///
+    /// assert_eq!(bitset.set_next_free_bit(), Some(0));
+    /// assert_eq!(bitset.set_next_free_bit(), Some(1));
+    /// bitset.remove(0);
+    /// assert_eq!(bitset.set_next_free_bit(), Some(0));
+    /// ```
+    #[inline]
+    pub const fn deterministic() -> Self {
+        Self {
+            meta: Metadata::new(true),
+            ..Self::new()
+        }
+    }
+
+    /// Creates a new `AtomicBitSet`, whose
+    /// [`set_next_free_bit_shuffled`](Self::set_next_free_bit_shuffled) follows the
+    /// permutation seeded by `seed`. [`new`](Self::new) uses the seed `0`.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::AtomicBitSet;
+    ///
+    /// let a: AtomicBitSet<2> = AtomicBitSet::with_shuffle_seed(42);
+    /// let b: AtomicBitSet<2> = AtomicBitSet::with_shuffle_seed(42);
+    /// assert!((0..128).all(|_| a.set_next_free_bit_shuffled() == b.set_next_free_bit_shuffled()));
+    /// ```
+    #[inline]
+    pub const fn with_shuffle_seed(seed: u64) -> Self {
+        Self {
+            meta: Metadata {
+                seed,
+                ..Metadata::new(false)
+            },
+            ..Self::new()
+        }
+    }
+
+    /// Returns the slots of the bitset.
+    ///
+    /// Same as dereferencing, but usable in const contexts.
+    #[inline]
+    pub const fn as_slice(&self) -> &[AtomicUsize] {
+        &self.bitset
+    }
+
     /// Atomically finds the next free bit (unset bit with value `0`) in the bitset, sets it to `1`,
     /// and returns its index.
     ///
@@ -39,7 +145,7 @@ impl<const N: usize> AtomicBitSet<N> {
     /// // Create a new AtomicBitSet with memory size of 1 kilobyte
     /// static BIT_SET: AtomicBitSet<{ slot_count::from_kilobytes(1) }> = AtomicBitSet::new();
     /// assert_eq!(BIT_SET.set_next_free_bit(), Some(0));
-    /// 
+    ///
     /// BIT_SET.insert(2);
     /// assert_eq!(BIT_SET.set_next_free_bit(), Some(1));
     /// assert_eq!(BIT_SET.set_next_free_bit(), Some(3));
@@ -52,38 +158,438 @@ impl<const N: usize> AtomicBitSet<N> {
     /// // it can hold up to 8192 unique identifiers.
     /// assert_eq!(BIT_SET.capacity(), 8192);
     /// ```
+    #[inline]
     pub fn set_next_free_bit(&self) -> Option<usize> {
-        // rotate the slots to find the next free id
-        let skip = self.rotation.load(Ordering::Relaxed);
-        let mut slot_idx = skip;
+        if self.meta.deterministic {
+            return util::set_lowest_free_bit(&self.bitset);
+        }
+        util::set_next_free_bit(&self.bitset, &self.meta.rotation)
+    }
 
-        let slots = utils::rotate_left(&self.bitset, skip);
+    /// Same as [`set_next_free_bit`](Self::set_next_free_bit), but gives up after
+    /// `max_attempts` failed compare-and-swap attempts, so that latency-critical callers
+    /// can fall back to another strategy under heavy contention.
+    ///
+    /// Returns [`AllocError::Full`] if the set is full,
+    /// and [`AllocError::Contended`] if the attempts ran out.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{AllocError, AtomicBitSet, SharedBitSet};
+    ///
+    /// let bitset: AtomicBitSet<1> = AtomicBitSet::new();
+    /// assert_eq!(bitset.set_next_free_bit_bounded(4), Ok(0));
+    ///
+    /// bitset.insert_iter(0..usize::BITS as usize).unwrap();
+    /// assert_eq!(bitset.set_next_free_bit_bounded(4), Err(AllocError::Full));
+    /// ```
+    #[inline]
+    pub fn set_next_free_bit_bounded(&self, max_attempts: usize) -> Result<usize, AllocError> {
+        if self.meta.deterministic {
+            return util::set_lowest_free_bit_bounded(&self.bitset, max_attempts);
+        }
+        util::set_next_free_bit_bounded(&self.bitset, &self.meta.rotation, max_attempts)
+    }
 
-        for slot in slots {
-            let available_slot = slot.fetch_update(Ordering::AcqRel, Ordering::Acquire, |curr| {
-                // slot is full
-                if curr == usize::MAX {
-                    return None;
-                }
-                let next_available_bit = (!curr).trailing_zeros() as usize;
-                Some(curr | (1 << next_available_bit))
+    /// Returns the slot where [`set_next_free_bit`](Self::set_next_free_bit)
+    /// starts searching for a free bit.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::AtomicBitSet;
+    ///
+    /// let bitset: AtomicBitSet<4> = AtomicBitSet::new();
+    /// assert_eq!(bitset.rotation_hint(), 0);
+    ///
+    /// bitset.set_rotation(2);
+    /// assert_eq!(bitset.rotation_hint(), 2);
+    /// assert_eq!(bitset.set_next_free_bit(), Some(2 * usize::BITS as usize));
+    ///
+    /// bitset.reset_rotation();
+    /// assert_eq!(bitset.set_next_free_bit(), Some(0));
+    /// ```
+    #[inline]
+    pub fn rotation_hint(&self) -> usize {
+        self.meta.rotation.load(Ordering::Relaxed)
+    }
+
+    /// Makes [`set_next_free_bit`](Self::set_next_free_bit) start searching from the given slot.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `slot >= N`.
+    #[inline]
+    pub fn set_rotation(&self, slot: usize) {
+        assert!(slot < N, "slot index out of range: {slot} >= {N}");
+        self.meta.rotation.store(slot, Ordering::Relaxed);
+    }
+
+    /// Makes [`set_next_free_bit`](Self::set_next_free_bit) start searching from the first slot,
+    /// e.g. after a bulk removal of low indices.
+    #[inline]
+    pub fn reset_rotation(&self) {
+        self.meta.rotation.store(0, Ordering::Relaxed);
+    }
+
+    /// Same as [`set_next_free_bit`](Self::set_next_free_bit), but returns `None` without
+    /// allocating if `policy` doesn't admit a new bit.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, BitSet};
+    /// use std::sync::atomic::AtomicUsize;
+    ///
+    /// let bitset: AtomicBitSet<1> = AtomicBitSet::new();
+    /// let below_two = |bitset: &[AtomicUsize]| bitset.size() < 2;
+    ///
+    /// assert_eq!(bitset.set_next_free_bit_with(&below_two), Some(0));
+    /// assert_eq!(bitset.set_next_free_bit_with(&below_two), Some(1));
+    /// assert_eq!(bitset.set_next_free_bit_with(&below_two), None);
+    /// assert_eq!(bitset.size(), 2);
+    /// ```
+    pub fn set_next_free_bit_with(&self, policy: &impl AdmissionPolicy) -> Option<usize> {
+        if !policy.admit(&self.bitset) {
+            return None;
+        }
+        let index = self.set_next_free_bit();
+        if index.is_none() {
+            policy.release();
+        }
+        index
+    }
+
+    /// Removes the bit at `index`, and tells `policy` that it was released if it was set.
+    ///
+    /// Returns `true` if the bit was set. Bits allocated with
+    /// [`set_next_free_bit_with`](Self::set_next_free_bit_with) must be released with the
+    /// same policy, for policies that track them, like [`MaxOccupancy`].
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, MaxOccupancy};
+    ///
+    /// let bitset: AtomicBitSet<1> = AtomicBitSet::new();
+    /// let policy = MaxOccupancy::new(1);
+    ///
+    /// let index = bitset.set_next_free_bit_with(&policy).unwrap();
+    /// assert!(bitset.release_with(index, &policy));
+    /// assert!(!bitset.release_with(index, &policy));
+    /// assert_eq!(policy.occupancy(), 0);
+    /// ```
+    pub fn release_with(&self, index: usize, policy: &impl AdmissionPolicy) -> bool {
+        let released = self.remove(index) == Some(true);
+        if released {
+            policy.release();
+        }
+        released
+    }
+
+    /// Exchanges the contents of two bitsets, word by word.
+    ///
+    /// Each word of `self` is replaced by the word of `other` in one atomic step, so every
+    /// concurrent modification of `self` is applied either before the swap of its word
+    /// (and moved to `other`), or after it. `other` must not be modified concurrently:
+    /// its modifications may be overwritten.
+    ///
+    /// The bitsets as a whole are **not** swapped atomically: a concurrent reader may
+    /// observe some words already swapped and others not, or the bits of a word in both
+    /// bitsets or in neither (while it is being moved). Readers that need a consistent view must be
+    /// synchronized with the swap externally (e.g. with a lock), or use [`std::mem::swap`]
+    /// when both bitsets are borrowed mutably.
+    ///
+    /// This enables double-buffering, where writers fill `self` while a reader swaps it with
+    /// `other` and processes the previous contents.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, BitSet, SharedBitSet};
+    ///
+    /// let front: AtomicBitSet<2> = AtomicBitSet::new();
+    /// let back: AtomicBitSet<2> = AtomicBitSet::new();
+    /// front.insert(1);
+    /// back.insert(100);
+    ///
+    /// front.swap_contents(&back);
+    /// assert!(front.has(100) && !front.has(1));
+    /// assert!(back.has(1) && !back.has(100));
+    /// ```
+    pub fn swap_contents(&self, other: &Self) {
+        for (a, b) in self.bitset.iter().zip(&other.bitset) {
+            let mut old_b = None;
+            // `b` takes the word of `a` first, and `a` only takes the previous word of `b` if it
+            // wasn't modified in the meantime, otherwise `b` takes the new word of `a`
+            let _ = a.fetch_update(Ordering::AcqRel, Ordering::Acquire, |old_a| {
+                let previous = b.swap(old_a, Ordering::AcqRel);
+                Some(*old_b.get_or_insert(previous))
             });
+        }
+    }
 
-            if let Ok(curr) = available_slot {
-                if skip != slot_idx {
-                    self.rotation.store(slot_idx, Ordering::Relaxed);
-                }
-                let next_available_bit = (!curr).trailing_zeros() as usize;
-                return Some(slot_idx * usize::BITS as usize + next_available_bit);
-            }
+    /// Takes an immutable snapshot of the bitset, that can be shared across threads
+    /// without further atomic loads.
+    ///
+    /// Each slot is loaded once, so modifications made while freezing may not be observed.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, BitSet, SharedBitSet};
+    ///
+    /// let bitset: AtomicBitSet<2> = AtomicBitSet::new();
+    /// bitset.insert(42);
+    ///
+    /// let frozen = bitset.freeze();
+    /// bitset.remove(42);
+    ///
+    /// assert!(frozen.has(42));
+    /// assert_eq!(frozen.size(), 1);
+    /// ```
+    pub fn freeze(&self) -> Arc<FrozenBitSet> {
+        Arc::new(FrozenBitSet::new(&self.bitset))
+    }
+
+    /// Allocates `k` identifiers (not necessarily contiguous), that are all released
+    /// when the returned group is dropped, even on error paths.
+    ///
+    /// Returns `None`, without allocating anything, if fewer than `k` identifiers are free.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, BitSet};
+    ///
+    /// let bitset: AtomicBitSet<1> = AtomicBitSet::new();
+    /// {
+    ///     let group = bitset.allocate_group(3).unwrap();
+    ///     assert_eq!(group[..], [0, 1, 2]);
+    ///     assert_eq!(bitset.size(), 3);
+    /// }
+    /// assert_eq!(bitset.size(), 0);
+    ///
+    /// assert!(bitset.allocate_group(usize::BITS as usize + 1).is_none());
+    /// assert_eq!(bitset.size(), 0);
+    /// ```
+    pub fn allocate_group(&self, k: usize) -> Option<IdGroup<'_>> {
+        IdGroup::allocate(&self.bitset, k, || self.set_next_free_bit())
+    }
 
-            slot_idx += 1;
-            if slot_idx >= N {
-                slot_idx = 0;
+    /// Atomically finds the lowest free bit in the bitset, sets it to `1`, and returns its index.
+    ///
+    /// The rotation hint used by [`set_next_free_bit`](Self::set_next_free_bit) is ignored,
+    /// so indices are assigned as densely as possible.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, slot_count, SharedBitSet};
+    ///
+    /// let bitset: AtomicBitSet<{ slot_count::from_bits(128) }> = AtomicBitSet::new();
+    /// assert_eq!(bitset.set_lowest_free_bit(), Some(0));
+    /// assert_eq!(bitset.set_lowest_free_bit(), Some(1));
+    ///
+    /// bitset.remove(0);
+    /// assert_eq!(bitset.set_lowest_free_bit(), Some(0));
+    /// ```
+    #[inline]
+    pub fn set_lowest_free_bit(&self) -> Option<usize> {
+        util::set_lowest_free_bit(&self.bitset)
+    }
+
+    /// Same as [`set_lowest_free_bit`](Self::set_lowest_free_bit), but gives up after
+    /// `max_retries` failed compare-and-swap attempts, e.g. for use inside interrupt handlers.
+    ///
+    /// The search never loops unboundedly: it makes at most `N + max_retries` attempts,
+    /// and doesn't write the rotation hint. Returns `None` if the set is full,
+    /// or if the retries ran out under contention.
+    ///
+    /// [`has`](BitSet::has), [`insert`](SharedBitSet::insert) and [`remove`](SharedBitSet::remove)
+    /// are single atomic operations, so they are safe to call from interrupt handlers as well.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, SharedBitSet};
+    ///
+    /// static BIT_SET: AtomicBitSet<1> = AtomicBitSet::new();
+    ///
+    /// // in an interrupt handler
+    /// assert_eq!(BIT_SET.try_set_lowest_free_bit(3), Some(0));
+    /// BIT_SET.remove(0);
+    /// assert_eq!(BIT_SET.try_set_lowest_free_bit(0), Some(0));
+    /// ```
+    #[inline]
+    pub fn try_set_lowest_free_bit(&self, max_retries: usize) -> Option<usize> {
+        util::set_lowest_free_bit_bounded(&self.bitset, max_retries).ok()
+    }
+
+    /// Atomically sets a free bit, preferring the regions with the highest weight,
+    /// and returns its index.
+    ///
+    /// The slots are split into `weights.len()` regions of (nearly) equal size.
+    /// Regions are tried in order of decreasing weight, the lowest region first on ties,
+    /// and regions with a weight of `0` are never used.
+    /// Within a region, the lowest free bit is used.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::AtomicBitSet;
+    ///
+    /// let bitset: AtomicBitSet<2> = AtomicBitSet::new();
+    /// let bits = usize::BITS as usize;
+    ///
+    /// // prefer the second half, but overflow to the first one
+    /// let weights = [1, 2];
+    /// for expected in bits..2 * bits {
+    ///     assert_eq!(bitset.set_next_free_bit_weighted(&weights), Some(expected));
+    /// }
+    /// assert_eq!(bitset.set_next_free_bit_weighted(&weights), Some(0));
+    ///
+    /// assert_eq!(bitset.set_next_free_bit_weighted(&[0, 0]), None);
+    /// ```
+    pub fn set_next_free_bit_weighted(&self, weights: &[u8]) -> Option<usize> {
+        let regions = weights.len();
+        let region_start = |region: usize| region * N / regions;
+
+        let mut weight = weights.iter().copied().max()?;
+        while weight > 0 {
+            for region in (0..regions).filter(|&region| weights[region] == weight) {
+                let start = region_start(region);
+                let slots = &self.bitset[start..region_start(region + 1)];
+                if let Some(index) = util::set_lowest_free_bit(slots) {
+                    return Some(start * usize::BITS as usize + index);
+                }
             }
+            // next lower weight
+            weight = weights.iter().copied().filter(|&w| w < weight).max()?;
         }
         None
     }
+
+    /// Atomically sets the next free bit after the most recently allocated one, wrapping around
+    /// to the start of the bitset once the end is reached, and returns its index.
+    ///
+    /// Unlike [`set_next_free_bit`](Self::set_next_free_bit), returned indices keep increasing
+    /// until wrap-around, even if lower indices were freed in the meantime.
+    /// This maximizes the time before an index is reused.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, slot_count, SharedBitSet};
+    ///
+    /// let bitset: AtomicBitSet<{ slot_count::from_bits(128) }> = AtomicBitSet::new();
+    /// assert_eq!(bitset.set_next_free_bit_cyclic(), Some(0));
+    /// assert_eq!(bitset.set_next_free_bit_cyclic(), Some(1));
+    ///
+    /// bitset.remove(0);
+    /// assert_eq!(bitset.set_next_free_bit_cyclic(), Some(2));
+    /// ```
+    #[inline]
+    pub fn set_next_free_bit_cyclic(&self) -> Option<usize> {
+        util::set_next_free_bit_cyclic(&self.bitset, &self.meta.cursor)
+    }
+
+    /// Atomically sets the next free bit in a pseudo-random permutation of the bitset,
+    /// and returns its index.
+    ///
+    /// The permutation is seeded at construction, see
+    /// [`with_shuffle_seed`](Self::with_shuffle_seed). The order is reproducible for a given
+    /// seed, and like [`set_next_free_bit_cyclic`](Self::set_next_free_bit_cyclic), it covers
+    /// the whole bitset before reusing an index. Spreads the identifiers evenly, e.g. across
+    /// the buckets of a hash table keyed by them.
+    ///
+    /// Returns `None` if the bitset is full.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, slot_count, SharedBitSet};
+    ///
+    /// let a: AtomicBitSet<{ slot_count::from_bits(128) }> = AtomicBitSet::with_shuffle_seed(42);
+    /// let b: AtomicBitSet<{ slot_count::from_bits(128) }> = AtomicBitSet::with_shuffle_seed(42);
+    ///
+    /// let mut order: Vec<_> = (0..128).map(|_| a.set_next_free_bit_shuffled().unwrap()).collect();
+    /// assert!((0..128).all(|i| b.set_next_free_bit_shuffled() == Some(order[i])));
+    /// assert_eq!(a.set_next_free_bit_shuffled(), None);
+    ///
+    /// order.sort();
+    /// assert!(order.into_iter().eq(0..128));
+    /// ```
+    #[inline]
+    pub fn set_next_free_bit_shuffled(&self) -> Option<usize> {
+        util::set_next_free_bit_shuffled(&self.bitset, &self.meta.step, self.meta.seed)
+    }
+
+    /// Atomically sets a free bit chosen uniformly at random, and returns its index.
+    ///
+    /// Unlike the sequential allocators, the returned indices are unpredictable, for
+    /// token-like identifiers. Each attempt counts the free bits, which is a full scan.
+    ///
+    /// Returns `None` if the bitset is full.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, BitSet, slot_count};
+    /// use rand::{SeedableRng, rngs::SmallRng};
+    ///
+    /// let mut rng = SmallRng::seed_from_u64(7);
+    /// let bitset: AtomicBitSet<{ slot_count::from_bits(128) }> = AtomicBitSet::new();
+    ///
+    /// let token = bitset.set_random_free_bit(&mut rng).unwrap();
+    /// assert!(bitset.has(token));
+    ///
+    /// while bitset.set_random_free_bit(&mut rng).is_some() {}
+    /// assert_eq!(bitset.free_count(), 0);
+    /// ```
+    #[cfg(feature = "rand")]
+    #[inline]
+    pub fn set_random_free_bit(&self, rng: &mut impl rand::Rng) -> Option<usize> {
+        util::set_random_free_bit(&self.bitset, rng)
+    }
+}
+
+/// Iterates over a snapshot of the values of the set, in ascending order.
+///
+/// Like [`freeze`](AtomicBitSet::freeze), every slot is loaded once (with `Acquire`
+/// ordering) before iterating, without allocating. Modifications made while iterating
+/// are not observed, and the ones that race with the loads may or may not be.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{AtomicBitSet, BitSet, SharedBitSet};
+///
+/// let bitset: AtomicBitSet<2> = AtomicBitSet::new();
+/// bitset.insert(5);
+/// bitset.insert(70);
+///
+/// let mut values = Vec::new();
+/// for value in &bitset {
+///     values.push(value);
+/// }
+/// assert_eq!(values, [5, 70]);
+///
+/// for value in &bitset {
+///     // not observed by the running iteration
+///     bitset.insert(value + 1);
+/// }
+/// assert_eq!(bitset.size(), 4);
+/// ```
+impl<const N: usize> IntoIterator for &AtomicBitSet<N> {
+    type Item = usize;
+    type IntoIter = SnapshotOnes<N>;
+
+    #[inline]
+    fn into_iter(self) -> SnapshotOnes<N> {
+        SnapshotOnes::new(&self.bitset)
+    }
 }
 
 impl<const N: usize> std::ops::Deref for AtomicBitSet<N> {
@@ -0,0 +1,136 @@
+use crate::*;
+use std::ops::{
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Sub, SubAssign,
+};
+
+/// Same as `[usize; N]`, but with the set operators.
+///
+/// `&`, `|`, `^` and `-` are the intersection, union, symmetric difference and difference,
+/// and `!` is the complement within the capacity. The set is `Copy`, so the operators take
+/// their operands by value.
+///
+/// Dereferences to `[usize]`, for the [`BitSet`] and [`BitSetMut`] methods.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{BitArray, BitSet, BitSetMut};
+///
+/// let mut allocated: BitArray<2> = BitArray::new();
+/// let mut permitted: BitArray<2> = BitArray::new();
+/// allocated.insert(3).unwrap();
+/// allocated.insert(70).unwrap();
+/// permitted.insert(70).unwrap();
+///
+/// assert!((allocated & permitted).iter_ones().eq([70]));
+/// assert!((allocated - permitted).iter_ones().eq([3]));
+/// assert_eq!((!allocated).size(), 126);
+///
+/// allocated |= permitted;
+/// assert_eq!(allocated.size(), 2);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitArray<const N: usize> {
+    slots: [usize; N],
+}
+
+impl<const N: usize> BitArray<N> {
+    /// Creates an empty set.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { slots: [0; N] }
+    }
+
+    /// Wraps the given slots.
+    #[inline]
+    pub const fn from_slots(slots: [usize; N]) -> Self {
+        Self { slots }
+    }
+
+    /// Returns the slots of the set.
+    #[inline]
+    pub const fn into_slots(self) -> [usize; N] {
+        self.slots
+    }
+
+    // applies `op` to each pair of slots
+    #[inline]
+    fn merge(&mut self, other: &Self, op: impl Fn(usize, usize) -> usize) {
+        for (slot, &other) in self.slots.iter_mut().zip(&other.slots) {
+            *slot = op(*slot, other);
+        }
+    }
+}
+
+impl<const N: usize> Default for BitArray<N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> std::ops::Deref for BitArray<N> {
+    type Target = [usize];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.slots
+    }
+}
+
+impl<const N: usize> std::ops::DerefMut for BitArray<N> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.slots
+    }
+}
+
+impl<'a, const N: usize> IntoIterator for &'a BitArray<N> {
+    type Item = usize;
+    type IntoIter = Ones<'a, usize>;
+
+    #[inline]
+    fn into_iter(self) -> Ones<'a, usize> {
+        self.iter_ones()
+    }
+}
+
+impl<const N: usize> Not for BitArray<N> {
+    type Output = Self;
+
+    #[inline]
+    fn not(mut self) -> Self {
+        for slot in self.slots.iter_mut() {
+            *slot = !*slot;
+        }
+        self
+    }
+}
+
+macro_rules! impl_bit_array_op {
+    [$($op:ident::$fn:ident, $op_assign:ident::$fn_assign:ident => |$a:ident, $b:ident| $expr:expr;)*] => {$(
+        impl<const N: usize> $op_assign for BitArray<N> {
+            #[inline]
+            fn $fn_assign(&mut self, other: Self) {
+                self.merge(&other, |$a, $b| $expr);
+            }
+        }
+
+        impl<const N: usize> $op for BitArray<N> {
+            type Output = Self;
+
+            #[inline]
+            fn $fn(mut self, other: Self) -> Self {
+                self.$fn_assign(other);
+                self
+            }
+        }
+    )*};
+}
+
+impl_bit_array_op! [
+    BitOr::bitor, BitOrAssign::bitor_assign => |a, b| a | b;
+    BitAnd::bitand, BitAndAssign::bitand_assign => |a, b| a & b;
+    BitXor::bitxor, BitXorAssign::bitxor_assign => |a, b| a ^ b;
+    Sub::sub, SubAssign::sub_assign => |a, b| a & !b;
+];
@@ -0,0 +1,141 @@
+/// Appends variable-length bit fields to a slice of slots.
+///
+/// Fields are written least significant bit first, with the same bit order as the indices
+/// of [`BitSet`](crate::BitSet): bit `i` of the stream is the value `i` of the set.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{BitReader, BitWriter, BitSet};
+///
+/// let mut slots = [0_u32; 2];
+///
+/// let mut writer = BitWriter::new(&mut slots);
+/// writer.write(0b101, 3).unwrap();
+/// writer.write(u64::from(u32::MAX), 32).unwrap();
+/// assert_eq!(writer.position(), 35);
+///
+/// assert!(slots.has(0) && !slots.has(1) && slots.has(2));
+///
+/// let mut reader = BitReader::new(&slots);
+/// assert_eq!(reader.read(3), Some(0b101));
+/// assert_eq!(reader.read(32), Some(u64::from(u32::MAX)));
+/// assert_eq!(reader.read(32), None);
+/// ```
+pub struct BitWriter<'a, T> {
+    slots: &'a mut [T],
+    position: usize,
+}
+
+/// Consumes variable-length bit fields from a slice of slots, see [`BitWriter`].
+pub struct BitReader<'a, T> {
+    slots: &'a [T],
+    position: usize,
+}
+
+impl<'a, T> BitWriter<'a, T> {
+    /// Creates a writer that starts at the first bit of `slots`.
+    #[inline]
+    pub fn new(slots: &'a mut [T]) -> Self {
+        Self { slots, position: 0 }
+    }
+
+    /// Returns the number of bits written so far.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl<'a, T> BitReader<'a, T> {
+    /// Creates a reader that starts at the first bit of `slots`.
+    #[inline]
+    pub fn new(slots: &'a [T]) -> Self {
+        Self { slots, position: 0 }
+    }
+
+    /// Returns the number of bits read so far.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+macro_rules! impl_bit_io {
+    [$($ty:tt),*] => {$(
+        impl BitWriter<'_, $ty> {
+            /// Returns the number of bits that can still be written.
+            #[inline]
+            pub fn remaining(&self) -> usize {
+                self.slots.len() * $ty::BITS as usize - self.position
+            }
+
+            /// Writes the lowest `bits` bits of `value`.
+            ///
+            /// Returns `None`, without writing anything, if there isn't enough room left.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `bits > 64`.
+            pub fn write(&mut self, value: u64, bits: u32) -> Option<()> {
+                assert!(bits <= u64::BITS, "cannot write more than 64 bits at once");
+                if bits as usize > self.remaining() {
+                    return None;
+                }
+                let mut written = 0;
+                while written < bits {
+                    let (slot_idx, offset) = crate::util::split_index(self.position, $ty::BITS);
+                    let n = (bits - written).min($ty::BITS - offset);
+                    let mask = $ty::MAX >> ($ty::BITS - n);
+                    let chunk = (value >> written) as $ty & mask;
+
+                    let slot = &mut self.slots[slot_idx];
+                    *slot = (*slot & !(mask << offset)) | (chunk << offset);
+
+                    written += n;
+                    self.position += n as usize;
+                }
+                Some(())
+            }
+        }
+
+        impl BitReader<'_, $ty> {
+            /// Returns the number of bits that can still be read.
+            #[inline]
+            pub fn remaining(&self) -> usize {
+                self.slots.len() * $ty::BITS as usize - self.position
+            }
+
+            /// Reads the next `bits` bits, as the lowest bits of the returned value.
+            ///
+            /// Returns `None`, without consuming anything, if there aren't enough bits left.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `bits > 64`.
+            pub fn read(&mut self, bits: u32) -> Option<u64> {
+                assert!(bits <= u64::BITS, "cannot read more than 64 bits at once");
+                if bits as usize > self.remaining() {
+                    return None;
+                }
+                let mut value = 0;
+                let mut read = 0;
+                while read < bits {
+                    let (slot_idx, offset) = crate::util::split_index(self.position, $ty::BITS);
+                    let n = (bits - read).min($ty::BITS - offset);
+                    let mask = $ty::MAX >> ($ty::BITS - n);
+                    let chunk = (self.slots[slot_idx] >> offset) & mask;
+
+                    value |= (chunk as u64) << read;
+                    read += n;
+                    self.position += n as usize;
+                }
+                Some(value)
+            }
+        }
+    )*};
+}
+
+impl_bit_io! {
+    u8, u16, u32, u64, usize, u128
+}
@@ -1,9 +1,28 @@
 use crate::*;
 
 /// A trait for reading values from a bit set.
+///
+/// Counts (capacity, size, ...) are returned as `T`. When they don't fit, e.g. the
+/// capacity of `[u8; 32]`, they saturate at the maximum value of `T`.
+///
+/// Slices only hold the values that fit in `T`: the slots of a `[u8; 40]` past the 32nd
+/// one are ignored by the per-value operations (iteration, lookups, draining, ...).
 pub trait BitSet<T> {
+    /// An iterator over the values of the set, see [`iter_ones_in`](BitSet::iter_ones_in).
+    type Ones<'a>: DoubleEndedIterator<Item = T>
+    where
+        Self: 'a;
+
+    /// An iterator over the values missing from the set, see [`iter_zeros_in`](BitSet::iter_zeros_in).
+    type Zeros<'a>: DoubleEndedIterator<Item = T>
+    where
+        Self: 'a;
+
     /// Returns the number of bits that can be stored in the set.
     ///
+    /// Saturates at the maximum value of `T`, so the values at or above it fail
+    /// [`has_strict`](BitSet::has_strict) even if the set holds them.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -27,6 +46,28 @@ pub trait BitSet<T> {
     /// ```
     fn has(&self, _: T) -> bool;
 
+    /// Same as [`has`](BitSet::has), but panics if the value is out of range of the set,
+    /// instead of returning `false`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,should_panic
+    /// use index_set::BitSet;
+    ///
+    /// let bitset: [u32; 4] = [0; 4];
+    /// assert!(!bitset.has_strict(7));
+    /// bitset.has_strict(128); // panics
+    /// ```
+    #[inline]
+    #[track_caller]
+    fn has_strict(&self, value: T) -> bool
+    where
+        T: PartialOrd,
+    {
+        assert!(value < self.capacity(), "value out of range of the set");
+        self.has(value)
+    }
+
     /// Returns `true` if the set is empty.
     ///
     /// # Example
@@ -55,6 +96,716 @@ pub trait BitSet<T> {
     /// assert_eq!(bitset.size(), 1);
     /// ```
     fn size(&self) -> T;
+
+    /// Calls `f` with every value in the set, in ascending order.
+    ///
+    /// Doesn't allocate, and only visits the non-zero slots.
+    /// It is a tight loop over the slots with `trailing_zeros`, without the bookkeeping of
+    /// [`iter_ones`](BitSet::iter_ones), so prefer it for full traversals of dense sets.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut bitset: [u32; 4] = [0; 4];
+    /// bitset.insert(3);
+    /// bitset.insert(70);
+    ///
+    /// let mut values = Vec::new();
+    /// bitset.for_each_one(|value| values.push(value));
+    /// assert_eq!(values, [3, 70]);
+    /// ```
+    #[inline]
+    fn for_each_one(&self, f: impl FnMut(T)) {
+        self.iter_ones().for_each(f)
+    }
+
+    /// Returns the lowest value that is not in the set, or `None` if the set is full.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut bitset: [u32; 2] = [0; 2];
+    /// bitset.insert(0);
+    /// bitset.insert(1);
+    /// assert_eq!(bitset.first_zero(), Some(2));
+    ///
+    /// let full: [u32; 2] = [u32::MAX; 2];
+    /// assert_eq!(full.first_zero(), None);
+    /// ```
+    #[inline]
+    fn first_zero(&self) -> Option<T> {
+        self.iter_zeros().next()
+    }
+
+    /// Returns the number of values that can still be inserted into the set.
+    ///
+    /// Same as `capacity() - size()`, but computed in a single pass.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut bitset: [u32; 4] = [0; 4];
+    /// bitset.insert(0);
+    /// assert_eq!(bitset.free_count(), 127);
+    /// ```
+    #[inline]
+    fn free_count(&self) -> T
+    where
+        T: Word,
+    {
+        T::saturating_from_u128(self.capacity().to_u128() - self.size().to_u128())
+    }
+
+    /// Returns the number of values in the given range that are not in the set.
+    ///
+    /// Like every range-taking method of this crate, any kind of range is accepted,
+    /// and it is clamped to the capacity of the set: unbounded ends cover the whole capacity,
+    /// indices beyond the capacity are ignored, and reversed ranges are empty.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut bitset: [u32; 4] = [0; 4];
+    /// bitset.insert(10);
+    /// bitset.insert(40);
+    /// assert_eq!(bitset.remaining_in(0..32), 31);
+    /// assert_eq!(bitset.remaining_in(100..1000), 28);
+    /// assert_eq!(bitset.remaining_in(..=10), 10);
+    /// assert_eq!(bitset.remaining_in(..), 126);
+    /// ```
+    #[inline]
+    fn remaining_in(&self, range: impl RangeBounds<T>) -> T
+    where
+        T: Word,
+    {
+        T::saturating_from_u128(self.iter_zeros_in(range).count() as u128)
+    }
+
+    /// Returns the number of values in the given range that are in the set.
+    ///
+    /// The range is clamped like in [`remaining_in`](BitSet::remaining_in).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut bitset: [u32; 4] = [0; 4];
+    /// bitset.insert(10);
+    /// bitset.insert(40);
+    /// assert_eq!(bitset.count_in_range(10..=40), 2);
+    /// assert_eq!(bitset.count_in_range(11..40), 0);
+    /// assert_eq!(bitset.count_in_range(20..), 1);
+    /// ```
+    #[inline]
+    fn count_in_range(&self, range: impl RangeBounds<T>) -> T
+    where
+        T: Word,
+    {
+        T::saturating_from_u128(self.iter_ones_in(range).count() as u128)
+    }
+
+    /// Returns the fraction of the capacity that is in use, between `0.0` and `1.0`.
+    ///
+    /// Returns `0.0` if the set has no capacity.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut bitset: [u32; 4] = [0; 4];
+    /// for value in 0..32 {
+    ///     bitset.insert(value);
+    /// }
+    /// assert_eq!(bitset.load_factor(), 0.25);
+    /// ```
+    fn load_factor(&self) -> f64
+    where
+        T: Word,
+    {
+        let capacity = self.capacity().to_u128();
+        if capacity == 0 {
+            return 0.0;
+        }
+        self.size().to_u128() as f64 / capacity as f64
+    }
+
+    /// Estimates the [load factor](BitSet::load_factor) by counting the bits
+    /// of `samples` slots, evenly spread across the set.
+    ///
+    /// Much cheaper than an exact count for very large sets.
+    /// Same as `load_factor()` if `samples` is at least the number of slots.
+    /// The default implementation doesn't sample, and returns the exact load factor.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::BitSet;
+    ///
+    /// // every other slot is full
+    /// let bitset: Vec<u64> = (0..1_000_000).map(|i| if i % 2 == 0 { u64::MAX } else { 0 }).collect();
+    ///
+    /// let estimate = bitset.estimate_load_factor(10_000);
+    /// assert!((estimate - 0.5).abs() < 0.05);
+    /// ```
+    #[inline]
+    fn estimate_load_factor(&self, _samples: usize) -> f64
+    where
+        T: Word,
+    {
+        self.load_factor()
+    }
+
+    /// Estimates the number of values in the set from a sample of
+    /// [`SizeEstimate::SAMPLES`] slots, with an error margin at the given `confidence` level.
+    ///
+    /// Useful for gigantic sets, where an exact count is too expensive.
+    /// The estimate is exact (with a margin of `0`) if the set has no more slots than the sample.
+    /// The default implementation doesn't sample, and returns the exact size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `confidence` is not between `0` and `1` (exclusive).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::BitSet;
+    ///
+    /// let bitset: Vec<u64> = (0..1_000_000_u64).map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15)).collect();
+    /// let exact = bitset.size() as f64;
+    ///
+    /// let estimate = bitset.estimate_size(0.99);
+    /// assert!(estimate.margin > 0.0);
+    /// assert!(estimate.range().contains(&exact));
+    /// ```
+    fn estimate_size(&self, confidence: f64) -> SizeEstimate
+    where
+        T: Word,
+    {
+        let capacity = self.capacity().to_u128() as f64;
+        SizeEstimate::new(self.load_factor(), capacity, capacity, confidence)
+    }
+
+    /// Returns an iterator over the values of the set within the given range, in ascending order.
+    ///
+    /// Slots entirely outside of the range are skipped without being read, and the first and
+    /// the last slot are masked, so iterating a sub-range (e.g. the identifiers of one tenant)
+    /// costs the same as iterating a set of that size.
+    /// The range is clamped like in [`remaining_in`](BitSet::remaining_in).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut bitset: [u32; 4] = [0; 4];
+    /// for value in [1, 31, 32, 70, 100] {
+    ///     bitset.insert(value);
+    /// }
+    /// let values: Vec<_> = bitset.iter_ones_in(31..=70).collect();
+    /// assert_eq!(values, [31, 32, 70]);
+    /// ```
+    fn iter_ones_in(&self, range: impl RangeBounds<T>) -> Self::Ones<'_>;
+
+    /// Returns an iterator over the values of the set, in ascending order.
+    ///
+    /// Empty slots are skipped with a single comparison, and the values of the other slots
+    /// are found with `trailing_zeros`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, SharedBitSet};
+    /// use std::sync::atomic::AtomicU64;
+    ///
+    /// let bitset: [AtomicU64; 4] = Default::default();
+    /// for value in [200, 3, 64] {
+    ///     bitset.insert(value);
+    /// }
+    /// let values: Vec<_> = bitset.iter_ones().collect();
+    /// assert_eq!(values, [3, 64, 200]);
+    /// ```
+    #[inline]
+    fn iter_ones(&self) -> Self::Ones<'_> {
+        self.iter_ones_in(..)
+    }
+
+    /// Returns the lowest value of the set that is strictly greater than `index`.
+    ///
+    /// Lets polling loops and paginated scans resume from where they left off,
+    /// without keeping an iterator around. Only the slots up to the found value are loaded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, SharedBitSet};
+    /// use std::sync::atomic::AtomicUsize;
+    ///
+    /// let bitset: [AtomicUsize; 4] = Default::default();
+    /// for value in [3, 64, 200] {
+    ///     bitset.insert(value);
+    /// }
+    /// let mut cursor = 0;
+    /// let mut page = Vec::new();
+    /// while let Some(value) = bitset.next_set_bit_after(cursor) {
+    ///     page.push(value);
+    ///     cursor = value;
+    /// }
+    /// assert_eq!(page, [3, 64, 200]);
+    /// assert_eq!(bitset.next_set_bit_after(200), None);
+    /// ```
+    #[inline]
+    fn next_set_bit_after(&self, index: T) -> Option<T> {
+        self.iter_ones_in((Bound::Excluded(index), Bound::Unbounded))
+            .next()
+    }
+
+    /// Returns the highest value of the set that is strictly less than `index`.
+    ///
+    /// The backward counterpart of [`next_set_bit_after`](BitSet::next_set_bit_after):
+    /// slots are scanned from `index` downwards, and the value is found with `leading_zeros`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut bitset: [u64; 4] = [0; 4];
+    /// for value in [3, 64, 200] {
+    ///     bitset.insert(value);
+    /// }
+    /// assert_eq!(bitset.prev_set_bit_before(200), Some(64));
+    /// assert_eq!(bitset.prev_set_bit_before(1000), Some(200));
+    /// assert_eq!(bitset.prev_set_bit_before(3), None);
+    /// ```
+    #[inline]
+    fn prev_set_bit_before(&self, index: T) -> Option<T> {
+        self.iter_ones_in(..index).next_back()
+    }
+
+    /// Returns an iterator over the maximal runs of consecutive values of the set,
+    /// as ranges in ascending order.
+    ///
+    /// Handy to convert a set into an interval list, e.g. for display or range-based syscalls.
+    ///
+    /// # Panics
+    ///
+    /// The iterator panics if a run ends with `T::MAX`, as its end can't be represented.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut bitset: [u32; 4] = [0; 4];
+    /// for value in [1, 2, 3, 31, 32, 33, 100] {
+    ///     bitset.insert(value);
+    /// }
+    /// let runs: Vec<_> = bitset.runs().collect();
+    /// assert_eq!(runs, [1..4, 31..34, 100..101]);
+    /// ```
+    #[inline]
+    fn runs(&self) -> Runs<Self::Ones<'_>>
+    where
+        T: Word,
+    {
+        Runs::new(self.iter_ones())
+    }
+
+    /// Returns an iterator over the values of the set, in ascending order, each paired with
+    /// the element at the same index of `companion`.
+    ///
+    /// Covers the common "bitset of occupied slots + parallel `Vec<V>`" pattern
+    /// without indexing (or bounds checks) in the loop.
+    ///
+    /// # Panics
+    ///
+    /// The iterator panics if it reaches a value that is out of bounds of `companion`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut occupied: [u32; 1] = [0];
+    /// let mut names = vec![String::new(); 32];
+    /// for (index, name) in [(2, "a"), (7, "b")] {
+    ///     occupied.insert(index);
+    ///     names[index as usize] = name.to_string();
+    /// }
+    ///
+    /// for (_, name) in occupied.iter_occupied_with(&mut names) {
+    ///     name.make_ascii_uppercase();
+    /// }
+    /// assert_eq!((names[2].as_str(), names[7].as_str()), ("A", "B"));
+    /// ```
+    #[inline]
+    fn iter_occupied_with<'a, V>(
+        &'a self,
+        companion: &'a mut [V],
+    ) -> OccupiedWith<'a, Self::Ones<'a>, V>
+    where
+        T: Word,
+    {
+        OccupiedWith::new(self.iter_ones(), companion)
+    }
+
+    /// Returns an iterator over the values missing from the set within the given range,
+    /// in ascending order.
+    ///
+    /// The range is clamped like in [`remaining_in`](BitSet::remaining_in),
+    /// so the iterator stops at the capacity.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut bitset: [u32; 4] = [u32::MAX; 4];
+    /// bitset.remove(40);
+    /// bitset.remove(99);
+    ///
+    /// let free: Vec<_> = bitset.iter_zeros_in(50..).collect();
+    /// assert_eq!(free, [99]);
+    /// ```
+    fn iter_zeros_in(&self, range: impl RangeBounds<T>) -> Self::Zeros<'_>;
+
+    /// Returns an iterator over the values missing from the set, in ascending order,
+    /// up to the capacity.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, SharedBitSet};
+    /// use std::sync::atomic::AtomicU32;
+    ///
+    /// let bitset: [AtomicU32; 1] = Default::default();
+    /// for value in 2..32 {
+    ///     bitset.insert(value);
+    /// }
+    /// let free: Vec<_> = bitset.iter_zeros().collect();
+    /// assert_eq!(free, [0, 1]);
+    /// ```
+    #[inline]
+    fn iter_zeros(&self) -> Self::Zeros<'_> {
+        self.iter_zeros_in(..)
+    }
+
+    /// Returns `true` if the set contains every value of `values`.
+    ///
+    /// Consecutive values that fall in the same slot share a single load,
+    /// so sorted input is the cheapest.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut permissions: [u32; 2] = [0; 2];
+    /// for value in [1, 3, 4, 40] {
+    ///     permissions.insert(value);
+    /// }
+    /// assert!(permissions.has_all(&[1, 3, 4]));
+    /// assert!(!permissions.has_all(&[1, 2]));
+    /// assert!(!permissions.has_all(&[1, 100]));
+    /// assert!(permissions.has_all(&[]));
+    /// ```
+    #[inline]
+    fn has_all(&self, values: &[T]) -> bool
+    where
+        T: Word,
+    {
+        values.iter().all(|&value| self.has(value))
+    }
+
+    /// Returns `true` if the set contains at least one value of `values`.
+    ///
+    /// Consecutive values that fall in the same slot share a single load,
+    /// so sorted input is the cheapest.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut permissions: [u32; 2] = [0; 2];
+    /// permissions.insert(40);
+    ///
+    /// assert!(permissions.has_any(&[1, 40, 100]));
+    /// assert!(!permissions.has_any(&[1, 2, 100]));
+    /// assert!(!permissions.has_any(&[]));
+    /// ```
+    #[inline]
+    fn has_any(&self, values: &[T]) -> bool
+    where
+        T: Word,
+    {
+        values.iter().any(|&value| self.has(value))
+    }
+
+    /// Writes into `out[i]` whether the set contains `values[i]`.
+    ///
+    /// Consecutive values that fall in the same slot share a single load,
+    /// so sorted input is the cheapest.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` and `out` don't have the same length.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut bitset: [u32; 2] = [0; 2];
+    /// bitset.insert(1);
+    /// bitset.insert(40);
+    ///
+    /// let mut out = [false; 4];
+    /// bitset.gather(&[0, 1, 40, 100], &mut out);
+    /// assert_eq!(out, [false, true, true, false]);
+    /// ```
+    fn gather(&self, values: &[T], out: &mut [bool])
+    where
+        T: Word,
+    {
+        assert_eq!(
+            values.len(),
+            out.len(),
+            "`values` and `out` must have the same length"
+        );
+        for (&value, out) in values.iter().zip(out) {
+            *out = self.has(value);
+        }
+    }
+
+    /// Returns a 64-bit hash of the values of the set, to quickly detect changes
+    /// between replicas without comparing them.
+    ///
+    /// Only depends on the values: sets of any slot type and capacity that hold the
+    /// same values have the same fingerprint. It is not a cryptographic hash.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut a: [u32; 4] = [0; 4];
+    /// let mut b: Vec<u64> = Vec::new();
+    /// assert_eq!(a.fingerprint(), b.fingerprint());
+    ///
+    /// a.insert(70);
+    /// assert_ne!(a.fingerprint(), b.fingerprint());
+    ///
+    /// BitSetMut::insert(&mut b, 70);
+    /// assert_eq!(a.fingerprint(), b.fingerprint());
+    /// ```
+    fn fingerprint(&self) -> u64
+    where
+        T: Word,
+    {
+        let mut hash = 0;
+        self.for_each_one(|value| hash ^= util::hash_index(value.to_u128()));
+        hash
+    }
+
+    /// Returns the size, the lowest and highest values, and the fingerprint of the set,
+    /// computed in a single pass.
+    ///
+    /// Each slot of a shared set is loaded once, so the statistics are consistent with each
+    /// other, unlike separate calls that may each observe different concurrent changes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, SharedBitSet, SnapshotStats};
+    /// use std::sync::atomic::AtomicU64;
+    ///
+    /// let bitset: [AtomicU64; 2] = Default::default();
+    /// bitset.insert(3);
+    /// bitset.insert(70);
+    ///
+    /// let stats = bitset.snapshot_stats();
+    /// assert_eq!((stats.size, stats.first, stats.last), (2, Some(3), Some(70)));
+    /// assert_eq!(stats.fingerprint, bitset.fingerprint());
+    /// ```
+    fn snapshot_stats(&self) -> SnapshotStats<T>
+    where
+        T: Word,
+    {
+        let (mut size, mut first, mut last, mut fingerprint) = (0, None, None, 0);
+        self.for_each_one(|value| {
+            size += 1;
+            first.get_or_insert(value);
+            last = Some(value);
+            fingerprint ^= util::hash_index(value.to_u128());
+        });
+        SnapshotStats {
+            size: T::saturating_from_u128(size),
+            first,
+            last,
+            fingerprint,
+        }
+    }
+
+    /// Returns `true` if every value of the set is in `other`.
+    ///
+    /// Stops at the first value missing from `other`.
+    /// Slices compare whole slots if `other` is a slice too.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::BitSet;
+    ///
+    /// let requested: [u64; 1] = [0b0101];
+    /// let granted: Vec<u64> = vec![0b1101, 0b1];
+    ///
+    /// assert!(requested.is_subset(granted.as_slice()));
+    /// assert!(!granted.is_subset(&requested[..]));
+    /// ```
+    fn is_subset(&self, other: &(impl BitSet<T> + ?Sized)) -> bool {
+        self.iter_ones().all(|value| other.has(value))
+    }
+
+    /// Returns `true` if every value of `other` is in the set.
+    ///
+    /// Stops at the first value of `other` missing from the set.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::BitSet;
+    ///
+    /// let granted: [u64; 2] = [0b1101, 0b1];
+    /// let requested: Vec<u64> = vec![0b0101];
+    ///
+    /// assert!(granted.is_superset(requested.as_slice()));
+    /// assert!(!requested.is_superset(&granted[..]));
+    /// ```
+    fn is_superset(&self, other: &(impl BitSet<T> + ?Sized)) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Returns `true` if the set and `other` have a value in common.
+    ///
+    /// Stops at the first common value, without computing the intersection.
+    /// Slices compare whole slots if `other` is a slice too.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::BitSet;
+    ///
+    /// let locked: [u64; 2] = [0b0011, 0b1];
+    ///
+    /// assert!(!locked.intersects(&[0b1100_u64][..]));
+    /// assert!(locked.intersects(&[0, 0b1_u64][..]));
+    /// ```
+    fn intersects(&self, other: &(impl BitSet<T> + ?Sized)) -> bool {
+        self.iter_ones().any(|value| other.has(value))
+    }
+
+    /// Returns `true` if the set and `other` have no value in common.
+    ///
+    /// Stops at the first common value, without computing the intersection.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::BitSet;
+    ///
+    /// let locked: [u64; 2] = [0b0011, 0b1];
+    /// let wanted: Vec<u64> = vec![0b1100];
+    ///
+    /// assert!(locked.is_disjoint(wanted.as_slice()));
+    /// assert!(!locked.is_disjoint(&[0b0110_u64][..]));
+    /// ```
+    #[inline]
+    fn is_disjoint(&self, other: &(impl BitSet<T> + ?Sized)) -> bool {
+        !self.intersects(other)
+    }
+
+    /// Returns a uniformly random value of the set, or `None` if the set is empty.
+    ///
+    /// Slices find the value by skipping whole slots with their popcount.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::BitSet;
+    /// use rand::{SeedableRng, rngs::SmallRng};
+    ///
+    /// let mut rng = SmallRng::seed_from_u64(7);
+    /// let live: [u64; 2] = [0b1010, 0b1];
+    ///
+    /// let victim = live.sample_one(&mut rng).unwrap();
+    /// assert!([1, 3, 64].contains(&victim));
+    /// assert_eq!([0_u64; 2].sample_one(&mut rng), None);
+    /// ```
+    #[cfg(feature = "rand")]
+    fn sample_one(&self, rng: &mut impl rand::Rng) -> Option<T>
+    where
+        T: Word,
+    {
+        let size = self.size().to_u128() as usize;
+        if size == 0 {
+            return None;
+        }
+        self.iter_ones().nth(rng.gen_range(0..size))
+    }
+
+    /// Returns `k` distinct values of the set chosen uniformly at random, in ascending order.
+    ///
+    /// Returns every value of the set if it has fewer than `k` values.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::BitSet;
+    /// use rand::{SeedableRng, rngs::SmallRng};
+    ///
+    /// let mut rng = SmallRng::seed_from_u64(7);
+    /// let live: [u64; 2] = [0b1010, 0b1];
+    ///
+    /// let victims = live.sample(&mut rng, 2);
+    /// assert_eq!(victims.len(), 2);
+    /// assert!(victims.iter().all(|victim| live.has(*victim)));
+    /// assert_eq!(live.sample(&mut rng, 10), [1, 3, 64]);
+    /// ```
+    #[cfg(feature = "rand")]
+    fn sample(&self, rng: &mut impl rand::Rng, k: usize) -> Vec<T>
+    where
+        T: Word,
+    {
+        let size = self.size().to_u128() as usize;
+        let mut ranks = rand::seq::index::sample(rng, size, k.min(size)).into_vec();
+        ranks.sort_unstable();
+
+        let mut ones = self.iter_ones().enumerate();
+        ranks
+            .into_iter()
+            .filter_map(|rank| ones.find(|(i, _)| *i == rank).map(|(_, value)| value))
+            .collect()
+    }
+
+    /// Returns the slots of the set, if it is backed by slots, so that the operations
+    /// between two sets can work on whole words instead of single values.
+    #[doc(hidden)]
+    #[inline]
+    fn words(&self) -> Option<impl Iterator<Item = T>> {
+        None::<std::iter::Empty<T>>
+    }
 }
 
 macro_rules! impl_deref {
@@ -63,6 +814,9 @@ macro_rules! impl_deref {
         where
             Set: BitSet<T> + ?Sized,
         {
+            type Ones<'a> = Set::Ones<'a> where Self: 'a;
+            type Zeros<'a> = Set::Zeros<'a> where Self: 'a;
+
             #[inline]
             fn capacity(&self) -> T {
                 BitSet::capacity(&**self)
@@ -82,82 +836,327 @@ macro_rules! impl_deref {
             fn size(&self) -> T {
                 BitSet::size(&**self)
             }
-        }
-    )*}
-}
 
-impl_deref! {
-    &Set, Box<Set>
-}
+            #[inline]
+            fn for_each_one(&self, f: impl FnMut(T)) {
+                BitSet::for_each_one(&**self, f)
+            }
 
-macro_rules! impl_bit_set {
-    [$($ty:tt),*] => {$(
-        impl BitSet<$ty> for [$ty] {
             #[inline]
-            fn capacity(&self) -> $ty {
-                self.len() as $ty * $ty::BITS as $ty
+            fn first_zero(&self) -> Option<T> {
+                BitSet::first_zero(&**self)
             }
 
             #[inline]
-            fn has(&self, index: $ty) -> bool {
-                 let slot_idx = match usize::try_from(index / $ty::BITS as $ty) {
-                    Ok(slot_idx) => slot_idx,
-                    Err(_) => return false,
-                };
-                let mask = 1 << (index % $ty::BITS as $ty);
-                self.get(slot_idx).is_some_and(|slot| slot & mask != 0)
+            fn free_count(&self) -> T
+            where
+                T: Word,
+            {
+                BitSet::free_count(&**self)
             }
 
             #[inline]
-            fn is_empty(&self) -> bool {
-                self.iter().all(|&slot| slot == 0)
+            fn remaining_in(&self, range: impl RangeBounds<T>) -> T
+            where
+                T: Word,
+            {
+                BitSet::remaining_in(&**self, range)
             }
 
             #[inline]
-            fn size(&self) -> $ty {
-                self.iter().map(|slot| slot.count_ones() as $ty).sum()
+            fn count_in_range(&self, range: impl RangeBounds<T>) -> T
+            where
+                T: Word,
+            {
+                BitSet::count_in_range(&**self, range)
             }
-        }
-    )*};
-}
 
-macro_rules! impl_atomic_bit_set {
-    [$($ty:tt for $target: ty)*] => {$(
-        impl BitSet<$ty> for [$target] {
-            fn capacity(&self) -> $ty {
-                self.len() as $ty * $ty::BITS as $ty
+            #[inline]
+            fn load_factor(&self) -> f64
+            where
+                T: Word,
+            {
+                BitSet::load_factor(&**self)
             }
 
             #[inline]
-            fn has(&self, index: $ty) -> bool {
-                let slot_idx = match usize::try_from(index / $ty::BITS as $ty) {
-                    Ok(slot_idx) => slot_idx,
-                    Err(_) => return false,
-                };
-                let mask = 1 << (index % $ty::BITS as $ty);
-                self.get(slot_idx)
-                    .is_some_and(|slot| slot.load(Ordering::Acquire) & mask != 0)
+            fn estimate_load_factor(&self, samples: usize) -> f64
+            where
+                T: Word,
+            {
+                BitSet::estimate_load_factor(&**self, samples)
             }
 
-            fn is_empty(&self) -> bool {
-                self.iter().all(|slot| slot.load(Ordering::Acquire) == 0)
+            #[inline]
+            fn estimate_size(&self, confidence: f64) -> SizeEstimate
+            where
+                T: Word,
+            {
+                BitSet::estimate_size(&**self, confidence)
+            }
+
+            #[inline]
+            fn iter_ones_in(&self, range: impl RangeBounds<T>) -> Self::Ones<'_> {
+                BitSet::iter_ones_in(&**self, range)
+            }
+
+            #[inline]
+            fn iter_zeros_in(&self, range: impl RangeBounds<T>) -> Self::Zeros<'_> {
+                BitSet::iter_zeros_in(&**self, range)
+            }
+
+            #[inline]
+            fn has_all(&self, values: &[T]) -> bool
+            where
+                T: Word,
+            {
+                BitSet::has_all(&**self, values)
+            }
+
+            #[inline]
+            fn has_any(&self, values: &[T]) -> bool
+            where
+                T: Word,
+            {
+                BitSet::has_any(&**self, values)
+            }
+
+            #[inline]
+            fn gather(&self, values: &[T], out: &mut [bool])
+            where
+                T: Word,
+            {
+                BitSet::gather(&**self, values, out)
+            }
+
+            #[inline]
+            fn is_subset(&self, other: &(impl BitSet<T> + ?Sized)) -> bool {
+                BitSet::is_subset(&**self, other)
+            }
+
+            #[inline]
+            fn intersects(&self, other: &(impl BitSet<T> + ?Sized)) -> bool {
+                BitSet::intersects(&**self, other)
             }
 
-            fn size(&self) -> $ty {
-                self.iter()
-                    .map(|slot| slot.load(Ordering::Acquire).count_ones() as $ty)
-                    .sum()
+            #[inline]
+            fn words(&self) -> Option<impl Iterator<Item = T>> {
+                BitSet::words(&**self)
             }
         }
-    )*};
+    )*}
 }
 
-impl_bit_set! {
-    u16, u32, u64, usize, u128
+impl_deref! {
+    &Set, Box<Set>
+}
+
+impl<S: Slot> BitSet<S::Word> for [S] {
+    type Ones<'a>
+        = crate::Ones<'a, S>
+    where
+        S: 'a;
+    type Zeros<'a>
+        = crate::Zeros<'a, S>
+    where
+        S: 'a;
+
+    #[inline]
+    fn capacity(&self) -> S::Word {
+        S::Word::saturating_from_u128(bits(self))
+    }
+
+    #[inline]
+    fn has(&self, index: S::Word) -> bool {
+        let Some((slot_idx, bit)) = index.split_index() else {
+            return false;
+        };
+        self.get(slot_idx)
+            .is_some_and(|slot| slot.load().and(S::Word::bit(bit)) != S::Word::ZERO)
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        util::scan(util::addressable(self)).all(|slot| slot.load() == S::Word::ZERO)
+    }
+
+    #[inline]
+    fn size(&self) -> S::Word {
+        let size: usize = util::scan(util::addressable(self))
+            .map(|slot| slot.load().count_ones() as usize)
+            .sum();
+        S::Word::saturating_from_u128(size as u128)
+    }
+
+    fn for_each_one(&self, mut f: impl FnMut(S::Word)) {
+        for (slot_idx, slot) in util::scan(util::addressable(self)).enumerate() {
+            let mut bits = slot.load();
+            while bits != S::Word::ZERO {
+                let bit = bits.trailing_zeros();
+                f(S::Word::from_index(slot_idx, bit));
+                bits = bits.without_bit(bit);
+            }
+        }
+    }
+
+    fn first_zero(&self) -> Option<S::Word> {
+        let slots = util::addressable(self);
+        slots.iter().enumerate().find_map(|(slot_idx, slot)| {
+            let slot = slot.load();
+            (slot != S::Word::MAX)
+                .then(|| S::Word::from_index(slot_idx, slot.not().trailing_zeros()))
+        })
+    }
+
+    #[inline]
+    fn free_count(&self) -> S::Word {
+        let free: usize = util::scan(util::addressable(self))
+            .map(|slot| slot.load().not().count_ones() as usize)
+            .sum();
+        S::Word::saturating_from_u128(free as u128)
+    }
+
+    fn remaining_in(&self, range: impl RangeBounds<S::Word>) -> S::Word {
+        let (start, end) = util::bit_range(&range, bits(self), S::Word::to_u128);
+        let remaining: usize = util::slot_ranges(start, end, S::Word::BITS)
+            .map(|(slot_idx, start, end)| {
+                let mask = S::Word::mask(start, end);
+                self[slot_idx].load().not().and(mask).count_ones() as usize
+            })
+            .sum();
+        S::Word::saturating_from_u128(remaining as u128)
+    }
+
+    fn count_in_range(&self, range: impl RangeBounds<S::Word>) -> S::Word {
+        let (start, end) = util::bit_range(&range, bits(self), S::Word::to_u128);
+        let count: usize = util::slot_ranges(start, end, S::Word::BITS)
+            .map(|(slot_idx, start, end)| {
+                let mask = S::Word::mask(start, end);
+                self[slot_idx].load().and(mask).count_ones() as usize
+            })
+            .sum();
+        S::Word::saturating_from_u128(count as u128)
+    }
+
+    fn load_factor(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        BitSet::size(self).to_u128() as f64 / bits(self) as f64
+    }
+
+    fn estimate_load_factor(&self, samples: usize) -> f64 {
+        let mut sampled = 0;
+        let count: u64 = util::sample_slots(self.len(), samples)
+            .map(|slot_idx| {
+                sampled += 1;
+                self[slot_idx].load().count_ones() as u64
+            })
+            .sum();
+
+        if sampled == 0 {
+            return 0.0;
+        }
+        count as f64 / (sampled as f64 * S::Word::BITS as f64)
+    }
+
+    fn estimate_size(&self, confidence: f64) -> SizeEstimate {
+        let samples = SizeEstimate::SAMPLES.min(self.len());
+        SizeEstimate::new(
+            BitSet::estimate_load_factor(self, samples),
+            samples as f64 * S::Word::BITS as f64,
+            bits(self) as f64,
+            confidence,
+        )
+    }
+
+    #[inline]
+    fn iter_ones_in(&self, range: impl RangeBounds<S::Word>) -> Self::Ones<'_> {
+        crate::Ones::new(self, util::bit_range(&range, bits(self), S::Word::to_u128))
+    }
+
+    #[inline]
+    fn iter_zeros_in(&self, range: impl RangeBounds<S::Word>) -> Self::Zeros<'_> {
+        crate::Zeros::new(self, util::bit_range(&range, bits(self), S::Word::to_u128))
+    }
+
+    #[inline]
+    fn has_all(&self, values: &[S::Word]) -> bool {
+        util::lookup(self, values).all(|has| has == Some(true))
+    }
+
+    #[inline]
+    fn has_any(&self, values: &[S::Word]) -> bool {
+        util::lookup(self, values).any(|has| has == Some(true))
+    }
+
+    fn gather(&self, values: &[S::Word], out: &mut [bool]) {
+        assert_eq!(
+            values.len(),
+            out.len(),
+            "`values` and `out` must have the same length"
+        );
+        for (out, has) in out.iter_mut().zip(util::lookup(self, values)) {
+            *out = has == Some(true);
+        }
+    }
+
+    fn is_subset(&self, other: &(impl BitSet<S::Word> + ?Sized)) -> bool {
+        let Some(mut words) = other.words() else {
+            return self.iter_ones().all(|value| other.has(value));
+        };
+        // the missing slots of `other` are empty
+        self.iter().all(|slot| {
+            let word = words.next().unwrap_or(S::Word::ZERO);
+            slot.load().and(word.not()) == S::Word::ZERO
+        })
+    }
+
+    fn intersects(&self, other: &(impl BitSet<S::Word> + ?Sized)) -> bool {
+        let Some(words) = other.words() else {
+            return self.iter_ones().any(|value| other.has(value));
+        };
+        self.iter()
+            .zip(words)
+            .any(|(slot, word)| slot.load().and(word) != S::Word::ZERO)
+    }
+
+    #[inline]
+    fn words(&self) -> Option<impl Iterator<Item = S::Word>> {
+        Some(self.iter().map(Slot::load))
+    }
+
+    #[cfg(feature = "rand")]
+    fn sample_one(&self, rng: &mut impl rand::Rng) -> Option<S::Word> {
+        let slots = util::addressable(self);
+        let size: usize = slots
+            .iter()
+            .map(|slot| slot.load().count_ones() as usize)
+            .sum();
+        if size == 0 {
+            return None;
+        }
+        // skips whole slots until the one holding the value of that rank
+        let mut rank = rng.gen_range(0..size);
+        for (slot_idx, slot) in slots.iter().enumerate() {
+            let mut word = slot.load();
+            let count = word.count_ones() as usize;
+            if rank >= count {
+                rank -= count;
+                continue;
+            }
+            for _ in 0..rank {
+                word = word.without_bit(word.trailing_zeros());
+            }
+            return Some(S::Word::from_index(slot_idx, word.trailing_zeros()));
+        }
+        // values removed concurrently from a shared set
+        None
+    }
 }
 
-impl_atomic_bit_set! {
-    u32 for AtomicU32
-    u64 for AtomicU64
-    usize for AtomicUsize
+// number of bits of `slots` that hold values, see `util::addressable`
+#[inline]
+fn bits<S: Slot>(slots: &[S]) -> u128 {
+    util::addressable(slots).len() as u128 * S::Word::BITS as u128
 }
@@ -1,5 +1,12 @@
+use crate::*;
+
 /// A trait for a mutate values in a bit set.
 pub trait BitSetMut<T> {
+    /// An iterator that removes the values of the set, see [`drain`](BitSetMut::drain).
+    type Drain<'a>: Iterator<Item = T>
+    where
+        Self: 'a;
+
     /// Clears the set
     ///
     /// # Example
@@ -32,6 +39,52 @@ pub trait BitSetMut<T> {
     /// ```
     fn insert(&mut self, _: T) -> Result<bool, usize>;
 
+    /// Inserts the value into the set, failing if it was already set.
+    ///
+    /// Unlike [`insert`](BitSetMut::insert), a double insertion can't go unnoticed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSetMut, InsertError};
+    ///
+    /// let mut bitset: [u32; 4] = [0; 4];
+    /// assert_eq!(bitset.insert_new(7), Ok(()));
+    /// assert_eq!(bitset.insert_new(7), Err(InsertError::AlreadyPresent));
+    /// assert_eq!(bitset.insert_new(128), Err(InsertError::OutOfRange));
+    /// ```
+    #[inline]
+    fn insert_new(&mut self, value: T) -> Result<(), InsertError> {
+        match self.insert(value) {
+            Ok(false) => Ok(()),
+            Ok(true) => Err(InsertError::AlreadyPresent),
+            Err(_) => Err(InsertError::OutOfRange),
+        }
+    }
+
+    /// Same as [`insert`](BitSetMut::insert), but panics if the set cannot hold the value,
+    /// for callers where an out-of-range value is always a logic bug.
+    ///
+    /// Returns `true` if the value was already set.
+    ///
+    /// # Example
+    ///
+    /// ```rust,should_panic
+    /// use index_set::BitSetMut;
+    ///
+    /// let mut bitset: [u32; 4] = [0; 4];
+    /// assert!(!bitset.insert_strict(7));
+    /// bitset.insert_strict(128); // panics
+    /// ```
+    #[inline]
+    #[track_caller]
+    fn insert_strict(&mut self, value: T) -> bool {
+        match self.insert(value) {
+            Ok(had) => had,
+            Err(_) => panic!("value out of range of the set"),
+        }
+    }
+
     /// Removes the value from the set
     ///
     /// Returns `Some(true)` if the value was already set.
@@ -50,32 +103,313 @@ pub trait BitSetMut<T> {
     /// assert_eq!(bitset.has(42), false);
     /// ```
     fn remove(&mut self, _: T) -> Option<bool>;
-}
 
-impl<T> BitSetMut<T> for Vec<T>
-where
-    T: Default + Clone,
-    [T]: BitSetMut<T>,
-{
+    /// Same as [`remove`](BitSetMut::remove), but panics if the set cannot hold the value.
+    ///
+    /// Returns `true` if the value was set.
     #[inline]
-    fn clear(&mut self) {
-        Vec::clear(self);
+    #[track_caller]
+    fn remove_strict(&mut self, value: T) -> bool {
+        match self.remove(value) {
+            Some(had) => had,
+            None => panic!("value out of range of the set"),
+        }
     }
 
-    fn insert(&mut self, value: T) -> Result<bool, usize> {
-        match self.as_mut_slice().insert(value.clone()) {
-            Ok(has) => Ok(has),
-            Err(slot_index) => {
-                self.resize(slot_index + 1, T::default());
-                self.as_mut_slice().insert(value)
+    /// Removes every value for which `predicate` returns `true`.
+    /// Only values present in the set are visited.
+    ///
+    /// Returns the number of removed values.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut bitset: [u32; 4] = [0; 4];
+    /// bitset.insert(1);
+    /// bitset.insert(2);
+    /// bitset.insert(3);
+    ///
+    /// assert_eq!(bitset.clear_where(|value| value % 2 == 1), 2);
+    /// assert_eq!(bitset.size(), 1);
+    /// assert!(bitset.has(2));
+    /// ```
+    fn clear_where(&mut self, predicate: impl FnMut(T) -> bool) -> T;
+
+    /// Merges `words` into the slots starting at the bit `offset`, according to `policy`.
+    ///
+    /// Used to assemble a set from fragments, e.g. the per-shard output of [`util::export_chunks`].
+    ///
+    /// Returns `Err(usize)` if the set cannot hold the fragment, where `usize` is the index
+    /// of its last slot. Nothing is written in that case.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is not a multiple of the number of bits of a slot.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut, MergePolicy};
+    ///
+    /// let mut bitset: [u32; 4] = [0; 4];
+    /// bitset.insert(1);
+    /// bitset.insert(64);
+    ///
+    /// bitset.import_at(64, &[0b110, 0b1], MergePolicy::Or).unwrap();
+    /// assert!(bitset.has(1) && bitset.has(64) && bitset.has(65) && bitset.has(66) && bitset.has(96));
+    ///
+    /// bitset.import_at(64, &[0b10], MergePolicy::And).unwrap();
+    /// assert_eq!(bitset.size(), 3);
+    ///
+    /// assert_eq!(bitset.import_at(96, &[0, 0], MergePolicy::Overwrite), Err(4));
+    /// ```
+    fn import_at(&mut self, offset: T, words: &[T], policy: MergePolicy) -> Result<(), usize>;
+
+    /// Inserts every value of `other` into the set, growing it if it's a `Vec`.
+    ///
+    /// Returns `Err(usize)` at the first value the set cannot hold, where `usize` is the index
+    /// of its slot. The smaller values of `other` are inserted in that case.
+    ///
+    /// Slices and `Vec`s merge whole slots if `other` is a slice too, and go value by
+    /// value otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut bitset: Vec<u32> = vec![0b1];
+    /// bitset.union_with(&[0b10_u32, 0, 0b1][..]).unwrap();
+    /// assert_eq!(bitset.iter_ones().collect::<Vec<_>>(), [0, 1, 64]);
+    ///
+    /// let mut fixed = [0_u32; 1];
+    /// assert_eq!(fixed.union_with(&bitset[..]), Err(2));
+    /// assert_eq!(fixed.size(), 2);
+    /// ```
+    fn union_with(&mut self, other: &(impl BitSet<T> + ?Sized)) -> Result<(), usize> {
+        for value in other.iter_ones() {
+            self.insert(value)?;
+        }
+        Ok(())
+    }
+
+    /// Removes every value that isn't in `other`, including the values past its capacity.
+    ///
+    /// Slices and `Vec`s mask whole slots if `other` is a slice too.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut allocated: [u32; 2] = [0b1011, 0b1];
+    /// let permitted: [u32; 1] = [0b0110];
+    ///
+    /// allocated.intersect_with(&permitted[..]);
+    /// assert_eq!(allocated, [0b0010, 0]);
+    /// ```
+    #[inline]
+    fn intersect_with(&mut self, other: &(impl BitSet<T> + ?Sized)) {
+        self.clear_where(|value| !other.has(value));
+    }
+
+    /// Removes every value that is in `other`.
+    ///
+    /// Slices and `Vec`s mask whole slots if `other` is a slice too.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut candidates: [u32; 2] = [0b1111, 0b1];
+    /// let blocked: [u32; 1] = [0b0101];
+    ///
+    /// candidates.difference_with(&blocked[..]);
+    /// assert_eq!(candidates, [0b1010, 0b1]);
+    /// ```
+    #[inline]
+    fn difference_with(&mut self, other: &(impl BitSet<T> + ?Sized)) {
+        self.clear_where(|value| other.has(value));
+    }
+
+    /// Flips every value up to the [`capacity`](BitSet::capacity) of the set,
+    /// without changing the capacity.
+    ///
+    /// Values past the capacity are never inserted, e.g. the padding bits of a [`FixedBitSet`].
+    /// Slices flip whole slots in place, and the default implementation toggles the values
+    /// one by one, without allocating.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut, FixedBitSet};
+    ///
+    /// let mut bitset: [u32; 2] = [0b1011, u32::MAX];
+    /// bitset.complement();
+    /// assert_eq!(bitset, [!0b1011, 0]);
+    ///
+    /// let mut fixed = FixedBitSet::new(10);
+    /// fixed.insert(3).unwrap();
+    /// BitSetMut::complement(&mut fixed);
+    /// assert_eq!(fixed.size(), 9);
+    /// ```
+    fn complement(&mut self)
+    where
+        Self: BitSet<T>,
+        T: Word,
+    {
+        if self.complement_in_place() {
+            return;
+        }
+        // toggles the values in place, below the capacity only
+        let capacity = self.capacity().to_u128();
+        for value in (0..capacity).map(T::from_u128) {
+            if self.has(value) {
+                self.remove(value);
+            } else {
+                let _ = self.insert(value);
             }
         }
     }
 
+    /// Flips every value up to the capacity of the set and returns `true`, if the set can
+    /// flip whole slots in place, so that [`complement`](BitSetMut::complement) reaches it
+    /// through a `Box` or a `&mut` too.
+    #[doc(hidden)]
     #[inline]
-    fn remove(&mut self, value: T) -> Option<bool> {
-        self.as_mut_slice().remove(value)
+    fn complement_in_place(&mut self) -> bool
+    where
+        T: Word,
+    {
+        false
     }
+
+    /// Returns an iterator that removes the values of the set as it yields them, in ascending order.
+    ///
+    /// Each value is yielded exactly once. The set is empty once the iterator is dropped,
+    /// even if it wasn't fully consumed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut};
+    ///
+    /// let mut pending: [u32; 4] = [0; 4];
+    /// pending.insert(40);
+    /// pending.insert(3);
+    ///
+    /// let work: Vec<_> = pending.drain().collect();
+    /// assert_eq!(work, [3, 40]);
+    /// assert!(BitSet::is_empty(&pending[..]));
+    /// ```
+    fn drain(&mut self) -> Self::Drain<'_>;
+}
+
+// the methods of the growable vectors, that go through their slice and grow it on `Err`
+macro_rules! impl_vec {
+    () => {
+        type Drain<'a>
+            = <[T] as BitSetMut<T>>::Drain<'a>
+        where
+            Self: 'a;
+
+        #[inline]
+        fn clear(&mut self) {
+            // the inherent `clear` of the vector
+            Self::clear(self);
+        }
+
+        fn insert(&mut self, value: T) -> Result<bool, usize> {
+            match self.as_mut_slice().insert(value.clone()) {
+                Ok(has) => Ok(has),
+                Err(slot_index) => {
+                    self.resize(slot_index + 1, T::default());
+                    self.as_mut_slice().insert(value)
+                }
+            }
+        }
+
+        #[inline]
+        fn remove(&mut self, value: T) -> Option<bool> {
+            self.as_mut_slice().remove(value)
+        }
+
+        #[inline]
+        fn clear_where(&mut self, predicate: impl FnMut(T) -> bool) -> T {
+            self.as_mut_slice().clear_where(predicate)
+        }
+
+        fn import_at(&mut self, offset: T, words: &[T], policy: MergePolicy) -> Result<(), usize> {
+            match self.as_mut_slice().import_at(offset.clone(), words, policy) {
+                Ok(()) => Ok(()),
+                Err(slot_index) => {
+                    self.resize(slot_index + 1, T::default());
+                    self.as_mut_slice().import_at(offset, words, policy)
+                }
+            }
+        }
+
+        fn union_with(&mut self, other: &(impl BitSet<T> + ?Sized)) -> Result<(), usize> {
+            if self.as_mut_slice().union_with(other).is_ok() {
+                return Ok(());
+            }
+            // grows to the slot of the last value of `other`, then merges again
+            if let Some(last) = other.iter_ones().next_back() {
+                BitSetMut::insert(self, last)?;
+            }
+            self.as_mut_slice().union_with(other)
+        }
+
+        #[inline]
+        fn intersect_with(&mut self, other: &(impl BitSet<T> + ?Sized)) {
+            self.as_mut_slice().intersect_with(other)
+        }
+
+        #[inline]
+        fn difference_with(&mut self, other: &(impl BitSet<T> + ?Sized)) {
+            self.as_mut_slice().difference_with(other)
+        }
+
+        #[inline]
+        fn drain(&mut self) -> Self::Drain<'_> {
+            self.as_mut_slice().drain()
+        }
+    };
+}
+
+impl<T> BitSetMut<T> for Vec<T>
+where
+    T: Default + Clone,
+    [T]: BitSetMut<T>,
+{
+    impl_vec!();
+}
+
+/// Same as `Vec<T>`, but the slots live in a custom allocator (e.g. an arena or huge pages).
+///
+/// Available with the `allocator-api2` feature.
+///
+/// # Example
+///
+/// ```rust
+/// use allocator_api2::{alloc::Global, vec::Vec};
+/// use index_set::{BitSet, BitSetMut};
+///
+/// let mut bitvec: Vec<u32, Global> = Vec::new_in(Global);
+/// BitSetMut::insert(&mut bitvec, 42);
+/// assert!(bitvec.has(42));
+/// ```
+#[cfg(feature = "allocator-api2")]
+impl<T, A> BitSetMut<T> for allocator_api2::vec::Vec<T, A>
+where
+    T: Default + Clone,
+    A: allocator_api2::alloc::Allocator,
+    [T]: BitSetMut<T>,
+{
+    impl_vec!();
 }
 
 macro_rules! impl_deref_mut {
@@ -84,6 +418,8 @@ macro_rules! impl_deref_mut {
         where
             Set: BitSetMut<T> + ?Sized,
         {
+            type Drain<'a> = Set::Drain<'a> where Self: 'a;
+
             #[inline]
             fn clear(&mut self) {
                 BitSetMut::clear(&mut **self)
@@ -98,6 +434,44 @@ macro_rules! impl_deref_mut {
             fn remove(&mut self, index: T) -> Option<bool> {
                 BitSetMut::remove(&mut **self, index)
             }
+
+            #[inline]
+            fn clear_where(&mut self, predicate: impl FnMut(T) -> bool) -> T {
+                BitSetMut::clear_where(&mut **self, predicate)
+            }
+
+            #[inline]
+            fn import_at(&mut self, offset: T, words: &[T], policy: MergePolicy) -> Result<(), usize> {
+                BitSetMut::import_at(&mut **self, offset, words, policy)
+            }
+
+            #[inline]
+            fn union_with(&mut self, other: &(impl BitSet<T> + ?Sized)) -> Result<(), usize> {
+                BitSetMut::union_with(&mut **self, other)
+            }
+
+            #[inline]
+            fn intersect_with(&mut self, other: &(impl BitSet<T> + ?Sized)) {
+                BitSetMut::intersect_with(&mut **self, other)
+            }
+
+            #[inline]
+            fn difference_with(&mut self, other: &(impl BitSet<T> + ?Sized)) {
+                BitSetMut::difference_with(&mut **self, other)
+            }
+
+            #[inline]
+            fn complement_in_place(&mut self) -> bool
+            where
+                T: Word,
+            {
+                BitSetMut::complement_in_place(&mut **self)
+            }
+
+            #[inline]
+            fn drain(&mut self) -> Self::Drain<'_> {
+                BitSetMut::drain(&mut **self)
+            }
         }
     )*}
 }
@@ -106,40 +480,123 @@ impl_deref_mut! {
     &mut Set, Box<Set>
 }
 
-macro_rules! impl_bit_set_mut {
-    [$($ty:tt),*] => {$(
-        impl BitSetMut<$ty> for [$ty] {
-            fn clear(&mut self) {
-                for slot in self {
-                    *slot = 0;
+impl<T: Word> BitSetMut<T> for [T] {
+    type Drain<'a>
+        = crate::Drain<'a, T>
+    where
+        T: 'a;
+
+    fn clear(&mut self) {
+        for slot in self {
+            *slot = T::ZERO;
+        }
+    }
+
+    #[inline]
+    fn insert(&mut self, index: T) -> Result<bool, usize> {
+        let (slot_idx, bit) = index.split_index().unwrap();
+        let mask = T::bit(bit);
+        let slot = self.get_mut(slot_idx).ok_or(slot_idx)?;
+
+        let old_value = slot.and(mask) != T::ZERO;
+        *slot = slot.or(mask);
+        Ok(old_value)
+    }
+
+    #[inline]
+    fn remove(&mut self, index: T) -> Option<bool> {
+        let (slot_idx, bit) = index.split_index()?;
+        let slot = self.get_mut(slot_idx)?;
+
+        let old_value = slot.and(T::bit(bit)) != T::ZERO;
+        *slot = slot.without_bit(bit);
+        Some(old_value)
+    }
+
+    fn clear_where(&mut self, mut predicate: impl FnMut(T) -> bool) -> T {
+        let mut cleared = 0_usize;
+        let len = util::addressable_len::<T>(self.len());
+        for (slot_idx, slot) in self[..len].iter_mut().enumerate() {
+            let mut bits = *slot;
+            while bits != T::ZERO {
+                let bit = bits.trailing_zeros();
+                bits = bits.without_bit(bit);
+                if predicate(T::from_index(slot_idx, bit)) {
+                    *slot = slot.without_bit(bit);
+                    cleared += 1;
                 }
             }
+        }
+        T::saturating_from_u128(cleared as u128)
+    }
 
-            #[inline]
-            fn insert(&mut self, index: $ty) -> Result<bool, usize> {
-                let slot_idx = usize::try_from(index / $ty::BITS as $ty).unwrap();
-                let mask = 1 << (index % $ty::BITS as $ty);
-                let slot = self.get_mut(slot_idx).ok_or(slot_idx)?;
+    fn import_at(&mut self, offset: T, words: &[T], policy: MergePolicy) -> Result<(), usize> {
+        let (slot_idx, bit) = offset.split_index().unwrap();
+        assert!(bit == 0, "offset must be a multiple of {} bits", T::BITS);
+        if words.is_empty() {
+            return Ok(());
+        }
+        // the index of the last slot, if it overflows
+        let end = slot_idx.checked_add(words.len()).ok_or(usize::MAX)?;
+        let slots = self.get_mut(slot_idx..end).ok_or_else(|| end - 1)?;
+
+        for (slot, &word) in slots.iter_mut().zip(words) {
+            *slot = match policy {
+                MergePolicy::Overwrite => word,
+                MergePolicy::Or => slot.or(word),
+                MergePolicy::And => slot.and(word),
+            };
+        }
+        Ok(())
+    }
 
-                let old_value = *slot & mask != 0;
-                *slot |= mask;
-                Ok(old_value)
+    fn union_with(&mut self, other: &(impl BitSet<T> + ?Sized)) -> Result<(), usize> {
+        let Some(words) = other.words() else {
+            for value in other.iter_ones() {
+                self.insert(value)?;
+            }
+            return Ok(());
+        };
+        for (slot_idx, word) in words.enumerate() {
+            match self.get_mut(slot_idx) {
+                Some(slot) => *slot = slot.or(word),
+                None if word != T::ZERO => return Err(slot_idx),
+                None => {}
             }
+        }
+        Ok(())
+    }
 
-            #[inline]
-            fn remove(&mut self, index: $ty) -> Option<bool> {
-                let slot_idx = usize::try_from(index / $ty::BITS as $ty).ok()?;
-                let mask = 1 << (index % $ty::BITS as $ty);
-                let slot = self.get_mut(slot_idx)?;
+    fn intersect_with(&mut self, other: &(impl BitSet<T> + ?Sized)) {
+        let Some(mut words) = other.words() else {
+            self.clear_where(|value| !other.has(value));
+            return;
+        };
+        // the missing slots of `other` are empty
+        for slot in self {
+            *slot = slot.and(words.next().unwrap_or(T::ZERO));
+        }
+    }
 
-                let old_value = *slot & mask != 0;
-                *slot &= !mask;
-                Some(old_value)
-            }
+    fn difference_with(&mut self, other: &(impl BitSet<T> + ?Sized)) {
+        let Some(words) = other.words() else {
+            self.clear_where(|value| other.has(value));
+            return;
+        };
+        for (slot, word) in self.iter_mut().zip(words) {
+            *slot = slot.and(word.not());
         }
-    )*};
-}
+    }
+
+    fn complement_in_place(&mut self) -> bool {
+        for slot in self {
+            *slot = slot.not();
+        }
+        true
+    }
 
-impl_bit_set_mut! {
-    u16, u32, u64, usize, u128
+    #[inline]
+    fn drain(&mut self) -> Self::Drain<'_> {
+        crate::Drain::new(self)
+    }
 }
@@ -0,0 +1,89 @@
+use crate::BitSetMut;
+use std::collections::TryReserveError;
+
+#[inline]
+const fn slots_for<T>(bits: usize) -> usize {
+    bits.div_ceil(size_of::<T>() * 8)
+}
+
+/// Creates an empty bitvec with room for at least `bits` bits,
+/// returning an error instead of aborting if the allocation fails.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{bitvec, BitSetMut};
+///
+/// let mut bitset: Vec<u64> = bitvec::try_with_capacity_bits(1000).unwrap();
+/// assert!(bitset.capacity() >= 16);
+///
+/// // doesn't reallocate
+/// BitSetMut::insert(&mut bitset, 999);
+///
+/// assert!(bitvec::try_with_capacity_bits::<u64>(usize::MAX).is_err());
+/// ```
+pub fn try_with_capacity_bits<T>(bits: usize) -> Result<Vec<T>, TryReserveError> {
+    let mut bitvec = Vec::new();
+    bitvec.try_reserve_exact(slots_for::<T>(bits))?;
+    Ok(bitvec)
+}
+
+/// Reserves room in `bitvec` for at least `bits` bits in total (not additional bits),
+/// returning an error instead of aborting if the allocation fails.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::bitvec;
+///
+/// let mut bitset: Vec<u32> = Vec::new();
+/// bitvec::try_reserve_bits(&mut bitset, 100).unwrap();
+/// assert!(bitset.capacity() >= 4);
+/// ```
+pub fn try_reserve_bits<T>(bitvec: &mut Vec<T>, bits: usize) -> Result<(), TryReserveError> {
+    let additional = slots_for::<T>(bits).saturating_sub(bitvec.len());
+    bitvec.try_reserve(additional)
+}
+
+/// Collects `values` into a bitvec, growing it to fit the largest value.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{bitvec, BitSet};
+///
+/// let bitset: Vec<u32> = bitvec::from_values([3, 70, 5]);
+/// assert_eq!(bitset.len(), 3);
+/// assert_eq!(bitset.iter_ones().collect::<Vec<_>>(), [3, 5, 70]);
+/// ```
+pub fn from_values<T>(values: impl IntoIterator<Item = T>) -> Vec<T>
+where
+    T: Default + Clone,
+    [T]: BitSetMut<T>,
+{
+    let mut bitvec = Vec::new();
+    extend(&mut bitvec, values);
+    bitvec
+}
+
+/// Inserts `values` into `bitvec`, growing it to fit the largest value.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{bitvec, BitSet};
+///
+/// let mut bitset: Vec<u64> = bitvec::from_values([1]);
+/// bitvec::extend(&mut bitset, [200, 1, 64]);
+/// assert_eq!(bitset.iter_ones().collect::<Vec<_>>(), [1, 64, 200]);
+/// ```
+pub fn extend<T>(bitvec: &mut Vec<T>, values: impl IntoIterator<Item = T>)
+where
+    T: Default + Clone,
+    [T]: BitSetMut<T>,
+{
+    for value in values {
+        // a `Vec` grows instead of failing
+        let _ = BitSetMut::insert(bitvec, value);
+    }
+}
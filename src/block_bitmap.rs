@@ -0,0 +1,285 @@
+use crate::*;
+
+/// A block allocation bitmap split into groups, in the style of ext2/ext4.
+///
+/// Each group has its own bitmap, and a counter of its free blocks (like a group descriptor)
+/// that is kept in sync on every allocation, so full groups are skipped without reading
+/// their bitmap. Runs of blocks never span two groups.
+///
+/// `BlockBitmap` dereferences to the bitmaps of all the groups, back to back,
+/// so the whole block space can be queried with [`BitSet`].
+///
+/// Ranges of groups can be set apart as zones (e.g. low and high memory),
+/// see [`add_zone`](BlockBitmap::add_zone).
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{BitSet, BlockBitmap};
+///
+/// // 4 groups of 1024 blocks
+/// let mut blocks = BlockBitmap::new(4, 1024);
+///
+/// // allocate 8 contiguous blocks, as close as possible to block 100
+/// assert_eq!(blocks.allocate_run(8, 100), Some(100));
+/// assert_eq!(blocks.allocate_run(8, 100), Some(108));
+/// assert_eq!(blocks.free_in_group(0), Some(1024 - 16));
+///
+/// assert!(blocks.has(107));
+/// assert_eq!(blocks.free_blocks(), 4096 - 16);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockBitmap {
+    slots: Vec<usize>,
+    blocks_per_group: usize,
+    // number of free blocks of each group
+    free: Vec<usize>,
+    zones: Vec<Zone>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Zone {
+    groups: Range<usize>,
+    // number of free blocks that allocations from the zone must leave
+    reserve: usize,
+}
+
+impl BlockBitmap {
+    /// Creates a bitmap of `groups` groups of `blocks_per_group` free blocks each.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `blocks_per_group` is zero or not a multiple of `usize::BITS`.
+    pub fn new(groups: usize, blocks_per_group: usize) -> Self {
+        let bits = usize::BITS as usize;
+        assert!(
+            blocks_per_group != 0 && blocks_per_group.is_multiple_of(bits),
+            "blocks per group must be a non-zero multiple of {bits}"
+        );
+        Self {
+            slots: vec![0; groups * (blocks_per_group / bits)],
+            blocks_per_group,
+            free: vec![blocks_per_group; groups],
+            zones: Vec::new(),
+        }
+    }
+
+    /// Returns the number of groups.
+    #[inline]
+    pub fn groups(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Returns the number of blocks in each group.
+    #[inline]
+    pub fn blocks_per_group(&self) -> usize {
+        self.blocks_per_group
+    }
+
+    /// Returns the bitmap of the given group (e.g. to write it to disk),
+    /// or `None` if `group >= self.groups()`.
+    pub fn group(&self, group: usize) -> Option<&[usize]> {
+        let slots = self.blocks_per_group / usize::BITS as usize;
+        self.slots.get(group * slots..(group + 1) * slots)
+    }
+
+    /// Returns the number of free blocks in the given group, without reading its bitmap.
+    #[inline]
+    pub fn free_in_group(&self, group: usize) -> Option<usize> {
+        self.free.get(group).copied()
+    }
+
+    /// Returns the number of free blocks in all the groups, without reading the bitmaps.
+    #[inline]
+    pub fn free_blocks(&self) -> usize {
+        self.free.iter().sum()
+    }
+
+    /// Marks the given block as used.
+    ///
+    /// Returns `Some(true)` if it was already used, or `None` if the block is out of range.
+    pub fn allocate(&mut self, block: usize) -> Option<bool> {
+        let had = BitSetMut::insert(&mut self.slots[..], block).ok()?;
+        if !had {
+            self.free[block / self.blocks_per_group] -= 1;
+        }
+        Some(had)
+    }
+
+    /// Marks the given block as free.
+    ///
+    /// Returns `Some(true)` if it was used, or `None` if the block is out of range.
+    pub fn free(&mut self, block: usize) -> Option<bool> {
+        let had = BitSetMut::remove(&mut self.slots[..], block)?;
+        if had {
+            self.free[block / self.blocks_per_group] += 1;
+        }
+        Some(had)
+    }
+
+    /// Finds `len` contiguous free blocks, as close as possible after `goal`,
+    /// and returns the first one.
+    ///
+    /// The group of `goal` is searched first (from `goal`, then from its start),
+    /// followed by the next groups, wrapping around. Groups with fewer than `len`
+    /// free blocks are skipped without reading their bitmap.
+    ///
+    /// Returns `None` if there is no such run, or if `len` is zero or larger than a group.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::BlockBitmap;
+    ///
+    /// let mut blocks = BlockBitmap::new(2, 64);
+    /// for block in (0..64).step_by(4) {
+    ///     blocks.allocate(block);
+    /// }
+    /// // no run of 4 free blocks in the first group
+    /// assert_eq!(blocks.find_free_run(3, 10), Some(13));
+    /// assert_eq!(blocks.find_free_run(4, 10), Some(64));
+    /// ```
+    pub fn find_free_run(&self, len: usize, goal: usize) -> Option<usize> {
+        self.find_run_in_groups(0..self.groups(), len, goal)
+    }
+
+    /// Finds `len` contiguous free blocks like [`find_free_run`](BlockBitmap::find_free_run),
+    /// marks them as used, and returns the first one.
+    ///
+    /// Zone reserves don't apply, see [`allocate_run_in`](BlockBitmap::allocate_run_in).
+    pub fn allocate_run(&mut self, len: usize, goal: usize) -> Option<usize> {
+        let start = self.find_free_run(len, goal)?;
+        self.mark_run(start, len);
+        Some(start)
+    }
+
+    /// Sets apart the given range of groups as a zone, and returns its id.
+    ///
+    /// Allocations from the zone, see [`allocate_run_in`](BlockBitmap::allocate_run_in),
+    /// only search its groups, and fail if they would leave fewer than `reserve` free blocks in it.
+    ///
+    /// Returns `None` if the range is empty, out of range, or overlaps another zone.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::BlockBitmap;
+    ///
+    /// let mut blocks = BlockBitmap::new(4, 64);
+    /// let low = blocks.add_zone(0..1, 16).unwrap();
+    /// let high = blocks.add_zone(1..4, 0).unwrap();
+    /// assert_eq!(blocks.add_zone(0..2, 0), None);
+    ///
+    /// assert_eq!(blocks.allocate_run_in(low, 40, 0), Some(0));
+    /// // would leave fewer than 16 free blocks in the low zone
+    /// assert_eq!(blocks.allocate_run_in(low, 10, 0), None);
+    /// assert_eq!(blocks.allocate_run_in(high, 10, 0), Some(64));
+    ///
+    /// assert_eq!(blocks.zone_free(low), Some(24));
+    /// assert_eq!(blocks.zone_free(high), Some(192 - 10));
+    /// ```
+    pub fn add_zone(&mut self, groups: Range<usize>, reserve: usize) -> Option<usize> {
+        if groups.is_empty() || groups.end > self.groups() {
+            return None;
+        }
+        let overlaps =
+            |zone: &Zone| zone.groups.start < groups.end && groups.start < zone.groups.end;
+        if self.zones.iter().any(overlaps) {
+            return None;
+        }
+        self.zones.push(Zone { groups, reserve });
+        Some(self.zones.len() - 1)
+    }
+
+    /// Returns the range of groups of the given zone.
+    #[inline]
+    pub fn zone_groups(&self, zone: usize) -> Option<Range<usize>> {
+        self.zones.get(zone).map(|zone| zone.groups.clone())
+    }
+
+    /// Returns the number of free blocks in the given zone, without reading its bitmaps.
+    pub fn zone_free(&self, zone: usize) -> Option<usize> {
+        let groups = self.zone_groups(zone)?;
+        Some(self.free[groups].iter().sum())
+    }
+
+    /// Finds `len` contiguous free blocks within the given zone, as close as possible after `goal`,
+    /// marks them as used, and returns the first one.
+    ///
+    /// The search order is the same as [`find_free_run`](BlockBitmap::find_free_run),
+    /// restricted to the groups of the zone (a `goal` outside of the zone starts from its edge).
+    /// Returns `None` if the zone doesn't exist, has no such run,
+    /// or would be left with fewer free blocks than its reserve.
+    pub fn allocate_run_in(&mut self, zone: usize, len: usize, goal: usize) -> Option<usize> {
+        let Zone { groups, reserve } = self.zones.get(zone)?.clone();
+        if self.free[groups.clone()].iter().sum::<usize>() < len.checked_add(reserve)? {
+            return None;
+        }
+        let start = self.find_run_in_groups(groups, len, goal)?;
+        self.mark_run(start, len);
+        Some(start)
+    }
+
+    // searches the groups in `groups`, starting from the group of `goal`, see `find_free_run`
+    fn find_run_in_groups(&self, groups: Range<usize>, len: usize, goal: usize) -> Option<usize> {
+        if len == 0 || len > self.blocks_per_group || groups.is_empty() {
+            return None;
+        }
+        let goal = goal.clamp(
+            groups.start * self.blocks_per_group,
+            groups.end * self.blocks_per_group - 1,
+        );
+        let goal_group = goal / self.blocks_per_group;
+
+        let count = groups.len();
+        let starts = std::iter::once((goal_group, goal))
+            .chain((1..count).map(|i| {
+                let group = groups.start + (goal_group - groups.start + i) % count;
+                (group, group * self.blocks_per_group)
+            }))
+            .chain([(goal_group, goal_group * self.blocks_per_group)]);
+
+        for (group, start) in starts {
+            if self.free[group] < len {
+                continue;
+            }
+            let end = (group + 1) * self.blocks_per_group;
+            if let Some(block) = self.find_run_in(start..end, len) {
+                return Some(block);
+            }
+        }
+        None
+    }
+
+    // marks the free run `start..start + len`, within a single group, as used
+    fn mark_run(&mut self, start: usize, len: usize) {
+        for block in start..start + len {
+            let _ = BitSetMut::insert(&mut self.slots[..], block);
+        }
+        self.free[start / self.blocks_per_group] -= len;
+    }
+
+    // finds the first run of `len` free blocks within `range`
+    fn find_run_in(&self, range: Range<usize>, len: usize) -> Option<usize> {
+        let mut run = 0..0;
+        for block in BitSet::iter_zeros_in(&self.slots[..], range) {
+            if block != run.end {
+                run = block..block;
+            }
+            run.end = block + 1;
+            if run.len() == len {
+                return Some(run.start);
+            }
+        }
+        None
+    }
+}
+
+impl std::ops::Deref for BlockBitmap {
+    type Target = [usize];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.slots
+    }
+}
@@ -0,0 +1,97 @@
+use crate::*;
+
+/// An [`AtomicBitSet`] wrapper that catches double-frees, frees of identifiers
+/// that were never allocated, and leaked identifiers.
+///
+/// A shadow bitset records every identifier ever allocated, to tell the two kinds of
+/// invalid frees apart. Meant for debugging, available with the `checked` feature.
+///
+/// ## Examples
+///
+/// ```rust,should_panic
+/// use index_set::CheckedBitSet;
+///
+/// let ids: CheckedBitSet<1> = CheckedBitSet::new();
+/// let id = ids.allocate().unwrap();
+/// ids.free(id);
+/// ids.free(id); // panics: double-free
+/// ```
+pub struct CheckedBitSet<const N: usize> {
+    bitset: AtomicBitSet<N>,
+    // every identifier that was ever allocated
+    shadow: AtomicBitSet<N>,
+}
+
+impl<const N: usize> CheckedBitSet<N> {
+    /// Creates a new, empty `CheckedBitSet`.
+    #[inline]
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            bitset: AtomicBitSet::new(),
+            shadow: AtomicBitSet::new(),
+        }
+    }
+
+    /// Returns the underlying bitset.
+    ///
+    /// Identifiers freed through it directly are not checked.
+    #[inline]
+    pub fn bitset(&self) -> &AtomicBitSet<N> {
+        &self.bitset
+    }
+
+    /// Allocates the next free identifier, see [`AtomicBitSet::set_next_free_bit`].
+    pub fn allocate(&self) -> Option<usize> {
+        let id = self.bitset.set_next_free_bit()?;
+        SharedBitSet::insert(&self.shadow[..], id);
+        Some(id)
+    }
+
+    /// Frees an identifier.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the identifier is already free, or was never allocated.
+    #[track_caller]
+    pub fn free(&self, id: usize) {
+        match SharedBitSet::remove(&self.bitset[..], id) {
+            Some(true) => {}
+            Some(false) if BitSet::has(&self.shadow[..], id) => {
+                panic!("double-free of identifier {id}")
+            }
+            _ => panic!("free of identifier {id}, that was never allocated"),
+        }
+    }
+
+    /// Returns the identifiers that are still allocated.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::CheckedBitSet;
+    ///
+    /// let ids: CheckedBitSet<1> = CheckedBitSet::new();
+    /// let a = ids.allocate().unwrap();
+    /// let b = ids.allocate().unwrap();
+    /// ids.free(a);
+    ///
+    /// assert_eq!(ids.leaked(), [b]);
+    /// # ids.free(b);
+    /// ```
+    pub fn leaked(&self) -> Vec<usize> {
+        let mut leaked = Vec::new();
+        BitSet::for_each_one(&self.bitset[..], |id| leaked.push(id));
+        leaked
+    }
+}
+
+impl<const N: usize> Drop for CheckedBitSet<N> {
+    /// Reports the leaked identifiers to stderr.
+    fn drop(&mut self) {
+        let leaked = self.leaked();
+        if !leaked.is_empty() {
+            eprintln!("CheckedBitSet dropped with leaked identifiers: {leaked:?}");
+        }
+    }
+}
@@ -0,0 +1,515 @@
+use crate::*;
+
+/// Presents several atomic bitsets (e.g. living in different RAM banks) as one logical index space.
+///
+/// Indices of the first chunk come first, followed by the indices of the second chunk, and so on.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{AtomicBitSet, ChunkedBitSet, BitSet, SharedBitSet};
+///
+/// static BANK_A: AtomicBitSet<1> = AtomicBitSet::new();
+/// static BANK_B: AtomicBitSet<2> = AtomicBitSet::new();
+/// static IDS: ChunkedBitSet<2> = ChunkedBitSet::new([BANK_A.as_slice(), BANK_B.as_slice()]);
+///
+/// let bits = usize::BITS as usize;
+/// assert_eq!(IDS.capacity(), 3 * bits);
+///
+/// IDS.insert(bits + 1);
+/// assert!(BANK_B.has(1));
+/// ```
+pub struct ChunkedBitSet<'a, const CHUNKS: usize> {
+    chunks: [&'a [AtomicUsize]; CHUNKS],
+    // rotation hint of each chunk, see `AtomicBitSet`
+    rotations: [AtomicUsize; CHUNKS],
+    // chunk where the search for the next free bit starts
+    current: AtomicUsize,
+}
+
+impl<'a, const CHUNKS: usize> ChunkedBitSet<'a, CHUNKS> {
+    /// Creates a new `ChunkedBitSet` from the given chunks.
+    #[inline]
+    pub const fn new(chunks: [&'a [AtomicUsize]; CHUNKS]) -> Self {
+        Self {
+            chunks,
+            rotations: [const { AtomicUsize::new(0) }; CHUNKS],
+            current: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the chunks of this set.
+    #[inline]
+    pub fn chunks(&self) -> &[&'a [AtomicUsize]; CHUNKS] {
+        &self.chunks
+    }
+
+    /// Atomically finds the next free bit across all chunks, sets it to `1`, and returns its index.
+    ///
+    /// See [`AtomicBitSet::set_next_free_bit`].
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, ChunkedBitSet};
+    ///
+    /// let a: AtomicBitSet<1> = AtomicBitSet::new();
+    /// let b: AtomicBitSet<1> = AtomicBitSet::new();
+    /// let ids = ChunkedBitSet::new([&a[..], &b[..]]);
+    ///
+    /// let bits = usize::BITS as usize;
+    /// for expected in 0..2 * bits {
+    ///     assert_eq!(ids.set_next_free_bit(), Some(expected));
+    /// }
+    /// assert_eq!(ids.set_next_free_bit(), None);
+    /// ```
+    pub fn set_next_free_bit(&self) -> Option<usize> {
+        let start = self.current.load(Ordering::Relaxed);
+        for step in 0..CHUNKS {
+            let chunk_idx = (start + step) % CHUNKS;
+            let index = util::set_next_free_bit(self.chunks[chunk_idx], &self.rotations[chunk_idx]);
+            if let Some(index) = index {
+                if chunk_idx != start {
+                    self.current.store(chunk_idx, Ordering::Relaxed);
+                }
+                return Some(self.offset_of(chunk_idx) + index);
+            }
+        }
+        None
+    }
+
+    // global index of the first bit of the given chunk
+    fn offset_of(&self, chunk_idx: usize) -> usize {
+        self.chunks[..chunk_idx]
+            .iter()
+            .map(|chunk| BitSet::<usize>::capacity(*chunk))
+            .sum()
+    }
+
+    // splits `range` into the ranges local to each chunk
+    fn local_ranges(
+        &self,
+        range: &impl RangeBounds<usize>,
+    ) -> impl Iterator<Item = (&'a [AtomicUsize], Range<usize>)> {
+        let (start, end) = util::bit_range(range, self.capacity() as u128, |index| index as u128);
+        let (start, end) = (start as usize, end as usize);
+        let mut offset = 0;
+        self.chunks.into_iter().map(move |chunk| {
+            let local = start.saturating_sub(offset)..end.saturating_sub(offset);
+            offset += BitSet::<usize>::capacity(chunk);
+            (chunk, local)
+        })
+    }
+
+    // finds the chunk holding the given index, and the index local to that chunk
+    fn locate(&self, mut index: usize) -> Option<(&'a [AtomicUsize], usize)> {
+        for chunk in self.chunks {
+            let capacity = BitSet::<usize>::capacity(chunk);
+            if index < capacity {
+                return Some((chunk, index));
+            }
+            index -= capacity;
+        }
+        None
+    }
+}
+
+impl<const CHUNKS: usize> BitSet<usize> for ChunkedBitSet<'_, CHUNKS> {
+    type Ones<'a>
+        = ChunkedOnes<'a>
+    where
+        Self: 'a;
+    type Zeros<'a>
+        = ChunkedZeros<'a>
+    where
+        Self: 'a;
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.offset_of(CHUNKS)
+    }
+
+    #[inline]
+    fn has(&self, index: usize) -> bool {
+        self.locate(index)
+            .is_some_and(|(chunk, index)| BitSet::has(chunk, index))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.chunks
+            .iter()
+            .all(|chunk| BitSet::<usize>::is_empty(*chunk))
+    }
+
+    fn size(&self) -> usize {
+        self.chunks
+            .iter()
+            .map(|chunk| BitSet::<usize>::size(*chunk))
+            .sum()
+    }
+
+    fn for_each_one(&self, mut f: impl FnMut(usize)) {
+        let mut offset = 0;
+        for chunk in self.chunks {
+            BitSet::for_each_one(chunk, |index| f(offset + index));
+            offset += BitSet::<usize>::capacity(chunk);
+        }
+    }
+
+    fn first_zero(&self) -> Option<usize> {
+        let mut offset = 0;
+        for chunk in self.chunks {
+            if let Some(index) = BitSet::<usize>::first_zero(chunk) {
+                return Some(offset + index);
+            }
+            offset += BitSet::<usize>::capacity(chunk);
+        }
+        None
+    }
+
+    fn free_count(&self) -> usize {
+        self.chunks
+            .iter()
+            .map(|chunk| BitSet::<usize>::free_count(*chunk))
+            .sum()
+    }
+
+    fn remaining_in(&self, range: impl RangeBounds<usize>) -> usize {
+        self.local_ranges(&range)
+            .map(|(chunk, local)| BitSet::remaining_in(chunk, local))
+            .sum()
+    }
+
+    fn count_in_range(&self, range: impl RangeBounds<usize>) -> usize {
+        self.local_ranges(&range)
+            .map(|(chunk, local)| BitSet::count_in_range(chunk, local))
+            .sum()
+    }
+
+    fn load_factor(&self) -> f64 {
+        let capacity = self.capacity();
+        if capacity == 0 {
+            return 0.0;
+        }
+        self.size() as f64 / capacity as f64
+    }
+
+    fn estimate_load_factor(&self, samples: usize) -> f64 {
+        let slots: usize = self.chunks.iter().map(|chunk| chunk.len()).sum();
+        if slots == 0 {
+            return 0.0;
+        }
+        // spread the samples between chunks, proportionally to their number of slots
+        self.chunks
+            .iter()
+            .map(|chunk| {
+                let chunk_samples =
+                    (samples as u128 * chunk.len() as u128 / slots as u128) as usize;
+                let weight = chunk.len() as f64 / slots as f64;
+                BitSet::<usize>::estimate_load_factor(*chunk, chunk_samples.max(1)) * weight
+            })
+            .sum()
+    }
+
+    fn estimate_size(&self, confidence: f64) -> SizeEstimate {
+        let slots: usize = self.chunks.iter().map(|chunk| chunk.len()).sum();
+        let samples = SizeEstimate::SAMPLES.min(slots);
+        SizeEstimate::new(
+            self.estimate_load_factor(samples),
+            samples as f64 * usize::BITS as f64,
+            slots as f64 * usize::BITS as f64,
+            confidence,
+        )
+    }
+
+    fn iter_ones_in(&self, range: impl RangeBounds<usize>) -> Self::Ones<'_> {
+        ChunkedOnes::new(&self.chunks, &range, self.capacity(), false)
+    }
+
+    fn iter_zeros_in(&self, range: impl RangeBounds<usize>) -> Self::Zeros<'_> {
+        ChunkedZeros(ChunkedOnes::new(
+            &self.chunks,
+            &range,
+            self.capacity(),
+            true,
+        ))
+    }
+
+    fn has_all(&self, values: &[usize]) -> bool {
+        values.iter().all(|&value| self.has(value))
+    }
+
+    fn has_any(&self, values: &[usize]) -> bool {
+        values.iter().any(|&value| self.has(value))
+    }
+
+    fn gather(&self, values: &[usize], out: &mut [bool]) {
+        assert_eq!(
+            values.len(),
+            out.len(),
+            "`values` and `out` must have the same length"
+        );
+        for (out, &value) in out.iter_mut().zip(values) {
+            *out = self.has(value);
+        }
+    }
+}
+
+impl<const CHUNKS: usize> SharedBitSet<usize> for ChunkedBitSet<'_, CHUNKS> {
+    type Drain<'a>
+        = ChunkedDrain<'a>
+    where
+        Self: 'a;
+
+    fn clear(&self) {
+        for chunk in self.chunks {
+            SharedBitSet::<usize>::clear(chunk);
+        }
+    }
+
+    #[inline]
+    fn insert(&self, index: usize) -> Option<bool> {
+        let (chunk, index) = self.locate(index)?;
+        SharedBitSet::insert(chunk, index)
+    }
+
+    #[inline]
+    fn remove(&self, index: usize) -> Option<bool> {
+        let (chunk, index) = self.locate(index)?;
+        SharedBitSet::remove(chunk, index)
+    }
+
+    #[inline]
+    fn insert_with(&self, index: usize, order: Ordering) -> Option<bool> {
+        let (chunk, index) = self.locate(index)?;
+        chunk.insert_with(index, order)
+    }
+
+    #[inline]
+    fn remove_with(&self, index: usize, order: Ordering) -> Option<bool> {
+        let (chunk, index) = self.locate(index)?;
+        chunk.remove_with(index, order)
+    }
+
+    #[inline]
+    fn insert_returning_word(&self, index: usize) -> Option<(bool, usize)> {
+        let (chunk, index) = self.locate(index)?;
+        SharedBitSet::insert_returning_word(chunk, index)
+    }
+
+    #[inline]
+    fn insert_transition(&self, index: usize) -> Option<SlotTransition> {
+        let (chunk, index) = self.locate(index)?;
+        SharedBitSet::insert_transition(chunk, index)
+    }
+
+    #[inline]
+    fn remove_transition(&self, index: usize) -> Option<SlotTransition> {
+        let (chunk, index) = self.locate(index)?;
+        SharedBitSet::remove_transition(chunk, index)
+    }
+
+    fn take_lowest(&self) -> Option<usize> {
+        let mut offset = 0;
+        for chunk in self.chunks {
+            if let Some(index) = SharedBitSet::<usize>::take_lowest(chunk) {
+                return Some(offset + index);
+            }
+            offset += BitSet::<usize>::capacity(chunk);
+        }
+        None
+    }
+
+    fn take_highest(&self) -> Option<usize> {
+        let mut offset = self.capacity();
+        for chunk in self.chunks.iter().rev() {
+            offset -= BitSet::<usize>::capacity(*chunk);
+            if let Some(index) = SharedBitSet::<usize>::take_highest(*chunk) {
+                return Some(offset + index);
+            }
+        }
+        None
+    }
+
+    fn clear_where(&self, mut predicate: impl FnMut(usize) -> bool) -> usize {
+        let mut offset = 0;
+        let mut cleared = 0;
+        for chunk in self.chunks {
+            cleared += SharedBitSet::clear_where(chunk, |index| predicate(offset + index));
+            offset += BitSet::<usize>::capacity(chunk);
+        }
+        cleared
+    }
+
+    fn import_at(&self, offset: usize, words: &[usize], policy: MergePolicy) -> Option<()> {
+        let bits = usize::BITS as usize;
+        assert!(
+            offset.is_multiple_of(bits),
+            "offset must be a multiple of {bits} bits"
+        );
+        let end = words.len().checked_mul(bits)?.checked_add(offset)?;
+        if end > self.capacity() {
+            return None;
+        }
+        // every chunk holds whole slots, so the fragment splits on slot boundaries
+        let mut chunk_offset = 0;
+        for chunk in self.chunks {
+            let capacity = BitSet::<usize>::capacity(chunk);
+            let start = offset.max(chunk_offset);
+            let stop = end.min(chunk_offset + capacity);
+            if start < stop {
+                let fragment = &words[(start - offset) / bits..(stop - offset) / bits];
+                SharedBitSet::import_at(chunk, start - chunk_offset, fragment, policy)?;
+            }
+            chunk_offset += capacity;
+        }
+        Some(())
+    }
+
+    #[inline]
+    fn drain(&self) -> Self::Drain<'_> {
+        ChunkedDrain {
+            chunks: &self.chunks,
+            offset: 0,
+            end: 0,
+            current: SharedDrain::new(&[]),
+        }
+    }
+}
+
+/// An iterator that atomically removes the values of a [`ChunkedBitSet`] as it yields them,
+/// in ascending order, see [`SharedBitSet::drain`].
+pub struct ChunkedDrain<'a> {
+    // chunks that weren't started yet
+    chunks: &'a [&'a [AtomicUsize]],
+    // global index of the first bit of the chunk being drained, and of the bit after it
+    offset: usize,
+    end: usize,
+    current: SharedDrain<'a, AtomicUsize>,
+}
+
+impl Iterator for ChunkedDrain<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            if let Some(index) = self.current.next() {
+                return Some(self.offset + index);
+            }
+            let (chunk, rest) = self.chunks.split_first()?;
+            self.chunks = rest;
+            self.offset = self.end;
+            self.end += chunk.len() * usize::BITS as usize;
+            self.current = SharedDrain::new(chunk);
+        }
+    }
+}
+
+impl std::iter::FusedIterator for ChunkedDrain<'_> {}
+
+/// An iterator over the values of a [`ChunkedBitSet`], in ascending order.
+pub struct ChunkedOnes<'a> {
+    // chunks that weren't started yet
+    chunks: &'a [&'a [AtomicUsize]],
+    range: (usize, usize),
+    // global index of the first bit of `chunks`, and of the bit after them
+    start: usize,
+    end: usize,
+    // the chunks being iterated from each end, with the global index of their first bit
+    front: (usize, Ones<'a, AtomicUsize>),
+    back: (usize, Ones<'a, AtomicUsize>),
+    // iterate over the unset bits instead, see `ChunkedZeros`
+    invert: bool,
+}
+
+impl<'a> ChunkedOnes<'a> {
+    fn new(
+        chunks: &'a [&'a [AtomicUsize]],
+        range: &impl RangeBounds<usize>,
+        capacity: usize,
+        invert: bool,
+    ) -> Self {
+        let (start, end) = util::bit_range(range, capacity as u128, |index| index as u128);
+        ChunkedOnes {
+            chunks,
+            range: (start as usize, end as usize),
+            start: 0,
+            end: capacity,
+            front: (0, Ones::new(&[], (0, 0))),
+            back: (0, Ones::new(&[], (0, 0))),
+            invert,
+        }
+    }
+
+    // iterates over the part of the range within the chunk starting at `offset`
+    fn iter_chunk(
+        &self,
+        chunk: &'a [AtomicUsize],
+        offset: usize,
+    ) -> (usize, Ones<'a, AtomicUsize>) {
+        let capacity = BitSet::<usize>::capacity(chunk);
+        let start = self.range.0.saturating_sub(offset).min(capacity);
+        let end = self.range.1.saturating_sub(offset).min(capacity);
+        let ones = Ones::with_inverted(chunk, (start as u128, end as u128), self.invert);
+        (offset, ones)
+    }
+}
+
+impl Iterator for ChunkedOnes<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            if let Some(index) = self.front.1.next() {
+                return Some(self.front.0 + index);
+            }
+            let Some((chunk, rest)) = self.chunks.split_first() else {
+                // the remaining values, if any, are in the chunk started from the back
+                return self.back.1.next().map(|index| self.back.0 + index);
+            };
+            self.chunks = rest;
+            self.front = self.iter_chunk(chunk, self.start);
+            self.start += BitSet::<usize>::capacity(*chunk);
+        }
+    }
+}
+
+impl DoubleEndedIterator for ChunkedOnes<'_> {
+    fn next_back(&mut self) -> Option<usize> {
+        loop {
+            if let Some(index) = self.back.1.next_back() {
+                return Some(self.back.0 + index);
+            }
+            let Some((chunk, rest)) = self.chunks.split_last() else {
+                // the remaining values, if any, are in the chunk started from the front
+                return self.front.1.next_back().map(|index| self.front.0 + index);
+            };
+            self.chunks = rest;
+            self.end -= BitSet::<usize>::capacity(*chunk);
+            self.back = self.iter_chunk(chunk, self.end);
+        }
+    }
+}
+
+impl std::iter::FusedIterator for ChunkedOnes<'_> {}
+
+/// An iterator over the values missing from a [`ChunkedBitSet`], in ascending order.
+pub struct ChunkedZeros<'a>(ChunkedOnes<'a>);
+
+impl Iterator for ChunkedZeros<'_> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        self.0.next()
+    }
+}
+
+impl DoubleEndedIterator for ChunkedZeros<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<usize> {
+        self.0.next_back()
+    }
+}
+
+impl std::iter::FusedIterator for ChunkedZeros<'_> {}
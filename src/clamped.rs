@@ -0,0 +1,285 @@
+use crate::*;
+
+/// Wraps a set, and maps the values that are out of its range to its last value,
+/// instead of failing.
+///
+/// Handy when the last bit is an overflow bucket, e.g. for a histogram of latencies
+/// where every value past the end lands in the last bucket. Since the capacity is what
+/// decides the clamping, wrapped `Vec`s don't grow anymore.
+///
+/// Only [`has`](BitSet::has), [`insert`](BitSetMut::insert), [`remove`](BitSetMut::remove)
+/// and their variants clamp. Ranges are already clamped to the capacity by every set.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{BitSet, BitSetMut, Clamped};
+///
+/// let mut buckets = Clamped::new(vec![0_usize; 1].into_boxed_slice());
+/// buckets.insert(3).unwrap();
+/// buckets.insert(1000).unwrap();
+///
+/// let last = usize::BITS as usize - 1;
+/// assert!(buckets.has(last) && buckets.has(5000));
+/// assert_eq!(buckets.iter_ones().collect::<Vec<_>>(), [3, last]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Clamped<S> {
+    set: S,
+}
+
+impl<S> Clamped<S> {
+    /// Wraps `set`.
+    #[inline]
+    pub const fn new(set: S) -> Self {
+        Self { set }
+    }
+
+    /// Returns a reference to the inner set.
+    #[inline]
+    pub fn get_ref(&self) -> &S {
+        &self.set
+    }
+
+    /// Unwraps the inner set.
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.set
+    }
+}
+
+impl<S: BitSet<usize>> Clamped<S> {
+    // maps `index` to the last value if it is out of range, unless the set has no capacity
+    #[inline]
+    fn clamp(&self, index: usize) -> usize {
+        index.min(self.set.capacity().saturating_sub(1))
+    }
+}
+
+impl<S: BitSet<usize>> BitSet<usize> for Clamped<S> {
+    type Ones<'a>
+        = S::Ones<'a>
+    where
+        Self: 'a;
+    type Zeros<'a>
+        = S::Zeros<'a>
+    where
+        Self: 'a;
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.set.capacity()
+    }
+
+    #[inline]
+    fn has(&self, index: usize) -> bool {
+        self.set.has(self.clamp(index))
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    #[inline]
+    fn size(&self) -> usize {
+        self.set.size()
+    }
+
+    #[inline]
+    fn for_each_one(&self, f: impl FnMut(usize)) {
+        self.set.for_each_one(f)
+    }
+
+    #[inline]
+    fn first_zero(&self) -> Option<usize> {
+        self.set.first_zero()
+    }
+
+    #[inline]
+    fn free_count(&self) -> usize {
+        self.set.free_count()
+    }
+
+    #[inline]
+    fn remaining_in(&self, range: impl RangeBounds<usize>) -> usize {
+        self.set.remaining_in(range)
+    }
+
+    #[inline]
+    fn count_in_range(&self, range: impl RangeBounds<usize>) -> usize {
+        self.set.count_in_range(range)
+    }
+
+    #[inline]
+    fn load_factor(&self) -> f64 {
+        self.set.load_factor()
+    }
+
+    #[inline]
+    fn estimate_load_factor(&self, samples: usize) -> f64 {
+        self.set.estimate_load_factor(samples)
+    }
+
+    #[inline]
+    fn estimate_size(&self, confidence: f64) -> SizeEstimate {
+        self.set.estimate_size(confidence)
+    }
+
+    #[inline]
+    fn iter_ones_in(&self, range: impl RangeBounds<usize>) -> Self::Ones<'_> {
+        self.set.iter_ones_in(range)
+    }
+
+    #[inline]
+    fn iter_zeros_in(&self, range: impl RangeBounds<usize>) -> Self::Zeros<'_> {
+        self.set.iter_zeros_in(range)
+    }
+
+    fn has_all(&self, values: &[usize]) -> bool {
+        values.iter().all(|&value| self.has(value))
+    }
+
+    fn has_any(&self, values: &[usize]) -> bool {
+        values.iter().any(|&value| self.has(value))
+    }
+
+    fn gather(&self, values: &[usize], out: &mut [bool]) {
+        assert_eq!(
+            values.len(),
+            out.len(),
+            "`values` and `out` must have the same length"
+        );
+        for (&value, out) in values.iter().zip(out) {
+            *out = self.has(value);
+        }
+    }
+}
+
+impl<S> BitSetMut<usize> for Clamped<S>
+where
+    S: BitSetMut<usize> + BitSet<usize>,
+{
+    type Drain<'a>
+        = <S as BitSetMut<usize>>::Drain<'a>
+    where
+        Self: 'a;
+
+    #[inline]
+    fn clear(&mut self) {
+        BitSetMut::clear(&mut self.set)
+    }
+
+    #[inline]
+    fn insert(&mut self, index: usize) -> Result<bool, usize> {
+        let index = self.clamp(index);
+        BitSetMut::insert(&mut self.set, index)
+    }
+
+    #[inline]
+    fn remove(&mut self, index: usize) -> Option<bool> {
+        let index = self.clamp(index);
+        BitSetMut::remove(&mut self.set, index)
+    }
+
+    #[inline]
+    fn clear_where(&mut self, predicate: impl FnMut(usize) -> bool) -> usize {
+        BitSetMut::clear_where(&mut self.set, predicate)
+    }
+
+    #[inline]
+    fn import_at(
+        &mut self,
+        offset: usize,
+        words: &[usize],
+        policy: MergePolicy,
+    ) -> Result<(), usize> {
+        BitSetMut::import_at(&mut self.set, offset, words, policy)
+    }
+
+    #[inline]
+    fn drain(&mut self) -> Self::Drain<'_> {
+        BitSetMut::drain(&mut self.set)
+    }
+}
+
+impl<S> SharedBitSet<usize> for Clamped<S>
+where
+    S: SharedBitSet<usize> + BitSet<usize>,
+{
+    type Drain<'a>
+        = <S as SharedBitSet<usize>>::Drain<'a>
+    where
+        Self: 'a;
+
+    #[inline]
+    fn clear(&self) {
+        SharedBitSet::clear(&self.set)
+    }
+
+    #[inline]
+    fn insert(&self, index: usize) -> Option<bool> {
+        SharedBitSet::insert(&self.set, self.clamp(index))
+    }
+
+    #[inline]
+    fn remove(&self, index: usize) -> Option<bool> {
+        SharedBitSet::remove(&self.set, self.clamp(index))
+    }
+
+    #[inline]
+    fn insert_with(&self, index: usize, order: Ordering) -> Option<bool> {
+        self.set.insert_with(self.clamp(index), order)
+    }
+
+    #[inline]
+    fn remove_with(&self, index: usize, order: Ordering) -> Option<bool> {
+        self.set.remove_with(self.clamp(index), order)
+    }
+
+    #[inline]
+    fn insert_returning_word(&self, index: usize) -> Option<(bool, usize)> {
+        self.set.insert_returning_word(self.clamp(index))
+    }
+
+    #[inline]
+    fn insert_transition(&self, index: usize) -> Option<SlotTransition> {
+        self.set.insert_transition(self.clamp(index))
+    }
+
+    #[inline]
+    fn remove_transition(&self, index: usize) -> Option<SlotTransition> {
+        self.set.remove_transition(self.clamp(index))
+    }
+
+    #[inline]
+    fn take_lowest(&self) -> Option<usize> {
+        self.set.take_lowest()
+    }
+
+    #[inline]
+    fn take_highest(&self) -> Option<usize> {
+        self.set.take_highest()
+    }
+
+    #[inline]
+    fn clear_where(&self, predicate: impl FnMut(usize) -> bool) -> usize {
+        SharedBitSet::clear_where(&self.set, predicate)
+    }
+
+    #[inline]
+    fn import_at(&self, offset: usize, words: &[usize], policy: MergePolicy) -> Option<()> {
+        SharedBitSet::import_at(&self.set, offset, words, policy)
+    }
+
+    #[inline]
+    fn insert_iter(&self, iter: impl IntoIterator<Item = usize>) -> Result<usize, (usize, usize)> {
+        let iter = iter.into_iter().map(|index| self.clamp(index));
+        self.set.insert_iter(iter)
+    }
+
+    #[inline]
+    fn drain(&self) -> Self::Drain<'_> {
+        SharedBitSet::drain(&self.set)
+    }
+}
@@ -0,0 +1,197 @@
+use crate::*;
+
+/// A view of the values missing from a set, without copying it.
+///
+/// The complement is bounded by a logical capacity, which is the capacity of the set
+/// unless narrowed with [`within`](Complement::within), so that APIs taking a
+/// [`BitSet`] can be fed "everything not in the set" up to that bound.
+///
+/// The estimates are derived from the estimates of the set, and assume its values are
+/// spread evenly when the capacity is narrowed.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{BitSet, BitSetMut, Complement};
+///
+/// let mut allocated = [0_usize; 1];
+/// allocated.insert(0).unwrap();
+/// allocated.insert(2).unwrap();
+///
+/// let free = Complement::within(&allocated[..], 5);
+/// assert!(free.has(1) && !free.has(2) && !free.has(5));
+/// assert_eq!(free.iter_ones().collect::<Vec<_>>(), [1, 3, 4]);
+/// assert_eq!(free.first_zero(), Some(0));
+/// assert_eq!(free.size(), 3);
+/// ```
+#[derive(Debug)]
+pub struct Complement<'a, S: ?Sized, T = usize> {
+    set: &'a S,
+    capacity: T,
+}
+
+impl<S: ?Sized, T: Copy> Clone for Complement<'_, S, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S: ?Sized, T: Copy> Copy for Complement<'_, S, T> {}
+
+impl<'a, T: Word, S: BitSet<T> + ?Sized> Complement<'a, S, T> {
+    /// Creates a view of the values missing from `set`, within its capacity.
+    #[inline]
+    pub fn new(set: &'a S) -> Self {
+        Self {
+            capacity: set.capacity(),
+            set,
+        }
+    }
+
+    /// Creates a view of the values missing from `set`, below `capacity`.
+    ///
+    /// The capacity is clamped to the capacity of `set`.
+    #[inline]
+    pub fn within(set: &'a S, capacity: T) -> Self {
+        let capacity = capacity.to_u128().min(set.capacity().to_u128());
+        Self {
+            capacity: T::from_u128(capacity),
+            set,
+        }
+    }
+
+    /// Returns a reference to the inner set.
+    #[inline]
+    pub fn get_ref(&self) -> &'a S {
+        self.set
+    }
+
+    // clamps `range` to the logical capacity
+    fn clamp(&self, range: &impl RangeBounds<T>) -> Range<T> {
+        let (start, end) = util::bit_range(range, self.capacity.to_u128(), T::to_u128);
+        // reversed ranges are empty
+        T::from_u128(start.min(end))..T::from_u128(end)
+    }
+
+    // the logical capacity, as a range
+    #[inline]
+    fn all(&self) -> Range<T> {
+        T::ZERO..self.capacity
+    }
+}
+
+impl<'a, T: Word, S: BitSet<T> + ?Sized> BitSet<T> for Complement<'a, S, T> {
+    type Ones<'b>
+        = S::Zeros<'a>
+    where
+        Self: 'b;
+    type Zeros<'b>
+        = S::Ones<'a>
+    where
+        Self: 'b;
+
+    #[inline]
+    fn capacity(&self) -> T {
+        self.capacity
+    }
+
+    #[inline]
+    fn has(&self, index: T) -> bool {
+        index.to_u128() < self.capacity.to_u128() && !self.set.has(index)
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.set.remaining_in(self.all()) == T::ZERO
+    }
+
+    #[inline]
+    fn size(&self) -> T {
+        self.set.remaining_in(self.all())
+    }
+
+    #[inline]
+    fn for_each_one(&self, f: impl FnMut(T)) {
+        self.set.iter_zeros_in(self.all()).for_each(f)
+    }
+
+    #[inline]
+    fn first_zero(&self) -> Option<T> {
+        self.set.iter_ones_in(self.all()).next()
+    }
+
+    #[inline]
+    fn free_count(&self) -> T {
+        self.set.count_in_range(self.all())
+    }
+
+    #[inline]
+    fn remaining_in(&self, range: impl RangeBounds<T>) -> T {
+        self.set.count_in_range(self.clamp(&range))
+    }
+
+    #[inline]
+    fn count_in_range(&self, range: impl RangeBounds<T>) -> T {
+        self.set.remaining_in(self.clamp(&range))
+    }
+
+    fn load_factor(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        self.size().to_u128() as f64 / self.capacity.to_u128() as f64
+    }
+
+    fn estimate_load_factor(&self, samples: usize) -> f64 {
+        if self.capacity == T::ZERO || samples == 0 {
+            return 0.0;
+        }
+        1.0 - self.set.estimate_load_factor(samples)
+    }
+
+    fn estimate_size(&self, confidence: f64) -> SizeEstimate {
+        let capacity = self.set.capacity().to_u128() as f64;
+        if self.capacity == T::ZERO {
+            return SizeEstimate {
+                size: 0.0,
+                margin: 0.0,
+            };
+        }
+        let estimate = self.set.estimate_size(confidence);
+        let scale = self.capacity.to_u128() as f64 / capacity;
+        SizeEstimate {
+            size: (capacity - estimate.size) * scale,
+            margin: estimate.margin * scale,
+        }
+    }
+
+    #[inline]
+    fn iter_ones_in(&self, range: impl RangeBounds<T>) -> Self::Ones<'_> {
+        self.set.iter_zeros_in(self.clamp(&range))
+    }
+
+    #[inline]
+    fn iter_zeros_in(&self, range: impl RangeBounds<T>) -> Self::Zeros<'_> {
+        self.set.iter_ones_in(self.clamp(&range))
+    }
+
+    fn has_all(&self, values: &[T]) -> bool {
+        values.iter().all(|&value| self.has(value))
+    }
+
+    fn has_any(&self, values: &[T]) -> bool {
+        values.iter().any(|&value| self.has(value))
+    }
+
+    fn gather(&self, values: &[T], out: &mut [bool]) {
+        assert_eq!(
+            values.len(),
+            out.len(),
+            "`values` and `out` must have the same length"
+        );
+        for (&value, out) in values.iter().zip(out) {
+            *out = self.has(value);
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use crate::*;
+use std::fmt::Write;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Sub, SubAssign};
+
+/// A set of CPUs, e.g. a thread affinity mask.
+///
+/// Can be parsed from and formatted to the two formats used by Linux:
+///
+/// - the mask format (`/proc/self/status`'s `Cpus_allowed`, `taskset -p`):
+///   comma-separated groups of 32 bits in hex, most significant first, e.g. `"1,0000000f"`.
+/// - the list format (`Cpus_allowed_list`, `/sys/devices/system/cpu/online`):
+///   comma-separated CPUs and inclusive ranges, e.g. `"0-3,32"`.
+///
+/// Dereferences to the underlying `[usize]` slots, so it can be queried with [`BitSet`].
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{BitSet, CpuSet};
+///
+/// let allowed = CpuSet::from_mask("1,0000000f").unwrap();
+/// assert_eq!(allowed.to_list(), "0-3,32");
+///
+/// let online = CpuSet::from_list("0-7").unwrap();
+/// let usable = &allowed & &online;
+/// assert_eq!(usable.iter().collect::<Vec<_>>(), [0, 1, 2, 3]);
+/// assert!(usable.has(2));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CpuSet {
+    // the last slot is never zero, so equal sets have equal slots
+    slots: Vec<usize>,
+}
+
+impl CpuSet {
+    /// Creates an empty set, without allocating.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { slots: Vec::new() }
+    }
+
+    /// Adds a CPU to the set, growing it if needed.
+    ///
+    /// Returns `true` if the CPU was already in the set.
+    pub fn insert(&mut self, cpu: usize) -> bool {
+        // `Vec` grows instead of failing
+        BitSetMut::insert(&mut self.slots, cpu).unwrap_or_default()
+    }
+
+    /// Removes a CPU from the set.
+    ///
+    /// Returns `true` if the CPU was in the set.
+    pub fn remove(&mut self, cpu: usize) -> bool {
+        let had = BitSetMut::remove(&mut self.slots[..], cpu).unwrap_or_default();
+        self.trim();
+        had
+    }
+
+    /// Returns an iterator over the CPUs of the set, in ascending order.
+    #[inline]
+    pub fn iter(&self) -> Ones<'_, usize> {
+        self.slots.iter_ones()
+    }
+
+    /// Parses a set from the Linux mask format, e.g. `"ff,00000001"`.
+    ///
+    /// Each comma-separated group holds 32 CPUs, the last group holds CPUs `0..32`.
+    /// Returns `None` if a group is empty, isn't hex, or doesn't fit in 32 bits.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::CpuSet;
+    ///
+    /// let cpus = CpuSet::from_mask("00000003,00000100").unwrap();
+    /// assert_eq!(cpus.iter().collect::<Vec<_>>(), [8, 32, 33]);
+    /// assert_eq!(CpuSet::from_mask("1,"), None);
+    /// ```
+    pub fn from_mask(mask: &str) -> Option<Self> {
+        let mut set = Self::new();
+        for (group, hex) in mask.trim().rsplit(',').enumerate() {
+            if hex.is_empty() || hex.len() > 8 {
+                return None;
+            }
+            let mut bits = u32::from_str_radix(hex, 16).ok()?;
+            while bits != 0 {
+                set.insert(group * 32 + bits.trailing_zeros() as usize);
+                bits &= bits - 1;
+            }
+        }
+        Some(set)
+    }
+
+    /// Formats the set in the Linux mask format, with as few groups as needed.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::CpuSet;
+    ///
+    /// let cpus = CpuSet::from_list("0-3,32").unwrap();
+    /// assert_eq!(cpus.to_mask(), "00000001,0000000f");
+    /// assert_eq!(CpuSet::new().to_mask(), "00000000");
+    /// ```
+    pub fn to_mask(&self) -> String {
+        let groups = self.iter().next_back().map_or(1, |cpu| cpu / 32 + 1);
+        let mut mask = String::with_capacity(groups * 9);
+        for group in (0..groups).rev() {
+            let mut bits = 0u32;
+            for cpu in self.slots.iter_ones_in(group * 32..(group + 1) * 32) {
+                bits |= 1 << (cpu % 32);
+            }
+            if !mask.is_empty() {
+                mask.push(',');
+            }
+            let _ = write!(mask, "{bits:08x}");
+        }
+        mask
+    }
+
+    /// Parses a set from the Linux list format, e.g. `"0-3,8,10-11"`.
+    ///
+    /// An empty string is an empty set. Returns `None` if an entry isn't a number
+    /// or a range, or if a range is reversed.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::CpuSet;
+    ///
+    /// let cpus = CpuSet::from_list("0-2,8").unwrap();
+    /// assert_eq!(cpus.iter().collect::<Vec<_>>(), [0, 1, 2, 8]);
+    /// assert_eq!(CpuSet::from_list("3-1"), None);
+    /// ```
+    pub fn from_list(list: &str) -> Option<Self> {
+        let mut set = Self::new();
+        let list = list.trim();
+        if list.is_empty() {
+            return Some(set);
+        }
+        for entry in list.split(',') {
+            let (start, end) = match entry.split_once('-') {
+                Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+                None => {
+                    let cpu = entry.parse().ok()?;
+                    (cpu, cpu)
+                }
+            };
+            if start > end {
+                return None;
+            }
+            for cpu in start..=end {
+                set.insert(cpu);
+            }
+        }
+        Some(set)
+    }
+
+    /// Formats the set in the Linux list format, merging consecutive CPUs into ranges.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::CpuSet;
+    ///
+    /// let cpus = CpuSet::from_mask("f0f").unwrap();
+    /// assert_eq!(cpus.to_list(), "0-3,8-11");
+    /// ```
+    pub fn to_list(&self) -> String {
+        let mut list = String::new();
+        for run in self.slots.runs() {
+            if !list.is_empty() {
+                list.push(',');
+            }
+            let _ = match run.len() {
+                1 => write!(list, "{}", run.start),
+                _ => write!(list, "{}-{}", run.start, run.end - 1),
+            };
+        }
+        list
+    }
+
+    // drops the trailing zero slots
+    fn trim(&mut self) {
+        while self.slots.last() == Some(&0) {
+            self.slots.pop();
+        }
+    }
+
+    // applies `op` slot by slot, treating missing slots as zero
+    fn merge(&mut self, other: &CpuSet, op: impl Fn(usize, usize) -> usize) {
+        if self.slots.len() < other.slots.len() {
+            self.slots.resize(other.slots.len(), 0);
+        }
+        for (slot_idx, slot) in self.slots.iter_mut().enumerate() {
+            *slot = op(*slot, other.slots.get(slot_idx).copied().unwrap_or(0));
+        }
+        self.trim();
+    }
+}
+
+impl std::ops::Deref for CpuSet {
+    type Target = [usize];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.slots
+    }
+}
+
+impl<'a> IntoIterator for &'a CpuSet {
+    type Item = usize;
+    type IntoIter = Ones<'a, usize>;
+
+    #[inline]
+    fn into_iter(self) -> Ones<'a, usize> {
+        self.iter()
+    }
+}
+
+impl FromIterator<usize> for CpuSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for cpu in iter {
+            set.insert(cpu);
+        }
+        set
+    }
+}
+
+macro_rules! impl_cpu_set_op {
+    [$($op:ident::$fn:ident, $op_assign:ident::$fn_assign:ident => |$a:ident, $b:ident| $expr:expr;)*] => {$(
+        impl $op_assign<&CpuSet> for CpuSet {
+            #[inline]
+            fn $fn_assign(&mut self, other: &CpuSet) {
+                self.merge(other, |$a, $b| $expr);
+            }
+        }
+
+        impl $op<&CpuSet> for &CpuSet {
+            type Output = CpuSet;
+
+            #[inline]
+            fn $fn(self, other: &CpuSet) -> CpuSet {
+                let mut set = self.clone();
+                set.$fn_assign(other);
+                set
+            }
+        }
+    )*};
+}
+
+impl_cpu_set_op! [
+    BitOr::bitor, BitOrAssign::bitor_assign => |a, b| a | b;
+    BitAnd::bitand, BitAndAssign::bitand_assign => |a, b| a & b;
+    BitXor::bitxor, BitXorAssign::bitxor_assign => |a, b| a ^ b;
+    Sub::sub, SubAssign::sub_assign => |a, b| a & !b;
+];
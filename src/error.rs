@@ -0,0 +1,63 @@
+use std::fmt;
+
+/// The error returned by [`BitSetMut::insert_new`](crate::BitSetMut::insert_new)
+/// and [`SharedBitSet::insert_new`](crate::SharedBitSet::insert_new).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertError {
+    /// The value was already in the set.
+    AlreadyPresent,
+    /// The set cannot hold the value.
+    OutOfRange,
+}
+
+impl fmt::Display for InsertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InsertError::AlreadyPresent => f.write_str("value is already in the set"),
+            InsertError::OutOfRange => f.write_str("value is out of the range of the set"),
+        }
+    }
+}
+
+impl std::error::Error for InsertError {}
+
+/// The error returned by [`SharedBitSet::try_claim`](crate::SharedBitSet::try_claim).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimError {
+    /// The index was already claimed.
+    Taken,
+    /// The set cannot hold the index.
+    OutOfRange,
+}
+
+impl fmt::Display for ClaimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClaimError::Taken => f.write_str("index is already taken"),
+            ClaimError::OutOfRange => f.write_str("index is out of the range of the set"),
+        }
+    }
+}
+
+impl std::error::Error for ClaimError {}
+
+/// The error returned by [`AtomicBitSet::set_next_free_bit_bounded`](crate::AtomicBitSet::set_next_free_bit_bounded).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocError {
+    /// Every bit of the set is taken.
+    Full,
+    /// Too many compare-and-swaps failed due to concurrent updates.
+    /// There may still be free bits.
+    Contended,
+}
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AllocError::Full => f.write_str("the set is full"),
+            AllocError::Contended => f.write_str("gave up on a contended set"),
+        }
+    }
+}
+
+impl std::error::Error for AllocError {}
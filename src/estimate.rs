@@ -0,0 +1,93 @@
+/// An estimated number of values in a set, see [`BitSet::estimate_size`](crate::BitSet::estimate_size).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SizeEstimate {
+    /// The estimated number of values.
+    pub size: f64,
+    /// The true size is within `size ± margin`, with the requested confidence.
+    pub margin: f64,
+}
+
+impl SizeEstimate {
+    /// Number of slots sampled by `estimate_size`.
+    pub const SAMPLES: usize = 4096;
+
+    /// Builds an estimate from a `load_factor` measured over `sampled_bits` bits,
+    /// of a set with the given `capacity`.
+    pub(crate) fn new(load_factor: f64, sampled_bits: f64, capacity: f64, confidence: f64) -> Self {
+        let size = load_factor * capacity;
+        if sampled_bits >= capacity {
+            return Self { size, margin: 0.0 };
+        }
+        // normal approximation of the sampled proportion, with finite population correction
+        let correction = ((capacity - sampled_bits) / (capacity - 1.0)).sqrt();
+        let std_error = (load_factor * (1.0 - load_factor) / sampled_bits).sqrt() * correction;
+        Self {
+            size,
+            margin: z_score(confidence) * std_error * capacity,
+        }
+    }
+
+    /// Returns the range `size - margin ..= size + margin`.
+    #[inline]
+    pub fn range(&self) -> std::ops::RangeInclusive<f64> {
+        (self.size - self.margin).max(0.0)..=self.size + self.margin
+    }
+}
+
+/// Returns the two-sided z-score of the given confidence level, e.g. `1.96` for `0.95`.
+fn z_score(confidence: f64) -> f64 {
+    assert!(
+        confidence > 0.0 && confidence < 1.0,
+        "confidence must be between 0 and 1 (exclusive)"
+    );
+    inverse_normal_cdf(0.5 + confidence / 2.0)
+}
+
+// Acklam's rational approximation, with a relative error below 1.15e-9.
+fn inverse_normal_cdf(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const LOW: f64 = 0.02425;
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
@@ -0,0 +1,149 @@
+use crate::*;
+
+/// A boolean expression over sets, evaluated in a single pass over the slots of every operand.
+///
+/// Each slot of the result is computed from the matching slots of the operands, without
+/// building a temporary set per operator. Operands may have different lengths: their
+/// missing slots are treated as empty. Atomic slots are loaded once each, with `Acquire` ordering.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{BitSet, Expr};
+///
+/// let a = [0b0011_u64, 1];
+/// let b = [0b0110_u64];
+/// let c = [0b0100_u64];
+///
+/// // (a | b) & !c
+/// let expr = Expr::set(&a).or(&b).and_not(&c);
+/// let mut out = [0; 2];
+/// expr.evaluate_into(&mut out);
+/// assert_eq!(out.iter_ones().collect::<Vec<_>>(), [0, 1, 64]);
+/// ```
+pub struct Expr<'a, S> {
+    node: Node<'a, S>,
+}
+
+enum Node<'a, S> {
+    Set(&'a [S]),
+    Not(Box<Node<'a, S>>),
+    Binary(Op, Box<Node<'a, S>>, Box<Node<'a, S>>),
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    And,
+    Or,
+    Xor,
+    AndNot,
+}
+
+impl<'a, S: Slot> Expr<'a, S> {
+    /// Creates an expression of a single set.
+    #[inline]
+    pub fn set(slots: &'a [S]) -> Self {
+        Self {
+            node: Node::Set(slots),
+        }
+    }
+
+    /// Returns the values in both `self` and `other`.
+    #[inline]
+    pub fn and(self, other: impl Into<Self>) -> Self {
+        self.binary(Op::And, other.into())
+    }
+
+    /// Returns the values in either `self` or `other`.
+    #[inline]
+    pub fn or(self, other: impl Into<Self>) -> Self {
+        self.binary(Op::Or, other.into())
+    }
+
+    /// Returns the values in exactly one of `self` and `other`.
+    #[inline]
+    pub fn xor(self, other: impl Into<Self>) -> Self {
+        self.binary(Op::Xor, other.into())
+    }
+
+    /// Returns the values in `self` but not in `other`.
+    ///
+    /// Same as `self.and(!other)`.
+    #[inline]
+    pub fn and_not(self, other: impl Into<Self>) -> Self {
+        self.binary(Op::AndNot, other.into())
+    }
+
+    /// Evaluates the expression into `out`, one slot at a time.
+    ///
+    /// Every slot of `out` is written, so the complement of an expression covers the
+    /// whole of `out`, even past the end of the operands.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::Expr;
+    ///
+    /// let allowed = [0b1111_u32];
+    /// let mut out = [0_u32; 2];
+    /// (!Expr::set(&allowed)).evaluate_into(&mut out);
+    /// assert_eq!(out, [!0b1111, u32::MAX]);
+    /// ```
+    pub fn evaluate_into(&self, out: &mut [S::Word]) {
+        for (slot_idx, word) in out.iter_mut().enumerate() {
+            *word = self.node.word(slot_idx);
+        }
+    }
+
+    #[inline]
+    fn binary(self, op: Op, other: Self) -> Self {
+        Self {
+            node: Node::Binary(op, Box::new(self.node), Box::new(other.node)),
+        }
+    }
+}
+
+impl<S: Slot> Node<'_, S> {
+    // evaluates the given slot
+    fn word(&self, slot_idx: usize) -> S::Word {
+        match self {
+            Node::Set(slots) => slots.get(slot_idx).map_or(S::Word::ZERO, Slot::load),
+            Node::Not(node) => node.word(slot_idx).not(),
+            Node::Binary(op, a, b) => {
+                let (a, b) = (a.word(slot_idx), b.word(slot_idx));
+                match op {
+                    Op::And => a.and(b),
+                    Op::Or => a.or(b),
+                    Op::Xor => a.xor(b),
+                    Op::AndNot => a.and(b.not()),
+                }
+            }
+        }
+    }
+}
+
+impl<'a, S: Slot> From<&'a [S]> for Expr<'a, S> {
+    #[inline]
+    fn from(slots: &'a [S]) -> Self {
+        Self::set(slots)
+    }
+}
+
+impl<'a, S: Slot, const N: usize> From<&'a [S; N]> for Expr<'a, S> {
+    #[inline]
+    fn from(slots: &'a [S; N]) -> Self {
+        Self::set(slots)
+    }
+}
+
+impl<S: Slot> std::ops::Not for Expr<'_, S> {
+    type Output = Self;
+
+    /// Returns the values missing from the expression.
+    #[inline]
+    fn not(self) -> Self {
+        Self {
+            node: Node::Not(Box::new(self.node)),
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::*;
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex};
+
+/// An identifier pool with a bounded FIFO queue of waiters.
+///
+/// When the pool is exhausted, [`acquire`](FairPool::acquire) waits in line, and
+/// [`release`](FairPool::release) hands the identifier directly to the oldest waiter,
+/// instead of letting every waiter race for it.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::FairPool;
+/// use std::sync::Arc;
+///
+/// let pool: Arc<FairPool<1>> = Arc::new(FairPool::new(16));
+/// while pool.try_acquire().is_some() {}
+///
+/// let waiter = {
+///     let pool = pool.clone();
+///     std::thread::spawn(move || pool.acquire())
+/// };
+/// while pool.waiters() == 0 {
+///     std::thread::yield_now();
+/// }
+/// pool.release(42);
+/// assert_eq!(waiter.join().unwrap(), Some(42));
+/// ```
+pub struct FairPool<const N: usize> {
+    bitset: AtomicBitSet<N>,
+    waiters: Mutex<VecDeque<Arc<Handoff>>>,
+    max_waiters: usize,
+}
+
+#[derive(Default)]
+struct Handoff {
+    id: Mutex<Option<usize>>,
+    ready: Condvar,
+}
+
+impl<const N: usize> FairPool<N> {
+    /// Creates an empty pool, where at most `max_waiters` callers can wait for an identifier.
+    #[inline]
+    pub const fn new(max_waiters: usize) -> Self {
+        Self {
+            bitset: AtomicBitSet::new(),
+            waiters: Mutex::new(VecDeque::new()),
+            max_waiters,
+        }
+    }
+
+    /// Returns the underlying bitset.
+    ///
+    /// Identifiers should be released with [`release`](FairPool::release),
+    /// otherwise they aren't handed to the waiters.
+    #[inline]
+    pub fn bitset(&self) -> &AtomicBitSet<N> {
+        &self.bitset
+    }
+
+    /// Returns the number of callers waiting for an identifier.
+    pub fn waiters(&self) -> usize {
+        self.lock_waiters().len()
+    }
+
+    /// Acquires a free identifier, without waiting.
+    ///
+    /// Returns `None` if the pool is exhausted.
+    #[inline]
+    pub fn try_acquire(&self) -> Option<usize> {
+        self.bitset.set_next_free_bit()
+    }
+
+    /// Acquires a free identifier, waiting for one to be released if the pool is exhausted.
+    ///
+    /// Returns `None` if the pool is exhausted and the queue of waiters is full.
+    pub fn acquire(&self) -> Option<usize> {
+        if let Some(id) = self.try_acquire() {
+            return Some(id);
+        }
+        let handoff = {
+            let mut waiters = self.lock_waiters();
+            // `release` holds the lock while deciding between freeing and handing off,
+            // so retrying under the lock can't miss a release.
+            if let Some(id) = self.try_acquire() {
+                return Some(id);
+            }
+            if waiters.len() >= self.max_waiters {
+                return None;
+            }
+            let handoff = Arc::new(Handoff::default());
+            waiters.push_back(handoff.clone());
+            handoff
+        };
+
+        let mut id = handoff.id.lock().unwrap_or_else(|err| err.into_inner());
+        loop {
+            if let Some(id) = *id {
+                return Some(id);
+            }
+            id = handoff
+                .ready
+                .wait(id)
+                .unwrap_or_else(|err| err.into_inner());
+        }
+    }
+
+    /// Releases an identifier, handing it to the oldest waiter if there is one.
+    pub fn release(&self, id: usize) {
+        let mut waiters = self.lock_waiters();
+        match waiters.pop_front() {
+            Some(handoff) => {
+                drop(waiters);
+                // the identifier stays allocated, and now belongs to the waiter
+                *handoff.id.lock().unwrap_or_else(|err| err.into_inner()) = Some(id);
+                handoff.ready.notify_one();
+            }
+            None => {
+                SharedBitSet::remove(&self.bitset[..], id);
+            }
+        }
+    }
+
+    fn lock_waiters(&self) -> std::sync::MutexGuard<'_, VecDeque<Arc<Handoff>>> {
+        self.waiters.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
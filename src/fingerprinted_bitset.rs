@@ -0,0 +1,309 @@
+use crate::*;
+use core::sync::atomic::AtomicU64;
+
+/// Wraps a shared bitset, and keeps its [`fingerprint`](BitSet::fingerprint) up to date
+/// on every change, so reading it is `O(1)`.
+///
+/// The fingerprint only tracks changes made through this wrapper.
+/// Bulk operations ([`clear`](SharedBitSet::clear), [`clear_where`](SharedBitSet::clear_where),
+/// [`import_at`](SharedBitSet::import_at), ...) are applied one value at a time,
+/// so they are slower than on the inner set.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{AtomicBitSet, BitSet, FingerprintedBitSet, SharedBitSet};
+///
+/// let replica: AtomicBitSet<4> = AtomicBitSet::new();
+/// let bitset = FingerprintedBitSet::new(&replica[..]);
+///
+/// bitset.insert(3);
+/// bitset.insert(200);
+/// bitset.remove(3);
+/// assert_eq!(bitset.fingerprint(), replica.fingerprint());
+/// ```
+pub struct FingerprintedBitSet<S> {
+    set: S,
+    hash: AtomicU64,
+}
+
+impl<S: BitSet<usize>> FingerprintedBitSet<S> {
+    /// Wraps `set`, computing its fingerprint once.
+    pub fn new(set: S) -> Self {
+        let hash = AtomicU64::new(set.fingerprint());
+        Self { set, hash }
+    }
+}
+
+impl<S> FingerprintedBitSet<S> {
+    /// Returns a reference to the inner set.
+    #[inline]
+    pub fn get_ref(&self) -> &S {
+        &self.set
+    }
+
+    /// Unwraps the inner set.
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.set
+    }
+
+    // records that `index` was inserted or removed
+    #[inline]
+    fn toggle(&self, index: usize) {
+        self.hash
+            .fetch_xor(util::hash_index(index as u128), Ordering::Release);
+    }
+}
+
+impl<S: BitSet<usize>> BitSet<usize> for FingerprintedBitSet<S> {
+    type Ones<'a>
+        = S::Ones<'a>
+    where
+        Self: 'a;
+    type Zeros<'a>
+        = S::Zeros<'a>
+    where
+        Self: 'a;
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.set.capacity()
+    }
+
+    #[inline]
+    fn has(&self, index: usize) -> bool {
+        self.set.has(index)
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    #[inline]
+    fn size(&self) -> usize {
+        self.set.size()
+    }
+
+    #[inline]
+    fn for_each_one(&self, f: impl FnMut(usize)) {
+        self.set.for_each_one(f)
+    }
+
+    #[inline]
+    fn first_zero(&self) -> Option<usize> {
+        self.set.first_zero()
+    }
+
+    #[inline]
+    fn free_count(&self) -> usize {
+        self.set.free_count()
+    }
+
+    #[inline]
+    fn remaining_in(&self, range: impl RangeBounds<usize>) -> usize {
+        self.set.remaining_in(range)
+    }
+
+    #[inline]
+    fn count_in_range(&self, range: impl RangeBounds<usize>) -> usize {
+        self.set.count_in_range(range)
+    }
+
+    #[inline]
+    fn load_factor(&self) -> f64 {
+        self.set.load_factor()
+    }
+
+    #[inline]
+    fn estimate_load_factor(&self, samples: usize) -> f64 {
+        self.set.estimate_load_factor(samples)
+    }
+
+    #[inline]
+    fn estimate_size(&self, confidence: f64) -> SizeEstimate {
+        self.set.estimate_size(confidence)
+    }
+
+    #[inline]
+    fn iter_ones_in(&self, range: impl RangeBounds<usize>) -> Self::Ones<'_> {
+        self.set.iter_ones_in(range)
+    }
+
+    #[inline]
+    fn iter_zeros_in(&self, range: impl RangeBounds<usize>) -> Self::Zeros<'_> {
+        self.set.iter_zeros_in(range)
+    }
+
+    #[inline]
+    fn has_all(&self, values: &[usize]) -> bool {
+        self.set.has_all(values)
+    }
+
+    #[inline]
+    fn has_any(&self, values: &[usize]) -> bool {
+        self.set.has_any(values)
+    }
+
+    #[inline]
+    fn gather(&self, values: &[usize], out: &mut [bool]) {
+        self.set.gather(values, out)
+    }
+
+    /// The fingerprint is maintained on every change, so this is `O(1)`.
+    #[inline]
+    fn fingerprint(&self) -> u64 {
+        self.hash.load(Ordering::Acquire)
+    }
+}
+
+impl<S> SharedBitSet<usize> for FingerprintedBitSet<S>
+where
+    S: SharedBitSet<usize> + BitSet<usize>,
+{
+    type Drain<'a>
+        = FingerprintedDrain<'a, S>
+    where
+        Self: 'a;
+
+    fn clear(&self) {
+        self.clear_where(|_| true);
+    }
+
+    #[inline]
+    fn insert(&self, index: usize) -> Option<bool> {
+        let had = self.set.insert(index)?;
+        if !had {
+            self.toggle(index);
+        }
+        Some(had)
+    }
+
+    #[inline]
+    fn remove(&self, index: usize) -> Option<bool> {
+        let had = self.set.remove(index)?;
+        if had {
+            self.toggle(index);
+        }
+        Some(had)
+    }
+
+    #[inline]
+    fn insert_with(&self, index: usize, order: Ordering) -> Option<bool> {
+        let had = self.set.insert_with(index, order)?;
+        if !had {
+            self.toggle(index);
+        }
+        Some(had)
+    }
+
+    #[inline]
+    fn remove_with(&self, index: usize, order: Ordering) -> Option<bool> {
+        let had = self.set.remove_with(index, order)?;
+        if had {
+            self.toggle(index);
+        }
+        Some(had)
+    }
+
+    #[inline]
+    fn insert_returning_word(&self, index: usize) -> Option<(bool, usize)> {
+        let (had, word) = self.set.insert_returning_word(index)?;
+        if !had {
+            self.toggle(index);
+        }
+        Some((had, word))
+    }
+
+    #[inline]
+    fn insert_transition(&self, index: usize) -> Option<SlotTransition> {
+        let transition = self.set.insert_transition(index)?;
+        if transition != SlotTransition::Unchanged {
+            self.toggle(index);
+        }
+        Some(transition)
+    }
+
+    #[inline]
+    fn remove_transition(&self, index: usize) -> Option<SlotTransition> {
+        let transition = self.set.remove_transition(index)?;
+        if transition != SlotTransition::Unchanged {
+            self.toggle(index);
+        }
+        Some(transition)
+    }
+
+    fn take_lowest(&self) -> Option<usize> {
+        let index = self.set.take_lowest()?;
+        self.toggle(index);
+        Some(index)
+    }
+
+    fn take_highest(&self) -> Option<usize> {
+        let index = self.set.take_highest()?;
+        self.toggle(index);
+        Some(index)
+    }
+
+    fn clear_where(&self, mut predicate: impl FnMut(usize) -> bool) -> usize {
+        // only the values removed by this call may be toggled, so remove them one by one
+        let mut cleared = 0;
+        self.set.for_each_one(|index| {
+            if predicate(index) && self.remove(index) == Some(true) {
+                cleared += 1;
+            }
+        });
+        cleared
+    }
+
+    fn import_at(&self, offset: usize, words: &[usize], policy: MergePolicy) -> Option<()> {
+        let bits = usize::BITS as usize;
+        assert!(
+            offset.is_multiple_of(bits),
+            "offset must be a multiple of {bits} bits"
+        );
+        let end = words.len().checked_mul(bits)?.checked_add(offset)?;
+        if end > self.capacity() {
+            return None;
+        }
+        for (slot_idx, &word) in words.iter().enumerate() {
+            let base = offset + slot_idx * bits;
+            for bit in 0..bits {
+                let index = base + bit;
+                match (word >> bit & 1 != 0, policy) {
+                    (true, MergePolicy::Overwrite | MergePolicy::Or) => self.insert(index),
+                    (false, MergePolicy::Overwrite | MergePolicy::And) => self.remove(index),
+                    _ => continue,
+                };
+            }
+        }
+        Some(())
+    }
+
+    #[inline]
+    fn drain(&self) -> Self::Drain<'_> {
+        FingerprintedDrain {
+            drain: self.set.drain(),
+            set: self,
+        }
+    }
+}
+
+/// An iterator that atomically removes the values of a [`FingerprintedBitSet`] as it yields them,
+/// updating its fingerprint, see [`SharedBitSet::drain`].
+pub struct FingerprintedDrain<'a, S: SharedBitSet<usize> + 'a> {
+    set: &'a FingerprintedBitSet<S>,
+    drain: S::Drain<'a>,
+}
+
+impl<'a, S: SharedBitSet<usize> + 'a> Iterator for FingerprintedDrain<'a, S> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        let index = self.drain.next()?;
+        self.set.toggle(index);
+        Some(index)
+    }
+}
@@ -0,0 +1,228 @@
+use crate::*;
+
+/// A bitset of exactly `len` bits, rather than a whole number of slots.
+///
+/// [`capacity`](BitSet::capacity), [`free_count`](BitSet::free_count),
+/// [`first_zero`](BitSet::first_zero), [`complement`](FixedBitSet::complement) and iteration
+/// all stop at `len`, instead of at the end of the last slot. With plain slots, a set of
+/// 1000 bits claims a capacity of 1024, and its 24 padding bits show up as free values.
+///
+/// The bits after `len` are always `0`.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{BitSet, BitSetMut, FixedBitSet};
+///
+/// let mut bitset = FixedBitSet::new(1000);
+/// assert_eq!(bitset.capacity(), 1000);
+/// assert_eq!(bitset.insert(1000), Err(15));
+///
+/// bitset.insert(3).unwrap();
+/// bitset.complement();
+/// assert_eq!(bitset.size(), 999);
+/// assert_eq!(bitset.iter_ones().next_back(), Some(999));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FixedBitSet {
+    slots: Box<[usize]>,
+    len: usize,
+}
+
+impl FixedBitSet {
+    /// Creates an empty set of `len` bits.
+    pub fn new(len: usize) -> Self {
+        Self {
+            slots: vec![0; slot_count::from_bits(len)].into(),
+            len,
+        }
+    }
+
+    /// Returns `true` if all of the `len` bits are set.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.first_zero().is_none()
+    }
+
+    /// Flips all of the `len` bits, leaving the bits after `len` unset.
+    pub fn complement(&mut self) {
+        for slot in self.slots.iter_mut() {
+            *slot = !*slot;
+        }
+        self.clear_padding();
+    }
+
+    // unsets the bits of the last slot that are after `len`
+    fn clear_padding(&mut self) {
+        let (slot_idx, bit) = util::split_index(self.len, usize::BITS);
+        if let Some(slot) = self.slots.get_mut(slot_idx) {
+            *slot &= util::bit_mask(0, bit);
+        }
+    }
+
+    // clamps `range` to `0..len`
+    fn clamp(&self, range: &impl RangeBounds<usize>) -> Range<usize> {
+        let (start, end) = util::bit_range(range, self.len as u128, |index| index as u128);
+        start as usize..end as usize
+    }
+}
+
+impl std::ops::Deref for FixedBitSet {
+    type Target = [usize];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.slots
+    }
+}
+
+impl BitSet<usize> for FixedBitSet {
+    type Ones<'a> = Ones<'a, usize>;
+    type Zeros<'a> = Zeros<'a, usize>;
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn has(&self, index: usize) -> bool {
+        BitSet::has(&self.slots[..], index)
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        BitSet::<usize>::is_empty(&self.slots[..])
+    }
+
+    #[inline]
+    fn size(&self) -> usize {
+        BitSet::size(&self.slots[..])
+    }
+
+    #[inline]
+    fn for_each_one(&self, f: impl FnMut(usize)) {
+        BitSet::for_each_one(&self.slots[..], f)
+    }
+
+    #[inline]
+    fn first_zero(&self) -> Option<usize> {
+        BitSet::first_zero(&self.slots[..]).filter(|&index| index < self.len)
+    }
+
+    #[inline]
+    fn free_count(&self) -> usize {
+        self.len - self.size()
+    }
+
+    #[inline]
+    fn remaining_in(&self, range: impl RangeBounds<usize>) -> usize {
+        BitSet::remaining_in(&self.slots[..], self.clamp(&range))
+    }
+
+    #[inline]
+    fn count_in_range(&self, range: impl RangeBounds<usize>) -> usize {
+        BitSet::count_in_range(&self.slots[..], range)
+    }
+
+    #[inline]
+    fn load_factor(&self) -> f64 {
+        if self.len == 0 {
+            return 0.0;
+        }
+        self.size() as f64 / self.len as f64
+    }
+
+    fn estimate_load_factor(&self, samples: usize) -> f64 {
+        if self.len == 0 {
+            return 0.0;
+        }
+        // the padding bits are sampled as unset, scale them out
+        let slot_bits = BitSet::capacity(&self.slots[..]);
+        let estimate = BitSet::<usize>::estimate_load_factor(&self.slots[..], samples);
+        (estimate * slot_bits as f64 / self.len as f64).min(1.0)
+    }
+
+    #[inline]
+    fn estimate_size(&self, confidence: f64) -> SizeEstimate {
+        BitSet::<usize>::estimate_size(&self.slots[..], confidence)
+    }
+
+    #[inline]
+    fn iter_ones_in(&self, range: impl RangeBounds<usize>) -> Self::Ones<'_> {
+        BitSet::iter_ones_in(&self.slots[..], range)
+    }
+
+    #[inline]
+    fn iter_zeros_in(&self, range: impl RangeBounds<usize>) -> Self::Zeros<'_> {
+        BitSet::iter_zeros_in(&self.slots[..], self.clamp(&range))
+    }
+
+    #[inline]
+    fn has_all(&self, values: &[usize]) -> bool {
+        BitSet::has_all(&self.slots[..], values)
+    }
+
+    #[inline]
+    fn has_any(&self, values: &[usize]) -> bool {
+        BitSet::has_any(&self.slots[..], values)
+    }
+
+    #[inline]
+    fn gather(&self, values: &[usize], out: &mut [bool]) {
+        BitSet::gather(&self.slots[..], values, out)
+    }
+}
+
+impl BitSetMut<usize> for FixedBitSet {
+    type Drain<'a> = Drain<'a, usize>;
+
+    #[inline]
+    fn clear(&mut self) {
+        BitSetMut::clear(&mut self.slots[..])
+    }
+
+    #[inline]
+    fn insert(&mut self, index: usize) -> Result<bool, usize> {
+        if index >= self.len {
+            return Err(index / usize::BITS as usize);
+        }
+        BitSetMut::insert(&mut self.slots[..], index)
+    }
+
+    #[inline]
+    fn remove(&mut self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+        BitSetMut::remove(&mut self.slots[..], index)
+    }
+
+    #[inline]
+    fn clear_where(&mut self, predicate: impl FnMut(usize) -> bool) -> usize {
+        BitSetMut::clear_where(&mut self.slots[..], predicate)
+    }
+
+    /// Bits of the fragment after `len` are dropped.
+    fn import_at(
+        &mut self,
+        offset: usize,
+        words: &[usize],
+        policy: MergePolicy,
+    ) -> Result<(), usize> {
+        BitSetMut::import_at(&mut self.slots[..], offset, words, policy)?;
+        self.clear_padding();
+        Ok(())
+    }
+
+    #[inline]
+    fn complement_in_place(&mut self) -> bool {
+        FixedBitSet::complement(self);
+        true
+    }
+
+    #[inline]
+    fn drain(&mut self) -> Self::Drain<'_> {
+        BitSetMut::drain(&mut self.slots[..])
+    }
+}
@@ -0,0 +1,232 @@
+use crate::*;
+
+/// An immutable snapshot of an atomic bitset, see [`AtomicBitSet::freeze`].
+///
+/// Reads don't need any atomic loads, so it can be shared across threads
+/// (e.g. behind an `Arc`) for read-heavy phases.
+///
+/// Dereferences to the underlying `[usize]` slots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrozenBitSet {
+    bitset: Box<[usize]>,
+    // number of set bits, computed once when frozen
+    size: usize,
+}
+
+impl FrozenBitSet {
+    pub(crate) fn new(slots: &[AtomicUsize]) -> Self {
+        let bitset: Box<[usize]> = slots
+            .iter()
+            .map(|slot| slot.load(Ordering::Acquire))
+            .collect();
+
+        let size = BitSet::size(&bitset[..]);
+        Self { bitset, size }
+    }
+
+    /// Returns an iterator over the values of the snapshot, in ascending order.
+    ///
+    /// Unlike [`iter_ones`](BitSet::iter_ones), the number of values is known up front,
+    /// so the iterator implements [`ExactSizeIterator`].
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, SharedBitSet};
+    ///
+    /// let bitset: AtomicBitSet<2> = AtomicBitSet::new();
+    /// for value in [3, 70, 100] {
+    ///     bitset.insert(value);
+    /// }
+    /// let frozen = bitset.freeze();
+    ///
+    /// let mut values = frozen.iter();
+    /// assert_eq!(values.len(), 3);
+    /// assert_eq!(values.next_back(), Some(100));
+    /// assert_eq!(values.len(), 2);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> FrozenOnes<'_> {
+        FrozenOnes {
+            ones: self.iter_ones(),
+            remaining: self.size,
+        }
+    }
+
+    /// Splits the snapshot into chunks of `chunk_bits` bits, each paired with the index of its first bit.
+    ///
+    /// See [`util::export_chunks`].
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, SharedBitSet};
+    ///
+    /// let bitset: AtomicBitSet<4> = AtomicBitSet::new();
+    /// bitset.insert(130);
+    ///
+    /// let bits = usize::BITS as usize;
+    /// let frozen = bitset.freeze();
+    /// for (offset, words) in frozen.export_chunks(2 * bits) {
+    ///     assert_eq!(words.len(), 2);
+    ///     assert_eq!(words.iter().any(|&word| word != 0), offset <= 130 && 130 < offset + 2 * bits);
+    /// }
+    /// ```
+    #[inline]
+    pub fn export_chunks(&self, chunk_bits: usize) -> impl Iterator<Item = (usize, &[usize])> {
+        util::export_chunks(&self.bitset, chunk_bits)
+    }
+}
+
+impl BitSet<usize> for FrozenBitSet {
+    type Ones<'a> = Ones<'a, usize>;
+    type Zeros<'a> = Zeros<'a, usize>;
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        BitSet::capacity(&self.bitset[..])
+    }
+
+    #[inline]
+    fn has(&self, index: usize) -> bool {
+        BitSet::has(&self.bitset[..], index)
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    #[inline]
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    #[inline]
+    fn for_each_one(&self, f: impl FnMut(usize)) {
+        BitSet::for_each_one(&self.bitset[..], f)
+    }
+
+    #[inline]
+    fn first_zero(&self) -> Option<usize> {
+        BitSet::first_zero(&self.bitset[..])
+    }
+
+    #[inline]
+    fn free_count(&self) -> usize {
+        self.capacity() - self.size
+    }
+
+    #[inline]
+    fn remaining_in(&self, range: impl RangeBounds<usize>) -> usize {
+        BitSet::remaining_in(&self.bitset[..], range)
+    }
+
+    #[inline]
+    fn count_in_range(&self, range: impl RangeBounds<usize>) -> usize {
+        BitSet::count_in_range(&self.bitset[..], range)
+    }
+
+    #[inline]
+    fn load_factor(&self) -> f64 {
+        if self.bitset.is_empty() {
+            return 0.0;
+        }
+        self.size as f64 / self.capacity() as f64
+    }
+
+    /// The size is known up front, so this is always exact.
+    #[inline]
+    fn estimate_load_factor(&self, _: usize) -> f64 {
+        self.load_factor()
+    }
+
+    /// The size is known up front, so this is always exact.
+    #[inline]
+    fn estimate_size(&self, _: f64) -> SizeEstimate {
+        SizeEstimate {
+            size: self.size as f64,
+            margin: 0.0,
+        }
+    }
+
+    #[inline]
+    fn iter_ones_in(&self, range: impl RangeBounds<usize>) -> Self::Ones<'_> {
+        BitSet::iter_ones_in(&self.bitset[..], range)
+    }
+
+    #[inline]
+    fn iter_zeros_in(&self, range: impl RangeBounds<usize>) -> Self::Zeros<'_> {
+        BitSet::iter_zeros_in(&self.bitset[..], range)
+    }
+
+    #[inline]
+    fn has_all(&self, values: &[usize]) -> bool {
+        BitSet::has_all(&self.bitset[..], values)
+    }
+
+    #[inline]
+    fn has_any(&self, values: &[usize]) -> bool {
+        BitSet::has_any(&self.bitset[..], values)
+    }
+
+    #[inline]
+    fn gather(&self, values: &[usize], out: &mut [bool]) {
+        BitSet::gather(&self.bitset[..], values, out)
+    }
+}
+
+impl std::ops::Deref for FrozenBitSet {
+    type Target = [usize];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.bitset
+    }
+}
+
+/// An iterator over the values of a [`FrozenBitSet`], in ascending order,
+/// see [`FrozenBitSet::iter`].
+pub struct FrozenOnes<'a> {
+    ones: Ones<'a, usize>,
+    remaining: usize,
+}
+
+impl Iterator for FrozenOnes<'_> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        let value = self.ones.next()?;
+        self.remaining -= 1;
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl DoubleEndedIterator for FrozenOnes<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<usize> {
+        let value = self.ones.next_back()?;
+        self.remaining -= 1;
+        Some(value)
+    }
+}
+
+impl ExactSizeIterator for FrozenOnes<'_> {}
+
+impl std::iter::FusedIterator for FrozenOnes<'_> {}
+
+impl<'a> IntoIterator for &'a FrozenBitSet {
+    type Item = usize;
+    type IntoIter = FrozenOnes<'a>;
+
+    #[inline]
+    fn into_iter(self) -> FrozenOnes<'a> {
+        self.iter()
+    }
+}
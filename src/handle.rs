@@ -0,0 +1,104 @@
+/// Packs an index and a generation counter into a single integer handle,
+/// e.g. to store it in a GPU buffer or an FFI struct.
+///
+/// The index takes the low `index_bits` bits, and the generation the `generation_bits` bits above.
+/// Layouts of at most 32 bits produce handles that fit in a `u32`.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::HandleLayout;
+///
+/// // 20 bits of index, 12 bits of generation
+/// const LAYOUT: HandleLayout = HandleLayout::new(20, 12);
+///
+/// let handle = LAYOUT.pack(1000, 3).unwrap() as u32;
+/// assert_eq!(LAYOUT.unpack(handle as u64), Some((1000, 3)));
+///
+/// // doesn't fit in 20 bits
+/// assert_eq!(LAYOUT.pack(1 << 20, 0), None);
+/// // generations wrap around
+/// assert_eq!(LAYOUT.next_generation(4095), 0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandleLayout {
+    index_bits: u32,
+    generation_bits: u32,
+}
+
+impl HandleLayout {
+    /// Creates a layout with `index_bits` bits of index and `generation_bits` bits of generation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index_bits` is zero or larger than `usize::BITS`,
+    /// or if the handle would take more than 64 bits.
+    pub const fn new(index_bits: u32, generation_bits: u32) -> Self {
+        assert!(
+            index_bits != 0 && index_bits <= usize::BITS,
+            "index bits must be in 1..=usize::BITS"
+        );
+        assert!(
+            index_bits + generation_bits <= u64::BITS,
+            "a handle must fit in 64 bits"
+        );
+        Self {
+            index_bits,
+            generation_bits,
+        }
+    }
+
+    /// Returns the number of bits of a handle.
+    #[inline]
+    pub const fn bits(&self) -> u32 {
+        self.index_bits + self.generation_bits
+    }
+
+    /// Returns the highest index that fits in a handle.
+    #[inline]
+    pub const fn max_index(&self) -> usize {
+        mask(self.index_bits) as usize
+    }
+
+    /// Returns the highest generation, after which [`next_generation`](HandleLayout::next_generation) wraps to `0`.
+    #[inline]
+    pub const fn max_generation(&self) -> u64 {
+        mask(self.generation_bits)
+    }
+
+    /// Packs `index` and `generation` into a handle.
+    ///
+    /// Returns `None` if either of them doesn't fit in its bits.
+    pub const fn pack(&self, index: usize, generation: u64) -> Option<u64> {
+        if index > self.max_index() || generation > self.max_generation() {
+            return None;
+        }
+        // shifting by 64 bits overflows, when the generation takes no bits
+        Some(generation.unbounded_shl(self.index_bits) | index as u64)
+    }
+
+    /// Unpacks a handle into its index and generation.
+    ///
+    /// Returns `None` if bits above [`bits`](HandleLayout::bits) are set.
+    pub const fn unpack(&self, handle: u64) -> Option<(usize, u64)> {
+        if handle > mask(self.bits()) {
+            return None;
+        }
+        let index = (handle & mask(self.index_bits)) as usize;
+        Some((index, handle.unbounded_shr(self.index_bits)))
+    }
+
+    /// Returns the generation following `generation`, wrapping around to `0` after
+    /// [`max_generation`](HandleLayout::max_generation).
+    ///
+    /// With zero generation bits, this is always `0`.
+    #[inline]
+    pub const fn next_generation(&self, generation: u64) -> u64 {
+        generation.wrapping_add(1) & self.max_generation()
+    }
+}
+
+// returns a word with the low `bits` bits set
+const fn mask(bits: u32) -> u64 {
+    u64::MAX.unbounded_shr(u64::BITS - bits)
+}
@@ -0,0 +1,547 @@
+use crate::*;
+use std::collections::TryReserveError;
+
+/// A growable bitset that stores sparse sets as a sorted list of values,
+/// and switches to dense slots once they get crowded (and back).
+///
+/// The list is converted to dense slots once it would take more than half of
+/// their memory, and back once it would take less than a quarter of it.
+/// Like `Vec<usize>`, [`insert`](BitSetMut::insert) grows the set instead of failing.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{BitSet, BitSetMut, HybridBitSet};
+///
+/// let mut bitset = HybridBitSet::new();
+/// bitset.insert(100_000).unwrap();
+/// assert!(!bitset.is_dense());
+///
+/// for value in 0..1000 {
+///     bitset.insert(value).unwrap();
+/// }
+/// assert!(bitset.is_dense());
+///
+/// bitset.clear_where(|value| value >= 100);
+/// assert!(!bitset.is_dense());
+/// assert!(bitset.iter_ones_in(..).eq(0..100));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HybridBitSet {
+    storage: Storage,
+    // number of slots of the dense form, it only grows (like the length of a `Vec`)
+    slots: usize,
+    size: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Storage {
+    // sorted, without duplicates
+    Sparse(Vec<usize>),
+    // always `slots` long
+    Dense(Vec<usize>),
+}
+
+impl Default for Storage {
+    #[inline]
+    fn default() -> Self {
+        Storage::Sparse(Vec::new())
+    }
+}
+
+impl HybridBitSet {
+    /// Creates an empty set, without allocating.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            storage: Storage::Sparse(Vec::new()),
+            slots: 0,
+            size: 0,
+        }
+    }
+
+    /// Returns `true` if the values are currently stored as dense slots.
+    #[inline]
+    pub fn is_dense(&self) -> bool {
+        matches!(self.storage, Storage::Dense(_))
+    }
+
+    /// Flips every value up to the [`capacity`](BitSet::capacity) of the set, like
+    /// [`BitSetMut::complement`].
+    ///
+    /// Returns an error, and leaves the set unchanged, if the slots of the complement
+    /// can't be allocated, e.g. for the huge capacity of a set holding `usize::MAX`.
+    /// [`BitSetMut::complement`] panics instead.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut, HybridBitSet};
+    ///
+    /// let mut bitset = HybridBitSet::new();
+    /// bitset.insert(3).unwrap();
+    ///
+    /// bitset.try_complement().unwrap();
+    /// assert_eq!(bitset.size(), bitset.capacity() - 1);
+    /// assert!(!bitset.has(3));
+    /// ```
+    pub fn try_complement(&mut self) -> Result<(), TryReserveError> {
+        // the slots are flipped in place, they have no bits past the capacity
+        self.make_dense()?;
+        let Storage::Dense(slots) = &mut self.storage else {
+            unreachable!()
+        };
+        BitSetMut::complement_in_place(&mut slots[..]);
+        self.size = (self.bits() - self.size as u128) as usize;
+        self.rebalance();
+        Ok(())
+    }
+
+    // converts the storage if the current form got too crowded or too sparse
+    fn rebalance(&mut self) {
+        match &self.storage {
+            Storage::Sparse(_) if self.size > self.slots / 2 => {
+                // the list is kept if the slots can't be allocated
+                let _ = self.make_dense();
+            }
+            Storage::Dense(slots) if self.size < self.slots / 4 => {
+                let mut values = Vec::with_capacity(self.size);
+                BitSet::for_each_one(&slots[..], |value| values.push(value));
+                self.storage = Storage::Sparse(values);
+            }
+            _ => {}
+        }
+    }
+
+    // fails, and stays sparse, if the slots can't be allocated, e.g. for the huge capacity
+    // of a set holding `usize::MAX`
+    fn make_dense(&mut self) -> Result<(), TryReserveError> {
+        if let Storage::Sparse(values) = &self.storage {
+            let mut slots = Vec::new();
+            slots.try_reserve_exact(self.slots)?;
+            slots.resize(self.slots, 0);
+            for &value in values {
+                let (slot_idx, bit) = util::split_index(value, usize::BITS);
+                slots[slot_idx] |= 1 << bit;
+            }
+            self.storage = Storage::Dense(slots);
+        }
+        Ok(())
+    }
+
+    // number of values the slots can hold, which may not fit in a `usize`
+    #[inline]
+    fn bits(&self) -> u128 {
+        self.slots as u128 * usize::BITS as u128
+    }
+
+    // grows the set so that it can hold `slots` slots
+    fn grow(&mut self, slots: usize) {
+        if slots > self.slots {
+            self.slots = slots;
+            if let Storage::Dense(dense) = &mut self.storage {
+                dense.resize(slots, 0);
+            }
+        }
+    }
+
+    // index of the first value of the sparse list that is `>= value`
+    fn position(values: &[usize], value: u128) -> usize {
+        values.partition_point(|&v| (v as u128) < value)
+    }
+}
+
+impl FromIterator<usize> for HybridBitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<usize> for HybridBitSet {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for value in iter {
+            // grows instead of failing
+            let _ = BitSetMut::insert(self, value);
+        }
+    }
+}
+
+impl BitSet<usize> for HybridBitSet {
+    type Ones<'a> = HybridOnes<'a>;
+    type Zeros<'a> = HybridZeros<'a>;
+
+    /// Saturates at `usize::MAX`, e.g. once the set holds `usize::MAX`.
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.slots.saturating_mul(usize::BITS as usize)
+    }
+
+    #[inline]
+    fn has(&self, index: usize) -> bool {
+        match &self.storage {
+            Storage::Sparse(values) => values.binary_search(&index).is_ok(),
+            Storage::Dense(slots) => BitSet::has(&slots[..], index),
+        }
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    #[inline]
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn for_each_one(&self, f: impl FnMut(usize)) {
+        match &self.storage {
+            Storage::Sparse(values) => values.iter().copied().for_each(f),
+            Storage::Dense(slots) => BitSet::for_each_one(&slots[..], f),
+        }
+    }
+
+    fn first_zero(&self) -> Option<usize> {
+        match &self.storage {
+            Storage::Sparse(values) => {
+                // values are sorted and unique, so the first gap is where `values[i] != i`
+                let index = values.partition_point(|&value| value < values.len());
+                let index = values[..index]
+                    .iter()
+                    .enumerate()
+                    .position(|(i, &value)| value != i)
+                    .unwrap_or(index);
+                ((index as u128) < self.bits()).then_some(index)
+            }
+            Storage::Dense(slots) => BitSet::first_zero(&slots[..]),
+        }
+    }
+
+    #[inline]
+    fn free_count(&self) -> usize {
+        self.capacity() - self.size
+    }
+
+    fn remaining_in(&self, range: impl RangeBounds<usize>) -> usize {
+        let (start, end) = util::bit_range(&range, self.bits(), |i| i as u128);
+        let remaining = end.saturating_sub(start) - self.count_in_range(range) as u128;
+        usize::try_from(remaining).unwrap_or(usize::MAX)
+    }
+
+    fn count_in_range(&self, range: impl RangeBounds<usize>) -> usize {
+        match &self.storage {
+            Storage::Sparse(values) => {
+                let (start, end) = util::bit_range(&range, self.bits(), |i| i as u128);
+                Self::position(values, end).saturating_sub(Self::position(values, start))
+            }
+            Storage::Dense(slots) => BitSet::count_in_range(&slots[..], range),
+        }
+    }
+
+    #[inline]
+    fn load_factor(&self) -> f64 {
+        if self.slots == 0 {
+            return 0.0;
+        }
+        self.size as f64 / self.bits() as f64
+    }
+
+    /// The size is always known, so this is always exact.
+    #[inline]
+    fn estimate_load_factor(&self, _: usize) -> f64 {
+        self.load_factor()
+    }
+
+    /// The size is always known, so this is always exact.
+    #[inline]
+    fn estimate_size(&self, _: f64) -> SizeEstimate {
+        SizeEstimate {
+            size: self.size as f64,
+            margin: 0.0,
+        }
+    }
+
+    fn iter_ones_in(&self, range: impl RangeBounds<usize>) -> Self::Ones<'_> {
+        match &self.storage {
+            Storage::Sparse(values) => {
+                let (start, end) = util::bit_range(&range, self.bits(), |i| i as u128);
+                let first = Self::position(values, start);
+                let last = Self::position(values, end).max(first);
+                HybridOnes(OnesInner::Sparse(values[first..last].iter()))
+            }
+            Storage::Dense(slots) => {
+                HybridOnes(OnesInner::Dense(BitSet::iter_ones_in(&slots[..], range)))
+            }
+        }
+    }
+
+    fn iter_zeros_in(&self, range: impl RangeBounds<usize>) -> Self::Zeros<'_> {
+        match &self.storage {
+            Storage::Sparse(values) => {
+                let (start, end) = util::bit_range(&range, self.bits(), |i| i as u128);
+                let first = Self::position(values, start);
+                let last = Self::position(values, end).max(first);
+                HybridZeros(ZerosInner::Sparse {
+                    range: start..end,
+                    values: values[first..last].iter(),
+                })
+            }
+            Storage::Dense(slots) => {
+                HybridZeros(ZerosInner::Dense(BitSet::iter_zeros_in(&slots[..], range)))
+            }
+        }
+    }
+
+    fn has_all(&self, values: &[usize]) -> bool {
+        match &self.storage {
+            Storage::Sparse(_) => values.iter().all(|&value| self.has(value)),
+            Storage::Dense(slots) => BitSet::has_all(&slots[..], values),
+        }
+    }
+
+    fn has_any(&self, values: &[usize]) -> bool {
+        match &self.storage {
+            Storage::Sparse(_) => values.iter().any(|&value| self.has(value)),
+            Storage::Dense(slots) => BitSet::has_any(&slots[..], values),
+        }
+    }
+
+    fn gather(&self, values: &[usize], out: &mut [bool]) {
+        match &self.storage {
+            Storage::Sparse(_) => {
+                assert_eq!(
+                    values.len(),
+                    out.len(),
+                    "`values` and `out` must have the same length"
+                );
+                for (out, &value) in out.iter_mut().zip(values) {
+                    *out = self.has(value);
+                }
+            }
+            Storage::Dense(slots) => BitSet::gather(&slots[..], values, out),
+        }
+    }
+}
+
+impl BitSetMut<usize> for HybridBitSet {
+    type Drain<'a> = HybridDrain<'a>;
+
+    #[inline]
+    fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    fn insert(&mut self, index: usize) -> Result<bool, usize> {
+        self.grow(index / usize::BITS as usize + 1);
+        let had = match &mut self.storage {
+            Storage::Sparse(values) => match values.binary_search(&index) {
+                Ok(_) => true,
+                Err(position) => {
+                    values.insert(position, index);
+                    false
+                }
+            },
+            Storage::Dense(slots) => BitSetMut::insert(&mut slots[..], index)?,
+        };
+        if !had {
+            self.size += 1;
+            self.rebalance();
+        }
+        Ok(had)
+    }
+
+    fn remove(&mut self, index: usize) -> Option<bool> {
+        if index as u128 >= self.bits() {
+            return None;
+        }
+        let had = match &mut self.storage {
+            Storage::Sparse(values) => match values.binary_search(&index) {
+                Ok(position) => {
+                    values.remove(position);
+                    true
+                }
+                Err(_) => false,
+            },
+            Storage::Dense(slots) => BitSetMut::remove(&mut slots[..], index)?,
+        };
+        if had {
+            self.size -= 1;
+            self.rebalance();
+        }
+        Some(had)
+    }
+
+    fn clear_where(&mut self, mut predicate: impl FnMut(usize) -> bool) -> usize {
+        let cleared = match &mut self.storage {
+            Storage::Sparse(values) => {
+                let len = values.len();
+                values.retain(|&value| !predicate(value));
+                len - values.len()
+            }
+            Storage::Dense(slots) => BitSetMut::clear_where(&mut slots[..], predicate),
+        };
+        self.size -= cleared;
+        self.rebalance();
+        cleared
+    }
+
+    fn import_at(
+        &mut self,
+        offset: usize,
+        words: &[usize],
+        policy: MergePolicy,
+    ) -> Result<(), usize> {
+        let (slots, end) = (self.slots, offset / usize::BITS as usize + words.len());
+        if !words.is_empty() {
+            self.grow(end);
+        }
+        // whole slots are merged in the dense form
+        if self.make_dense().is_err() {
+            // nothing is written, not even the capacity
+            self.slots = slots;
+            return Err(end.saturating_sub(1));
+        }
+        let Storage::Dense(slots) = &mut self.storage else {
+            unreachable!()
+        };
+        BitSetMut::import_at(&mut slots[..], offset, words, policy)?;
+        self.size = BitSet::size(&slots[..]);
+        self.rebalance();
+        Ok(())
+    }
+
+    fn complement_in_place(&mut self) -> bool {
+        self.try_complement()
+            .expect("the slots of the complement can't be allocated");
+        true
+    }
+
+    #[inline]
+    fn drain(&mut self) -> Self::Drain<'_> {
+        HybridDrain { set: self, next: 0 }
+    }
+}
+
+/// An iterator that removes the values of a [`HybridBitSet`] as it yields them, in ascending order.
+///
+/// The set is [cleared](BitSetMut::clear) when the iterator is dropped.
+pub struct HybridDrain<'a> {
+    set: &'a mut HybridBitSet,
+    // position in the sparse list, or the lowest value that wasn't yielded yet
+    next: usize,
+}
+
+impl Iterator for HybridDrain<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        match &self.set.storage {
+            Storage::Sparse(values) => {
+                let value = *values.get(self.next)?;
+                self.next += 1;
+                Some(value)
+            }
+            Storage::Dense(slots) => {
+                let value = BitSet::iter_ones_in(&slots[..], self.next..).next()?;
+                self.next = value + 1;
+                Some(value)
+            }
+        }
+    }
+}
+
+impl std::iter::FusedIterator for HybridDrain<'_> {}
+
+impl Drop for HybridDrain<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        self.set.clear();
+    }
+}
+
+/// An iterator over the values of a [`HybridBitSet`], in ascending order.
+pub struct HybridOnes<'a>(OnesInner<'a>);
+
+enum OnesInner<'a> {
+    Sparse(std::slice::Iter<'a, usize>),
+    Dense(Ones<'a, usize>),
+}
+
+impl Iterator for HybridOnes<'_> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        match &mut self.0 {
+            OnesInner::Sparse(values) => values.next().copied(),
+            OnesInner::Dense(ones) => ones.next(),
+        }
+    }
+}
+
+impl DoubleEndedIterator for HybridOnes<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<usize> {
+        match &mut self.0 {
+            OnesInner::Sparse(values) => values.next_back().copied(),
+            OnesInner::Dense(ones) => ones.next_back(),
+        }
+    }
+}
+
+impl std::iter::FusedIterator for HybridOnes<'_> {}
+
+/// An iterator over the values missing from a [`HybridBitSet`], in ascending order.
+pub struct HybridZeros<'a>(ZerosInner<'a>);
+
+enum ZerosInner<'a> {
+    Sparse {
+        // may end past `usize::MAX`, see `HybridBitSet::bits`
+        range: Range<u128>,
+        // values of the set within `range`
+        values: std::slice::Iter<'a, usize>,
+    },
+    Dense(Zeros<'a, usize>),
+}
+
+impl Iterator for HybridZeros<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        match &mut self.0 {
+            ZerosInner::Sparse { range, values } => {
+                for index in range.by_ref().map(|index| index as usize) {
+                    if values.as_slice().first() == Some(&index) {
+                        values.next();
+                    } else {
+                        return Some(index);
+                    }
+                }
+                None
+            }
+            ZerosInner::Dense(zeros) => zeros.next(),
+        }
+    }
+}
+
+impl DoubleEndedIterator for HybridZeros<'_> {
+    fn next_back(&mut self) -> Option<usize> {
+        match &mut self.0 {
+            ZerosInner::Sparse { range, values } => {
+                while let Some(index) = range.next_back().map(|index| index as usize) {
+                    if values.as_slice().last() == Some(&index) {
+                        values.next_back();
+                    } else {
+                        return Some(index);
+                    }
+                }
+                None
+            }
+            ZerosInner::Dense(zeros) => zeros.next_back(),
+        }
+    }
+}
+
+impl std::iter::FusedIterator for HybridZeros<'_> {}
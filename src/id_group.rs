@@ -0,0 +1,65 @@
+use crate::*;
+
+/// A group of identifiers, allocated together and released together when dropped.
+///
+/// See [`AtomicBitSet::allocate_group`].
+///
+/// Dereferences to the allocated identifiers.
+#[derive(Debug)]
+pub struct IdGroup<'a> {
+    bitset: &'a [AtomicUsize],
+    ids: Vec<usize>,
+}
+
+impl<'a> IdGroup<'a> {
+    /// Allocates `k` identifiers from `bitset`, or none of them.
+    pub(crate) fn allocate(
+        bitset: &'a [AtomicUsize],
+        k: usize,
+        mut set_next_free_bit: impl FnMut() -> Option<usize>,
+    ) -> Option<Self> {
+        let mut group = Self {
+            bitset,
+            ids: Vec::with_capacity(k),
+        };
+        for _ in 0..k {
+            // on failure, dropping the group releases the identifiers allocated so far
+            group.ids.push(set_next_free_bit()?);
+        }
+        Some(group)
+    }
+
+    /// Keeps the identifiers allocated, and returns them.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, BitSet};
+    ///
+    /// let bitset: AtomicBitSet<1> = AtomicBitSet::new();
+    /// let ids = bitset.allocate_group(2).unwrap().leak();
+    ///
+    /// assert_eq!(ids, [0, 1]);
+    /// assert_eq!(bitset.size(), 2);
+    /// ```
+    pub fn leak(mut self) -> Vec<usize> {
+        std::mem::take(&mut self.ids)
+    }
+}
+
+impl std::ops::Deref for IdGroup<'_> {
+    type Target = [usize];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.ids
+    }
+}
+
+impl Drop for IdGroup<'_> {
+    fn drop(&mut self) {
+        for &id in &self.ids {
+            SharedBitSet::remove(self.bitset, id);
+        }
+    }
+}
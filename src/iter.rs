@@ -0,0 +1,662 @@
+use crate::*;
+
+/// An iterator over the values of a set, in ascending order.
+///
+/// Created by [`BitSet::iter_ones`] and [`BitSet::iter_ones_in`].
+/// Use [`rev`](Iterator::rev) to iterate from the highest value.
+///
+/// Each slot is loaded once, when the iterator reaches it from either end.
+/// Slots entirely outside of the iterated range are never loaded.
+pub struct Ones<'a, S: Slot> {
+    slots: &'a [S],
+    // slots that weren't loaded yet
+    pending: Range<usize>,
+    // the slots being iterated from each end, and their remaining bits
+    front: Option<(usize, S::Word)>,
+    back: Option<(usize, S::Word)>,
+    // bits to keep in the first and the last slot of the range
+    first: (usize, S::Word),
+    last: (usize, S::Word),
+    // iterate over the unset bits instead, see `Zeros`
+    invert: bool,
+}
+
+impl<'a, S: Slot> Ones<'a, S> {
+    /// Iterates over the values in the bit range `start..end`, which must be
+    /// within the capacity of `slots`.
+    #[inline]
+    pub(crate) fn new(slots: &'a [S], range: (u128, u128)) -> Self {
+        Self::with_inverted(slots, range, false)
+    }
+
+    /// Same as [`Ones::new`], but iterates over the unset bits if `invert` is `true`.
+    pub(crate) fn with_inverted(slots: &'a [S], (start, end): (u128, u128), invert: bool) -> Self {
+        let bits = S::Word::BITS as u128;
+        if start >= end {
+            return Self {
+                slots,
+                pending: 0..0,
+                front: None,
+                back: None,
+                first: (0, S::Word::MAX),
+                last: (0, S::Word::MAX),
+                invert,
+            };
+        }
+        let first_slot = (start / bits) as usize;
+        let last_slot = ((end - 1) / bits) as usize;
+        let first_mask = S::Word::mask((start % bits) as u32, S::Word::BITS);
+        let last_mask = S::Word::mask(0, ((end - 1) % bits) as u32 + 1);
+
+        Self {
+            slots,
+            pending: first_slot..last_slot + 1,
+            front: None,
+            back: None,
+            first: (first_slot, first_mask),
+            last: (last_slot, last_mask),
+            invert,
+        }
+    }
+
+    // loads the given slot, keeping only the bits within the range
+    fn load(&self, slot_idx: usize) -> S::Word {
+        let mut word = self.slots[slot_idx].load();
+        if self.invert {
+            word = word.not();
+        }
+        if slot_idx == self.first.0 {
+            word = word.and(self.first.1);
+        }
+        if slot_idx == self.last.0 {
+            word = word.and(self.last.1);
+        }
+        word
+    }
+}
+
+impl<S: Slot> Iterator for Ones<'_, S> {
+    type Item = S::Word;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(index) = take_lowest(&mut self.front) {
+                return Some(index);
+            }
+            match self.pending.next() {
+                Some(slot_idx) => self.front = Some((slot_idx, self.load(slot_idx))),
+                // the remaining bits, if any, are in the slot loaded from the back
+                None => return take_lowest(&mut self.back),
+            }
+        }
+    }
+}
+
+impl<S: Slot> DoubleEndedIterator for Ones<'_, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(index) = take_highest(&mut self.back) {
+                return Some(index);
+            }
+            match self.pending.next_back() {
+                Some(slot_idx) => self.back = Some((slot_idx, self.load(slot_idx))),
+                // the remaining bits, if any, are in the slot loaded from the front
+                None => return take_highest(&mut self.front),
+            }
+        }
+    }
+}
+
+// removes the lowest remaining bit of a loaded slot, and returns its index
+#[inline]
+fn take_lowest<W: Word>(slot: &mut Option<(usize, W)>) -> Option<W> {
+    let (slot_idx, word) = slot.as_mut()?;
+    if *word == W::ZERO {
+        return None;
+    }
+    let bit = word.trailing_zeros();
+    *word = word.without_bit(bit);
+    Some(W::from_index(*slot_idx, bit))
+}
+
+// removes the highest remaining bit of a loaded slot, and returns its index
+#[inline]
+fn take_highest<W: Word>(slot: &mut Option<(usize, W)>) -> Option<W> {
+    let (slot_idx, word) = slot.as_mut()?;
+    if *word == W::ZERO {
+        return None;
+    }
+    let bit = W::BITS - 1 - word.leading_zeros();
+    *word = word.without_bit(bit);
+    Some(W::from_index(*slot_idx, bit))
+}
+
+impl<S: Slot> std::iter::FusedIterator for Ones<'_, S> {}
+
+/// An iterator over the values missing from a set, in ascending order.
+///
+/// Created by [`BitSet::iter_zeros`] and [`BitSet::iter_zeros_in`].
+///
+/// Like [`Ones`], each slot is loaded once, when the iterator reaches it.
+pub struct Zeros<'a, S: Slot>(Ones<'a, S>);
+
+impl<'a, S: Slot> Zeros<'a, S> {
+    /// Iterates over the unset bits in the bit range `start..end`, which must be
+    /// within the capacity of `slots`.
+    #[inline]
+    pub(crate) fn new(slots: &'a [S], range: (u128, u128)) -> Self {
+        Self(Ones::with_inverted(slots, range, true))
+    }
+}
+
+impl<S: Slot> Iterator for Zeros<'_, S> {
+    type Item = S::Word;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+impl<S: Slot> DoubleEndedIterator for Zeros<'_, S> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<S: Slot> std::iter::FusedIterator for Zeros<'_, S> {}
+
+/// An iterator over a snapshot of the values of an [`AtomicBitSet`], in ascending order.
+///
+/// Created by iterating over `&AtomicBitSet`. Every slot is loaded once (with `Acquire`
+/// ordering) when the iterator is created, so later modifications are not observed.
+pub struct SnapshotOnes<const N: usize> {
+    words: [usize; N],
+    // slots that weren't reached yet
+    pending: Range<usize>,
+    // the slots being iterated from each end, and their remaining bits
+    front: Option<(usize, usize)>,
+    back: Option<(usize, usize)>,
+}
+
+impl<const N: usize> SnapshotOnes<N> {
+    #[inline]
+    pub(crate) fn new(slots: &[AtomicUsize; N]) -> Self {
+        Self {
+            words: std::array::from_fn(|i| slots[i].load(Ordering::Acquire)),
+            pending: 0..N,
+            front: None,
+            back: None,
+        }
+    }
+}
+
+impl<const N: usize> Iterator for SnapshotOnes<N> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            if let Some(index) = take_lowest(&mut self.front) {
+                return Some(index);
+            }
+            match self.pending.next() {
+                Some(slot_idx) => self.front = Some((slot_idx, self.words[slot_idx])),
+                None => return take_lowest(&mut self.back),
+            }
+        }
+    }
+}
+
+impl<const N: usize> DoubleEndedIterator for SnapshotOnes<N> {
+    fn next_back(&mut self) -> Option<usize> {
+        loop {
+            if let Some(index) = take_highest(&mut self.back) {
+                return Some(index);
+            }
+            match self.pending.next_back() {
+                Some(slot_idx) => self.back = Some((slot_idx, self.words[slot_idx])),
+                None => return take_highest(&mut self.front),
+            }
+        }
+    }
+}
+
+impl<const N: usize> std::iter::FusedIterator for SnapshotOnes<N> {}
+
+/// An iterator over the maximal runs of consecutive values of a set, in ascending order.
+///
+/// Created by [`BitSet::runs`].
+pub struct Runs<I: Iterator> {
+    values: std::iter::Peekable<I>,
+}
+
+impl<I: Iterator> Runs<I> {
+    #[inline]
+    pub(crate) fn new(values: I) -> Self {
+        Self {
+            values: values.peekable(),
+        }
+    }
+}
+
+impl<I> Iterator for Runs<I>
+where
+    I: Iterator,
+    I::Item: Word,
+{
+    type Item = Range<I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.values.next()?;
+        let mut end = start.to_u128() + 1;
+        while self
+            .values
+            .next_if(|value| value.to_u128() == end)
+            .is_some()
+        {
+            end += 1;
+        }
+        assert!(
+            end <= I::Item::MAX.to_u128(),
+            "the end of a run must be representable by the index type"
+        );
+        Some(start..I::Item::from_u128(end))
+    }
+}
+
+impl<I> std::iter::FusedIterator for Runs<I>
+where
+    I: Iterator,
+    I::Item: Word,
+{
+}
+
+/// An iterator that removes the values of a set as it yields them, in ascending order.
+///
+/// Created by [`BitSetMut::drain`]. Each slot is cleared when the iterator reaches it,
+/// and the slots that weren't reached are cleared when the iterator is dropped.
+pub struct Drain<'a, T: Word> {
+    slots: std::iter::Enumerate<std::slice::IterMut<'a, T>>,
+    current: Option<(usize, T)>,
+}
+
+impl<'a, T: Word> Drain<'a, T> {
+    #[inline]
+    pub(crate) fn new(slots: &'a mut [T]) -> Self {
+        // the slots past the addressable ones hold no values, but are cleared too
+        let (slots, rest) = slots.split_at_mut(util::addressable_len::<T>(slots.len()));
+        rest.fill(T::ZERO);
+        Self {
+            slots: slots.iter_mut().enumerate(),
+            current: None,
+        }
+    }
+}
+
+impl<T: Word> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            if let Some(index) = take_lowest(&mut self.current) {
+                return Some(index);
+            }
+            let (slot_idx, slot) = self.slots.next()?;
+            self.current = Some((slot_idx, std::mem::replace(slot, T::ZERO)));
+        }
+    }
+}
+
+impl<T: Word> std::iter::FusedIterator for Drain<'_, T> {}
+
+impl<T: Word> Drop for Drain<'_, T> {
+    fn drop(&mut self) {
+        for (_, slot) in self.slots.by_ref() {
+            *slot = T::ZERO;
+        }
+    }
+}
+
+/// An iterator that atomically removes the values of a shared set as it yields them,
+/// in ascending order.
+///
+/// Created by [`SharedBitSet::drain`]. Each slot is taken with a single atomic swap
+/// when the iterator reaches it, so concurrent consumers never yield the same value.
+/// Slots that weren't reached when the iterator is dropped are left untouched.
+pub struct SharedDrain<'a, S: AtomicSlot> {
+    slots: std::iter::Enumerate<std::slice::Iter<'a, S>>,
+    current: Option<(usize, S::Word)>,
+}
+
+impl<'a, S: AtomicSlot> SharedDrain<'a, S> {
+    #[inline]
+    pub(crate) fn new(slots: &'a [S]) -> Self {
+        Self {
+            slots: slots.iter().enumerate(),
+            current: None,
+        }
+    }
+}
+
+impl<S: AtomicSlot> Iterator for SharedDrain<'_, S> {
+    type Item = S::Word;
+
+    fn next(&mut self) -> Option<S::Word> {
+        loop {
+            if let Some(index) = take_lowest(&mut self.current) {
+                return Some(index);
+            }
+            let (slot_idx, slot) = self.slots.next()?;
+            // don't write to empty slots, other threads may be reading them
+            if slot.load() != S::Word::ZERO {
+                self.current = Some((slot_idx, slot.take()));
+            }
+        }
+    }
+}
+
+impl<S: AtomicSlot> std::iter::FusedIterator for SharedDrain<'_, S> {}
+
+/// An iterator over the values of a set, each paired with the matching element of
+/// a companion slice, in ascending order.
+///
+/// Created by [`BitSet::iter_occupied_with`].
+pub struct OccupiedWith<'a, I, V> {
+    values: I,
+    // the elements after the last yielded one, starting at index `base`
+    rest: &'a mut [V],
+    base: usize,
+}
+
+impl<'a, I, V> OccupiedWith<'a, I, V> {
+    #[inline]
+    pub(crate) fn new(values: I, companion: &'a mut [V]) -> Self {
+        Self {
+            values,
+            rest: companion,
+            base: 0,
+        }
+    }
+}
+
+impl<'a, I, V> Iterator for OccupiedWith<'a, I, V>
+where
+    I: Iterator,
+    I::Item: Word,
+{
+    type Item = (I::Item, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.values.next()?;
+        let index = value.to_u128() as usize;
+        let rest = std::mem::take(&mut self.rest);
+        assert!(
+            index - self.base < rest.len(),
+            "the companion slice is shorter than the set"
+        );
+        let (element, rest) = rest[index - self.base..].split_first_mut()?;
+        self.rest = rest;
+        self.base = index + 1;
+        Some((value, element))
+    }
+}
+
+impl<I, V> std::iter::FusedIterator for OccupiedWith<'_, I, V>
+where
+    I: std::iter::FusedIterator,
+    I::Item: Word,
+{
+}
+
+/// A value that is only in one of two sets, see [`util::diff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Change<T> {
+    /// The value is only in the new set.
+    Added(T),
+    /// The value is only in the old set.
+    Removed(T),
+}
+
+/// An iterator over the values that differ between two sets, in ascending order.
+///
+/// Created by [`util::diff`]. Each pair of slots is loaded once, and XOR-ed
+/// to find the changed bits.
+pub struct Diff<'a, S: Slot> {
+    old: &'a [S],
+    new: &'a [S],
+    next_slot: usize,
+    // the changed bits of the current slot that weren't yielded yet
+    current: Option<(usize, S::Word)>,
+    // the new word of the current slot
+    new_word: S::Word,
+}
+
+impl<'a, S: Slot> Diff<'a, S> {
+    #[inline]
+    pub(crate) fn new(old: &'a [S], new: &'a [S]) -> Self {
+        Self {
+            old,
+            new,
+            next_slot: 0,
+            current: None,
+            new_word: S::Word::ZERO,
+        }
+    }
+}
+
+impl<S: Slot> Iterator for Diff<'_, S> {
+    type Item = Change<S::Word>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((slot_idx, changed)) = &mut self.current
+                && *changed != S::Word::ZERO
+            {
+                let bit = changed.trailing_zeros();
+                *changed = changed.without_bit(bit);
+                let value = S::Word::from_index(*slot_idx, bit);
+                return Some(if self.new_word.without_bit(bit) != self.new_word {
+                    Change::Added(value)
+                } else {
+                    Change::Removed(value)
+                });
+            }
+            let slot_idx = self.next_slot;
+            if slot_idx >= self.old.len().max(self.new.len()) {
+                return None;
+            }
+            self.next_slot += 1;
+            // the shorter set is padded with unset bits
+            let load = |slots: &[S]| slots.get(slot_idx).map_or(S::Word::ZERO, Slot::load);
+            let (old, new) = (load(self.old), load(self.new));
+            self.current = Some((slot_idx, old.xor(new)));
+            self.new_word = new;
+        }
+    }
+}
+
+impl<S: Slot> std::iter::FusedIterator for Diff<'_, S> {}
+
+/// An iterator over the union of several sets, in ascending order.
+///
+/// Created by [`util::union_iter`]. The words of each slot are loaded and OR-ed
+/// together when the iterator reaches it, so the union is never materialized.
+pub struct UnionIter<'a, S: Slot> {
+    sets: &'a [&'a [S]],
+    next_slot: usize,
+    // the longest of the sets
+    len: usize,
+    current: Option<(usize, S::Word)>,
+}
+
+impl<'a, S: Slot> UnionIter<'a, S> {
+    #[inline]
+    pub(crate) fn new(sets: &'a [&'a [S]]) -> Self {
+        Self {
+            sets,
+            next_slot: 0,
+            len: util::addressable_len::<S::Word>(
+                sets.iter().map(|set| set.len()).max().unwrap_or(0),
+            ),
+            current: None,
+        }
+    }
+}
+
+impl<S: Slot> Iterator for UnionIter<'_, S> {
+    type Item = S::Word;
+
+    fn next(&mut self) -> Option<S::Word> {
+        loop {
+            if let Some(index) = take_lowest(&mut self.current) {
+                return Some(index);
+            }
+            let slot_idx = self.next_slot;
+            if slot_idx >= self.len {
+                return None;
+            }
+            self.next_slot += 1;
+            let word = self
+                .sets
+                .iter()
+                .filter_map(|set| set.get(slot_idx))
+                .fold(S::Word::ZERO, |word, slot| word.or(slot.load()));
+            self.current = Some((slot_idx, word));
+        }
+    }
+}
+
+impl<S: Slot> std::iter::FusedIterator for UnionIter<'_, S> {}
+
+/// An iterator over the values of either of two iterators, in ascending order.
+///
+/// Iterates the views of [`UnionView`](crate::UnionView) and [`IntersectionView`](crate::IntersectionView).
+/// Both iterators must be in ascending order, and a value yielded by both is yielded once.
+pub struct Merged<A: Iterator, B: Iterator<Item = A::Item>> {
+    a: A,
+    b: B,
+    // values pulled from either end of `a` and `b`, but not yielded yet
+    a_front: Option<A::Item>,
+    a_back: Option<A::Item>,
+    b_front: Option<A::Item>,
+    b_back: Option<A::Item>,
+}
+
+impl<A: Iterator, B: Iterator<Item = A::Item>> Merged<A, B> {
+    #[inline]
+    pub(crate) fn new(a: A, b: B) -> Self {
+        Self {
+            a,
+            b,
+            a_front: None,
+            a_back: None,
+            b_front: None,
+            b_back: None,
+        }
+    }
+}
+
+impl<A, B> Iterator for Merged<A, B>
+where
+    A: Iterator,
+    B: Iterator<Item = A::Item>,
+    A::Item: Word,
+{
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<A::Item> {
+        let a = peek(&mut self.a_front, &mut self.a_back, || self.a.next());
+        let b = peek(&mut self.b_front, &mut self.b_back, || self.b.next());
+        match (a, b) {
+            (Some(a), Some(b)) if a.to_u128() > b.to_u128() => self.b_front.take(),
+            (Some(a), Some(b)) if a == b => {
+                self.b_front = None;
+                self.a_front.take()
+            }
+            (Some(_), _) => self.a_front.take(),
+            (None, _) => self.b_front.take(),
+        }
+    }
+}
+
+impl<A, B> DoubleEndedIterator for Merged<A, B>
+where
+    A: DoubleEndedIterator,
+    B: DoubleEndedIterator<Item = A::Item>,
+    A::Item: Word,
+{
+    fn next_back(&mut self) -> Option<A::Item> {
+        let a = peek(&mut self.a_back, &mut self.a_front, || self.a.next_back());
+        let b = peek(&mut self.b_back, &mut self.b_front, || self.b.next_back());
+        match (a, b) {
+            (Some(a), Some(b)) if a.to_u128() < b.to_u128() => self.b_back.take(),
+            (Some(a), Some(b)) if a == b => {
+                self.b_back = None;
+                self.a_back.take()
+            }
+            (Some(_), _) => self.a_back.take(),
+            (None, _) => self.b_back.take(),
+        }
+    }
+}
+
+// pulls the next value from one end into `near`, falling back to the value pulled
+// from the other end once the iterator is exhausted
+#[inline]
+fn peek<T: Copy>(
+    near: &mut Option<T>,
+    far: &mut Option<T>,
+    next: impl FnOnce() -> Option<T>,
+) -> Option<T> {
+    if near.is_none() {
+        *near = next().or_else(|| far.take());
+    }
+    *near
+}
+
+/// An iterator over the values of another iterator that are in a set, or that are missing
+/// from it.
+///
+/// Iterates the views of [`UnionView`](crate::UnionView) and [`IntersectionView`](crate::IntersectionView).
+pub struct Filtered<'a, I, S: ?Sized> {
+    iter: I,
+    set: &'a S,
+    // whether the yielded values are in the set
+    has: bool,
+}
+
+impl<'a, I, S: ?Sized> Filtered<'a, I, S> {
+    #[inline]
+    pub(crate) fn new(iter: I, set: &'a S, has: bool) -> Self {
+        Self { iter, set, has }
+    }
+}
+
+impl<T, I, S> Iterator for Filtered<'_, I, S>
+where
+    T: Copy,
+    I: Iterator<Item = T>,
+    S: BitSet<T> + ?Sized,
+{
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        let (set, has) = (self.set, self.has);
+        self.iter.find(|&value| set.has(value) == has)
+    }
+}
+
+impl<T, I, S> DoubleEndedIterator for Filtered<'_, I, S>
+where
+    T: Copy,
+    I: DoubleEndedIterator<Item = T>,
+    S: BitSet<T> + ?Sized,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        let (set, has) = (self.set, self.has);
+        self.iter.rfind(|&value| set.has(value) == has)
+    }
+}
@@ -1,18 +1,103 @@
 #![doc = include_str!("../README.md")]
 // #![no_std]
 
+mod admission;
 mod atomic_bitset;
+mod bit_array;
+mod bit_io;
 mod bitset;
 mod bitset_mut;
+mod block_bitmap;
+#[cfg(feature = "checked")]
+mod checked;
+mod chunked_bitset;
+mod clamped;
+mod complement;
+mod cpu_set;
+mod error;
+mod estimate;
+mod expr;
+mod fair_pool;
+mod fingerprinted_bitset;
+mod fixed_bitset;
+mod frozen_bitset;
+mod handle;
+mod hybrid_bitset;
+mod id_group;
+mod iter;
+mod merge_policy;
+mod namespaced_id_set;
+#[cfg(feature = "rayon")]
+mod par;
+mod port_allocator;
+mod reference_set;
 mod shared_bitset;
-mod utils;
+mod small_bitset;
+mod snapshot_stats;
+mod static_pools;
+mod summarized_bitset;
+#[cfg(feature = "timestamps")]
+mod timed_bitset;
+mod transaction;
+mod views;
+mod word;
 
 /// A module that provides functions to calculate the number of slots.
 pub mod slot_count;
 
+/// A module that provides fallible allocation functions for `Vec<T>` backed bitsets.
+pub mod bitvec;
+
+/// A module that provides conversions from and to Apache Arrow validity bitmaps.
+pub mod arrow;
+
+/// A module that provides the slot and bit index math used by this crate.
+pub mod util;
+
+pub use admission::{AdmissionPolicy, MaxOccupancy};
 pub use atomic_bitset::AtomicBitSet;
+pub use bit_array::BitArray;
+pub use bit_io::{BitReader, BitWriter};
 pub use bitset::BitSet;
 pub use bitset_mut::BitSetMut;
-pub use shared_bitset::SharedBitSet;
+pub use block_bitmap::BlockBitmap;
+#[cfg(feature = "checked")]
+pub use checked::CheckedBitSet;
+pub use chunked_bitset::{ChunkedBitSet, ChunkedDrain, ChunkedOnes, ChunkedZeros};
+pub use clamped::Clamped;
+pub use complement::Complement;
+pub use cpu_set::CpuSet;
+pub use error::{AllocError, ClaimError, InsertError};
+pub use estimate::SizeEstimate;
+pub use expr::Expr;
+pub use fair_pool::FairPool;
+pub use fingerprinted_bitset::{FingerprintedBitSet, FingerprintedDrain};
+pub use fixed_bitset::FixedBitSet;
+pub use frozen_bitset::{FrozenBitSet, FrozenOnes};
+pub use handle::HandleLayout;
+pub use hybrid_bitset::{HybridBitSet, HybridDrain, HybridOnes, HybridZeros};
+pub use id_group::IdGroup;
+pub use iter::{
+    Change, Diff, Drain, Filtered, Merged, OccupiedWith, Ones, Runs, SharedDrain, SnapshotOnes,
+    UnionIter, Zeros,
+};
+pub use merge_policy::MergePolicy;
+pub use namespaced_id_set::{Namespace, NamespaceStats, NamespacedIdSet};
+#[cfg(feature = "rayon")]
+pub use par::{ParBitSet, ParBitSetMut};
+pub use port_allocator::PortAllocator;
+pub use reference_set::ReferenceSet;
+pub use shared_bitset::{SharedBitSet, SlotTransition};
+pub use small_bitset::SmallBitSet;
+pub use snapshot_stats::SnapshotStats;
+pub use static_pools::StaticPools;
+pub use summarized_bitset::SummarizedBitSet;
+#[cfg(feature = "timestamps")]
+pub use timed_bitset::TimedBitSet;
+pub use transaction::Transaction;
+pub use views::{IntersectionView, UnionView};
+pub use word::{AtomicSlot, Slot, Word};
 
-use core::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use core::sync::atomic::{AtomicU8, AtomicU16, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::ops::{Bound, Range, RangeBounds};
+use std::sync::Arc;
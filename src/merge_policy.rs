@@ -0,0 +1,12 @@
+/// How imported words are combined with the slots already in a set,
+/// see [`BitSetMut::import_at`](crate::BitSetMut::import_at) and
+/// [`SharedBitSet::import_at`](crate::SharedBitSet::import_at).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// The imported words replace the slots.
+    Overwrite,
+    /// The slots keep their values, and also get the values of the imported words.
+    Or,
+    /// The slots only keep the values that are also in the imported words.
+    And,
+}
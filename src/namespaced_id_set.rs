@@ -0,0 +1,379 @@
+use crate::*;
+
+/// Splits one atomic bitset into `NAMESPACES` disjoint regions of equal size.
+///
+/// Each namespace (e.g. a tenant) gets its own local index space starting at `0`,
+/// without paying for the slack of a separate bitset per namespace.
+/// Slots that don't divide evenly between the namespaces are left unused.
+///
+/// The global index space can still be queried with [`BitSet`]. It is only modified
+/// through the [namespaces](NamespacedIdSet::namespace), which keep track of their usage.
+///
+/// A namespace can be given a [quota](Namespace::set_quota), so that one tenant can't
+/// exhaust the shared index space.
+pub struct NamespacedIdSet<const N: usize, const NAMESPACES: usize> {
+    bitset: [AtomicUsize; N],
+    // rotation hint of each namespace, see `AtomicBitSet`
+    rotations: [AtomicUsize; NAMESPACES],
+    counters: [Counters; NAMESPACES],
+}
+
+impl<const N: usize, const NAMESPACES: usize> NamespacedIdSet<N, NAMESPACES> {
+    /// Number of slots in each namespace.
+    pub const SLOTS_PER_NAMESPACE: usize = N / NAMESPACES;
+
+    /// Creates a new `NamespacedIdSet` with `N` slots, shared between `NAMESPACES` namespaces.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{NamespacedIdSet, slot_count};
+    ///
+    /// // 4 tenants, each with room for 256 identifiers.
+    /// let ids: NamespacedIdSet<{ slot_count::from_bits(1024) }, 4> = NamespacedIdSet::new();
+    /// ```
+    #[inline]
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            bitset: [const { AtomicUsize::new(0) }; N],
+            rotations: [const { AtomicUsize::new(0) }; NAMESPACES],
+            counters: [const { Counters::new() }; NAMESPACES],
+        }
+    }
+
+    /// Returns the namespace with the given id, or `None` if `id >= NAMESPACES`.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{NamespacedIdSet, slot_count, BitSet};
+    ///
+    /// let ids: NamespacedIdSet<{ slot_count::from_bits(1024) }, 4> = NamespacedIdSet::new();
+    /// let tenant = ids.namespace(1).unwrap();
+    ///
+    /// assert_eq!(tenant.set_next_free_bit(), Some(0));
+    /// assert_eq!(tenant.global_index(0), 256);
+    /// assert!(ids.has(256));
+    ///
+    /// assert_eq!(tenant.size(), 1);
+    /// assert_eq!(tenant.capacity(), 256);
+    ///
+    /// tenant.clear();
+    /// assert!(ids.is_empty());
+    ///
+    /// assert!(ids.namespace(4).is_none());
+    /// ```
+    pub fn namespace(&self, id: usize) -> Option<Namespace<'_>> {
+        let rotation = self.rotations.get(id)?;
+        let offset = id * Self::SLOTS_PER_NAMESPACE;
+        Some(Namespace {
+            bitset: &self.bitset[offset..offset + Self::SLOTS_PER_NAMESPACE],
+            rotation,
+            counters: &self.counters[id],
+            offset: offset * usize::BITS as usize,
+        })
+    }
+
+    #[inline]
+    fn slots(&self) -> &[AtomicUsize] {
+        &self.bitset
+    }
+}
+
+/// A region of a [`NamespacedIdSet`], addressed with local indices.
+///
+/// [`BitSet`] operations only see the identifiers of this namespace.
+#[derive(Clone, Copy)]
+pub struct Namespace<'a> {
+    bitset: &'a [AtomicUsize],
+    rotation: &'a AtomicUsize,
+    counters: &'a Counters,
+    // global index of the first bit of this namespace
+    offset: usize,
+}
+
+impl<'a> Namespace<'a> {
+    /// Atomically finds the next free bit in this namespace, sets it to `1`,
+    /// and returns its local index.
+    ///
+    /// Returns `None` if the namespace is full, or if its [quota](Namespace::set_quota)
+    /// is used up. See [`AtomicBitSet::set_next_free_bit`].
+    pub fn set_next_free_bit(&self) -> Option<usize> {
+        // reserves a unit of the quota first, so concurrent allocations can't overshoot it
+        let reserved = self.counters.allocated.fetch_update(
+            Ordering::AcqRel,
+            Ordering::Acquire,
+            |allocated| {
+                let quota = self.counters.quota.load(Ordering::Acquire);
+                (allocated < quota).then_some(allocated + 1)
+            },
+        );
+        if reserved.is_err() {
+            self.counters.failures.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        let Some(index) = util::set_next_free_bit(self.bitset, self.rotation) else {
+            self.counters.allocated.fetch_sub(1, Ordering::AcqRel);
+            self.counters.failures.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+        self.counters
+            .high_water_mark
+            .fetch_max(index + 1, Ordering::Relaxed);
+        Some(index)
+    }
+
+    /// Removes an identifier allocated by [`set_next_free_bit`](Namespace::set_next_free_bit),
+    /// and gives its unit of the quota back.
+    ///
+    /// Returns `Some(true)` if the identifier was set, or `None` if `local` is out of range.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::NamespacedIdSet;
+    ///
+    /// let ids: NamespacedIdSet<4, 2> = NamespacedIdSet::new();
+    /// let tenant = ids.namespace(0).unwrap();
+    /// tenant.set_quota(Some(1));
+    ///
+    /// assert_eq!(tenant.set_next_free_bit(), Some(0));
+    /// assert_eq!(tenant.set_next_free_bit(), None);
+    ///
+    /// assert_eq!(tenant.remove(0), Some(true));
+    /// assert_eq!(tenant.remove(0), Some(false));
+    /// assert_eq!(tenant.remove(128), None);
+    /// assert_eq!(tenant.set_next_free_bit(), Some(0));
+    /// ```
+    pub fn remove(&self, local: usize) -> Option<bool> {
+        let removed = SharedBitSet::remove(self.bitset, local)?;
+        if removed {
+            self.counters.allocated.fetch_sub(1, Ordering::AcqRel);
+        }
+        Some(removed)
+    }
+
+    /// Removes every identifier of this namespace, and gives their units of the quota back.
+    ///
+    /// Each slot is swapped out in one atomic step, but the namespace as a whole isn't
+    /// cleared atomically.
+    pub fn clear(&self) {
+        for slot in self.bitset {
+            let cleared = slot.swap(0, Ordering::AcqRel).count_ones() as usize;
+            if cleared != 0 {
+                self.counters.allocated.fetch_sub(cleared, Ordering::AcqRel);
+            }
+        }
+    }
+
+    /// Limits the number of identifiers this namespace may hold at a time,
+    /// or removes the limit with `None`.
+    ///
+    /// The quota is enforced by [`set_next_free_bit`](Namespace::set_next_free_bit),
+    /// and is never exceeded, even by concurrent allocations. Lowering it below the
+    /// current usage doesn't revoke any identifier: allocations fail until enough of
+    /// them are [removed](Namespace::remove).
+    pub fn set_quota(&self, quota: Option<usize>) {
+        let quota = quota.unwrap_or(usize::MAX);
+        self.counters.quota.store(quota, Ordering::Release);
+    }
+
+    /// Returns the quota of this namespace, see [`set_quota`](Namespace::set_quota).
+    #[inline]
+    pub fn quota(&self) -> Option<usize> {
+        let quota = self.counters.quota.load(Ordering::Acquire);
+        (quota != usize::MAX).then_some(quota)
+    }
+
+    /// Returns the usage statistics of this namespace.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::NamespacedIdSet;
+    ///
+    /// let ids: NamespacedIdSet<2, 2> = NamespacedIdSet::new();
+    /// let tenant = ids.namespace(0).unwrap();
+    ///
+    /// while tenant.set_next_free_bit().is_some() {}
+    /// tenant.remove(10);
+    ///
+    /// let stats = tenant.stats();
+    /// assert_eq!(stats.occupancy, usize::BITS as usize - 1);
+    /// assert_eq!(stats.high_water_mark, usize::BITS as usize);
+    /// assert_eq!(stats.failures, 1);
+    /// ```
+    pub fn stats(&self) -> NamespaceStats {
+        NamespaceStats {
+            occupancy: BitSet::size(self.bitset),
+            high_water_mark: self.counters.high_water_mark.load(Ordering::Relaxed),
+            failures: self.counters.failures.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Maps a local index of this namespace to its index in the whole [`NamespacedIdSet`].
+    #[inline]
+    pub fn global_index(&self, local: usize) -> usize {
+        self.offset + local
+    }
+
+    #[inline]
+    fn slots(&self) -> &'a [AtomicUsize] {
+        self.bitset
+    }
+}
+
+// forwards the read-only operations to the slots; modifications must go through
+// `Namespace`, so that the quota usage stays exact
+macro_rules! impl_bitset {
+    ($([$($generics: tt)*] $set: ty),*) => {$(
+        impl<$($generics)*> BitSet<usize> for $set {
+            type Ones<'a>
+                = crate::Ones<'a, AtomicUsize>
+            where
+                Self: 'a;
+            type Zeros<'a>
+                = crate::Zeros<'a, AtomicUsize>
+            where
+                Self: 'a;
+
+            #[inline]
+            fn capacity(&self) -> usize {
+                BitSet::capacity(self.slots())
+            }
+
+            #[inline]
+            fn has(&self, index: usize) -> bool {
+                BitSet::has(self.slots(), index)
+            }
+
+            #[inline]
+            fn is_empty(&self) -> bool {
+                BitSet::is_empty(self.slots())
+            }
+
+            #[inline]
+            fn size(&self) -> usize {
+                BitSet::size(self.slots())
+            }
+
+            #[inline]
+            fn for_each_one(&self, f: impl FnMut(usize)) {
+                BitSet::for_each_one(self.slots(), f)
+            }
+
+            #[inline]
+            fn first_zero(&self) -> Option<usize> {
+                BitSet::first_zero(self.slots())
+            }
+
+            #[inline]
+            fn free_count(&self) -> usize {
+                BitSet::free_count(self.slots())
+            }
+
+            #[inline]
+            fn remaining_in(&self, range: impl RangeBounds<usize>) -> usize {
+                BitSet::remaining_in(self.slots(), range)
+            }
+
+            #[inline]
+            fn count_in_range(&self, range: impl RangeBounds<usize>) -> usize {
+                BitSet::count_in_range(self.slots(), range)
+            }
+
+            #[inline]
+            fn load_factor(&self) -> f64 {
+                BitSet::load_factor(self.slots())
+            }
+
+            #[inline]
+            fn estimate_load_factor(&self, samples: usize) -> f64 {
+                BitSet::estimate_load_factor(self.slots(), samples)
+            }
+
+            #[inline]
+            fn estimate_size(&self, confidence: f64) -> SizeEstimate {
+                BitSet::estimate_size(self.slots(), confidence)
+            }
+
+            #[inline]
+            fn iter_ones_in(&self, range: impl RangeBounds<usize>) -> Self::Ones<'_> {
+                BitSet::iter_ones_in(self.slots(), range)
+            }
+
+            #[inline]
+            fn iter_zeros_in(&self, range: impl RangeBounds<usize>) -> Self::Zeros<'_> {
+                BitSet::iter_zeros_in(self.slots(), range)
+            }
+
+            #[inline]
+            fn has_all(&self, values: &[usize]) -> bool {
+                BitSet::has_all(self.slots(), values)
+            }
+
+            #[inline]
+            fn has_any(&self, values: &[usize]) -> bool {
+                BitSet::has_any(self.slots(), values)
+            }
+
+            #[inline]
+            fn gather(&self, values: &[usize], out: &mut [bool]) {
+                BitSet::gather(self.slots(), values, out)
+            }
+
+            #[inline]
+            fn is_subset(&self, other: &(impl BitSet<usize> + ?Sized)) -> bool {
+                BitSet::is_subset(self.slots(), other)
+            }
+
+            #[inline]
+            fn intersects(&self, other: &(impl BitSet<usize> + ?Sized)) -> bool {
+                BitSet::intersects(self.slots(), other)
+            }
+
+            #[inline]
+            fn words(&self) -> Option<impl Iterator<Item = usize>> {
+                BitSet::words(self.slots())
+            }
+        }
+    )*}
+}
+
+impl_bitset! {
+    [const N: usize, const NAMESPACES: usize] NamespacedIdSet<N, NAMESPACES>,
+    [] Namespace<'_>
+}
+
+/// Usage statistics of a [`Namespace`], see [`Namespace::stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NamespaceStats {
+    /// Number of identifiers currently in use.
+    pub occupancy: usize,
+    /// One past the highest local index ever returned by `set_next_free_bit`.
+    pub high_water_mark: usize,
+    /// Number of times `set_next_free_bit` failed because the namespace was full,
+    /// or over its quota.
+    pub failures: usize,
+}
+
+struct Counters {
+    high_water_mark: AtomicUsize,
+    failures: AtomicUsize,
+    // `usize::MAX` if the namespace has no quota
+    quota: AtomicUsize,
+    // identifiers allocated and not released yet, that count against the quota
+    allocated: AtomicUsize,
+}
+
+impl Counters {
+    const fn new() -> Self {
+        Self {
+            high_water_mark: AtomicUsize::new(0),
+            failures: AtomicUsize::new(0),
+            quota: AtomicUsize::new(usize::MAX),
+            allocated: AtomicUsize::new(0),
+        }
+    }
+}
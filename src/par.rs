@@ -0,0 +1,98 @@
+use crate::*;
+use rayon::prelude::*;
+
+// number of slots handled by a single rayon task
+const CHUNK_SIZE: usize = 4096;
+
+/// Parallel bulk operations for very large bit sets, backed by [rayon](https://docs.rs/rayon).
+///
+/// Available with the `rayon` feature.
+pub trait ParBitSetMut {
+    /// Clears the set, zeroing chunks of slots in parallel.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, BitSetMut, ParBitSetMut};
+    ///
+    /// let mut bitset = vec![0_u64; 1 << 16];
+    /// BitSetMut::insert(&mut bitset, 42);
+    ///
+    /// bitset.par_clear();
+    /// assert!(BitSet::is_empty(&bitset[..]));
+    /// ```
+    fn par_clear(&mut self);
+
+    /// Inserts every value up to the capacity of the set, filling chunks of slots in parallel.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, ParBitSetMut};
+    ///
+    /// let mut bitset = vec![0_u64; 1 << 16];
+    ///
+    /// bitset.par_fill();
+    /// assert_eq!(bitset.size(), BitSet::capacity(&bitset[..]));
+    /// ```
+    fn par_fill(&mut self);
+}
+
+impl<T: Word + Send> ParBitSetMut for [T] {
+    #[inline]
+    fn par_clear(&mut self) {
+        self.par_chunks_mut(CHUNK_SIZE)
+            .for_each(|chunk| chunk.fill(T::ZERO));
+    }
+
+    #[inline]
+    fn par_fill(&mut self) {
+        self.par_chunks_mut(CHUNK_SIZE)
+            .for_each(|chunk| chunk.fill(T::MAX));
+    }
+}
+
+/// Parallel iteration over the values of very large bit sets, backed by [rayon](https://docs.rs/rayon).
+///
+/// Implemented for slices of plain and atomic slots, so it also works on `Vec<T>` and
+/// [`AtomicBitSet`] through deref. Available with the `rayon` feature.
+pub trait ParBitSet<T> {
+    /// Returns a parallel iterator over the values of the set, splitting chunks of slots across threads.
+    ///
+    /// The values are yielded in no particular order. Atomic slots are loaded once each,
+    /// so concurrent changes may or may not be observed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, ParBitSet, SharedBitSet};
+    /// use rayon::prelude::*;
+    ///
+    /// let bitset = vec![0b101_u64; 1 << 16];
+    /// assert_eq!(bitset.par_ones().count(), 2 << 16);
+    ///
+    /// let ids: AtomicBitSet<64> = AtomicBitSet::new();
+    /// ids.insert(3);
+    /// ids.insert(4000);
+    /// assert_eq!(ids.par_ones().sum::<usize>(), 4003);
+    /// ```
+    fn par_ones(&self) -> impl ParallelIterator<Item = T>;
+}
+
+impl<S> ParBitSet<S::Word> for [S]
+where
+    S: Slot + Sync,
+    S::Word: Send,
+{
+    fn par_ones(&self) -> impl ParallelIterator<Item = S::Word> {
+        util::addressable(self)
+            .par_chunks(CHUNK_SIZE)
+            .enumerate()
+            .flat_map_iter(|(chunk_idx, chunk)| {
+                // the first value of the chunk, as the values of `chunk` start at 0
+                let offset = S::Word::from_index(chunk_idx * CHUNK_SIZE, 0).to_u128();
+                BitSet::iter_ones(chunk)
+                    .map(move |value| S::Word::from_u128(offset + value.to_u128()))
+            })
+    }
+}
@@ -0,0 +1,103 @@
+use crate::*;
+
+/// A lock-free allocator of ports within a range, e.g. the ephemeral port range.
+///
+/// Ports are handed out cyclically, see [`AtomicBitSet::set_next_free_bit_cyclic`],
+/// so a released port is only reused once the rest of the range was allocated,
+/// which keeps stale packets away from new connections.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::PortAllocator;
+///
+/// // like Linux's default `ip_local_port_range`, without the reserved metrics port
+/// let ports = PortAllocator::with_exclusions(32768..=60999, [32769]);
+///
+/// assert_eq!(ports.allocate_port(), Some(32768));
+/// assert_eq!(ports.allocate_port(), Some(32770));
+///
+/// assert!(ports.release_port(32768));
+/// assert_eq!(ports.allocate_port(), Some(32771));
+/// assert!(!ports.release_port(32769));
+/// ```
+pub struct PortAllocator {
+    // bit `i` is port `start + i`, excluded ports and the padding after the range are always set
+    ports: Box<[AtomicUsize]>,
+    excluded: Box<[usize]>,
+    start: u16,
+    len: usize,
+    cursor: AtomicUsize,
+}
+
+impl PortAllocator {
+    /// Creates an allocator of the ports in `range`, all free.
+    #[inline]
+    pub fn new(range: impl RangeBounds<u16>) -> Self {
+        Self::with_exclusions(range, [])
+    }
+
+    /// Creates an allocator of the ports in `range`, that never hands out the `excluded` ports.
+    ///
+    /// Excluded ports outside of the range are ignored.
+    pub fn with_exclusions(
+        range: impl RangeBounds<u16>,
+        excluded: impl IntoIterator<Item = u16>,
+    ) -> Self {
+        let (start, end) = util::bit_range(&range, 1 << 16, |port: u16| port as u128);
+        let len = end.saturating_sub(start) as usize;
+        let start = start.min(u16::MAX as u128) as u16;
+
+        let mut taken = vec![0; slot_count::from_bits(len)];
+        for padding in len..taken.len() * usize::BITS as usize {
+            let _ = BitSetMut::insert(&mut taken[..], padding);
+        }
+        let mut skipped = vec![0; taken.len()];
+        for port in excluded {
+            if let Some(offset) = port.checked_sub(start).map(usize::from)
+                && offset < len
+            {
+                let _ = BitSetMut::insert(&mut taken[..], offset);
+                let _ = BitSetMut::insert(&mut skipped[..], offset);
+            }
+        }
+        Self {
+            ports: taken.into_iter().map(AtomicUsize::new).collect(),
+            excluded: skipped.into(),
+            start,
+            len,
+            cursor: AtomicUsize::new(0),
+        }
+    }
+
+    /// Allocates the next free port after the most recently allocated one, wrapping around.
+    ///
+    /// Returns `None` if every port of the range is allocated or excluded.
+    #[inline]
+    pub fn allocate_port(&self) -> Option<u16> {
+        let offset = util::set_next_free_bit_cyclic(&self.ports, &self.cursor)?;
+        Some(self.start + offset as u16)
+    }
+
+    /// Releases an allocated port.
+    ///
+    /// Returns `false` if the port wasn't allocated, is excluded, or is out of range.
+    pub fn release_port(&self, port: u16) -> bool {
+        match self.offset(port) {
+            Some(offset) => SharedBitSet::remove(&self.ports[..], offset) == Some(true),
+            None => false,
+        }
+    }
+
+    /// Returns `true` if the port is currently allocated.
+    pub fn is_allocated(&self, port: u16) -> bool {
+        self.offset(port)
+            .is_some_and(|offset| BitSet::has(&self.ports[..], offset))
+    }
+
+    // returns the bit of `port`, if it is within the range and not excluded
+    fn offset(&self, port: u16) -> Option<usize> {
+        let offset = usize::from(port.checked_sub(self.start)?);
+        (offset < self.len && !BitSet::has(&self.excluded[..], offset)).then_some(offset)
+    }
+}
@@ -0,0 +1,226 @@
+use crate::*;
+use std::collections::{BTreeSet, btree_set};
+
+/// A slow but straightforward model of a `[usize]` set of `slots` slots, backed by a [`BTreeSet`].
+///
+/// Meant for differential testing: run the same operations on a fast set and on a `ReferenceSet`,
+/// and compare the results. Every method follows the semantics of the slice implementations,
+/// including the out-of-range errors and the sampled estimates.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{BitSet, BitSetMut, ReferenceSet};
+///
+/// let mut fast = [0_usize; 2];
+/// let mut reference = ReferenceSet::new(2);
+///
+/// for value in [3, 64, 3, 500] {
+///     assert_eq!(fast.insert(value), reference.insert(value));
+/// }
+/// assert_eq!(fast.remove(64), reference.remove(64));
+/// assert!(fast.iter_ones().eq(reference.iter_ones()));
+/// assert_eq!(fast.first_zero(), reference.first_zero());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReferenceSet {
+    values: BTreeSet<usize>,
+    slots: usize,
+}
+
+impl ReferenceSet {
+    /// Creates an empty set, with the capacity of `slots` slots of `usize::BITS` bits.
+    #[inline]
+    pub const fn new(slots: usize) -> Self {
+        Self {
+            values: BTreeSet::new(),
+            slots,
+        }
+    }
+
+    /// Returns the values of the set.
+    #[inline]
+    pub fn values(&self) -> &BTreeSet<usize> {
+        &self.values
+    }
+
+    // clamps `range` to the capacity
+    fn clamp(&self, range: &impl RangeBounds<usize>) -> Range<usize> {
+        let (start, end) = util::bit_range(range, self.capacity() as u128, |index| index as u128);
+        // reversed ranges are empty
+        start.min(end) as usize..end as usize
+    }
+}
+
+impl BitSet<usize> for ReferenceSet {
+    type Ones<'a> = std::iter::Copied<btree_set::Range<'a, usize>>;
+    type Zeros<'a> = Box<dyn DoubleEndedIterator<Item = usize> + 'a>;
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.slots * usize::BITS as usize
+    }
+
+    #[inline]
+    fn has(&self, index: usize) -> bool {
+        self.values.contains(&index)
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    #[inline]
+    fn size(&self) -> usize {
+        self.values.len()
+    }
+
+    fn for_each_one(&self, f: impl FnMut(usize)) {
+        self.values.iter().copied().for_each(f)
+    }
+
+    fn first_zero(&self) -> Option<usize> {
+        (0..self.capacity()).find(|index| !self.has(*index))
+    }
+
+    #[inline]
+    fn free_count(&self) -> usize {
+        self.capacity() - self.size()
+    }
+
+    fn remaining_in(&self, range: impl RangeBounds<usize>) -> usize {
+        self.iter_zeros_in(range).count()
+    }
+
+    fn count_in_range(&self, range: impl RangeBounds<usize>) -> usize {
+        self.iter_ones_in(range).count()
+    }
+
+    fn load_factor(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        self.size() as f64 / self.capacity() as f64
+    }
+
+    fn estimate_load_factor(&self, samples: usize) -> f64 {
+        let bits = usize::BITS as usize;
+        let slot_idxs: Vec<_> = util::sample_slots(self.slots, samples).collect();
+        if slot_idxs.is_empty() {
+            return 0.0;
+        }
+        let count: usize = slot_idxs
+            .iter()
+            .map(|slot_idx| self.count_in_range(slot_idx * bits..(slot_idx + 1) * bits))
+            .sum();
+        count as f64 / (slot_idxs.len() * bits) as f64
+    }
+
+    fn estimate_size(&self, confidence: f64) -> SizeEstimate {
+        let samples = SizeEstimate::SAMPLES.min(self.slots);
+        SizeEstimate::new(
+            self.estimate_load_factor(samples),
+            (samples * usize::BITS as usize) as f64,
+            self.capacity() as f64,
+            confidence,
+        )
+    }
+
+    fn iter_ones_in(&self, range: impl RangeBounds<usize>) -> Self::Ones<'_> {
+        self.values.range(self.clamp(&range)).copied()
+    }
+
+    fn iter_zeros_in(&self, range: impl RangeBounds<usize>) -> Self::Zeros<'_> {
+        Box::new(self.clamp(&range).filter(|index| !self.has(*index)))
+    }
+
+    fn has_all(&self, values: &[usize]) -> bool {
+        values.iter().all(|&value| self.has(value))
+    }
+
+    fn has_any(&self, values: &[usize]) -> bool {
+        values.iter().any(|&value| self.has(value))
+    }
+
+    fn gather(&self, values: &[usize], out: &mut [bool]) {
+        assert_eq!(
+            values.len(),
+            out.len(),
+            "`values` and `out` must have the same length"
+        );
+        for (&value, out) in values.iter().zip(out) {
+            *out = self.has(value);
+        }
+    }
+}
+
+impl BitSetMut<usize> for ReferenceSet {
+    type Drain<'a> = btree_set::IntoIter<usize>;
+
+    #[inline]
+    fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    fn insert(&mut self, index: usize) -> Result<bool, usize> {
+        if index >= self.capacity() {
+            return Err(index / usize::BITS as usize);
+        }
+        Ok(!self.values.insert(index))
+    }
+
+    fn remove(&mut self, index: usize) -> Option<bool> {
+        if index >= self.capacity() {
+            return None;
+        }
+        Some(self.values.remove(&index))
+    }
+
+    fn clear_where(&mut self, mut predicate: impl FnMut(usize) -> bool) -> usize {
+        let size = self.size();
+        self.values.retain(|&value| !predicate(value));
+        size - self.size()
+    }
+
+    fn import_at(
+        &mut self,
+        offset: usize,
+        words: &[usize],
+        policy: MergePolicy,
+    ) -> Result<(), usize> {
+        let bits = usize::BITS as usize;
+        assert!(
+            offset.is_multiple_of(bits),
+            "offset must be a multiple of {bits} bits"
+        );
+        if words.is_empty() {
+            return Ok(());
+        }
+        let end = offset / bits + words.len();
+        if end > self.slots {
+            return Err(end - 1);
+        }
+        for (i, &word) in words.iter().enumerate() {
+            for bit in 0..bits {
+                let index = offset + i * bits + bit;
+                let set = word >> bit & 1 == 1;
+                let keep = match policy {
+                    MergePolicy::Overwrite => set,
+                    MergePolicy::Or => set || self.has(index),
+                    MergePolicy::And => set && self.has(index),
+                };
+                match keep {
+                    true => self.values.insert(index),
+                    false => self.values.remove(&index),
+                };
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn drain(&mut self) -> Self::Drain<'_> {
+        std::mem::take(&mut self.values).into_iter()
+    }
+}
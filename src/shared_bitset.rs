@@ -2,6 +2,11 @@ use crate::*;
 
 /// A trait for updating values in a shared bit-set.
 pub trait SharedBitSet<T> {
+    /// An iterator that removes the values of the set, see [`drain`](SharedBitSet::drain).
+    type Drain<'a>: Iterator<Item = T>
+    where
+        Self: 'a;
+
     /// Clears the set
     ///
     /// # Example
@@ -34,6 +39,146 @@ pub trait SharedBitSet<T> {
     /// ```
     fn insert(&self, index: T) -> Option<bool>;
 
+    /// Inserts the index into the set, failing if it was already set.
+    ///
+    /// Unlike [`insert`](SharedBitSet::insert), a double insertion can't go unnoticed:
+    /// when several threads race for the same index, exactly one of them succeeds.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{SharedBitSet, InsertError};
+    /// use std::sync::atomic::AtomicU32;
+    ///
+    /// let bitset: [AtomicU32; 4] = Default::default();
+    /// assert_eq!(bitset.insert_new(7), Ok(()));
+    /// assert_eq!(bitset.insert_new(7), Err(InsertError::AlreadyPresent));
+    /// assert_eq!(bitset.insert_new(128), Err(InsertError::OutOfRange));
+    /// ```
+    #[inline]
+    fn insert_new(&self, index: T) -> Result<(), InsertError> {
+        match self.insert(index) {
+            Some(false) => Ok(()),
+            Some(true) => Err(InsertError::AlreadyPresent),
+            None => Err(InsertError::OutOfRange),
+        }
+    }
+
+    /// Same as [`insert`](SharedBitSet::insert), but panics if the set cannot hold the index,
+    /// for callers where an out-of-range index is always a logic bug.
+    ///
+    /// Returns `true` if the index was already set.
+    ///
+    /// # Example
+    ///
+    /// ```rust,should_panic
+    /// use index_set::SharedBitSet;
+    /// use std::sync::atomic::AtomicU32;
+    ///
+    /// let bitset: [AtomicU32; 4] = Default::default();
+    /// assert!(!bitset.insert_strict(7));
+    /// bitset.insert_strict(128); // panics
+    /// ```
+    #[inline]
+    #[track_caller]
+    fn insert_strict(&self, index: T) -> bool {
+        match self.insert(index) {
+            Some(had) => had,
+            None => panic!("index out of range of the set"),
+        }
+    }
+
+    /// Same as [`remove`](SharedBitSet::remove), but panics if the set cannot hold the index.
+    ///
+    /// Returns `true` if the index was set.
+    #[inline]
+    #[track_caller]
+    fn remove_strict(&self, index: T) -> bool {
+        match self.remove(index) {
+            Some(had) => had,
+            None => panic!("index out of range of the set"),
+        }
+    }
+
+    /// Claims the given index, e.g. to bind to a requested port or identifier.
+    ///
+    /// Succeeds only if the index was free, so when several threads race for the same index,
+    /// exactly one of them gets it.
+    ///
+    /// Returns [`ClaimError::Taken`] if the index was already claimed, or
+    /// [`ClaimError::OutOfRange`] if the set cannot hold it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, ClaimError, SharedBitSet};
+    ///
+    /// let ports: AtomicBitSet<1024> = AtomicBitSet::new();
+    /// assert_eq!(ports.try_claim(8080), Ok(()));
+    /// assert_eq!(ports.try_claim(8080), Err(ClaimError::Taken));
+    /// assert_eq!(ports.try_claim(1 << 20), Err(ClaimError::OutOfRange));
+    ///
+    /// ports.remove(8080);
+    /// assert_eq!(ports.try_claim(8080), Ok(()));
+    /// ```
+    #[inline]
+    fn try_claim(&self, index: T) -> Result<(), ClaimError> {
+        match self.insert(index) {
+            Some(false) => Ok(()),
+            Some(true) => Err(ClaimError::Taken),
+            None => Err(ClaimError::OutOfRange),
+        }
+    }
+
+    /// Claims the given index like [`try_claim`](SharedBitSet::try_claim), then runs `f`,
+    /// and releases the index again if `f` fails.
+    ///
+    /// Useful when the index must also be reserved somewhere else (e.g. registered with
+    /// an external service), so that a failure there doesn't leak it. `f` isn't run if
+    /// the index can't be claimed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{AtomicBitSet, BitSet, ClaimError, SharedBitSet};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum Error {
+    ///     Claim(ClaimError),
+    ///     Rejected,
+    /// }
+    ///
+    /// impl From<ClaimError> for Error {
+    ///     fn from(error: ClaimError) -> Self {
+    ///         Error::Claim(error)
+    ///     }
+    /// }
+    ///
+    /// let ids: AtomicBitSet<1> = AtomicBitSet::new();
+    ///
+    /// assert_eq!(ids.claim_with(3, || Err::<(), _>(Error::Rejected)), Err(Error::Rejected));
+    /// assert!(!ids.has(3));
+    ///
+    /// assert_eq!(ids.claim_with(3, || Ok::<_, Error>("registered")), Ok("registered"));
+    /// assert_eq!(
+    ///     ids.claim_with(3, || Ok::<_, Error>("registered")),
+    ///     Err(Error::Claim(ClaimError::Taken))
+    /// );
+    /// assert!(ids.has(3));
+    /// ```
+    fn claim_with<R, E>(&self, index: T, f: impl FnOnce() -> Result<R, E>) -> Result<R, E>
+    where
+        T: Copy,
+        E: From<ClaimError>,
+    {
+        self.try_claim(index)?;
+        let result = f();
+        if result.is_err() {
+            self.remove(index);
+        }
+        result
+    }
+
     /// Removes the index from the set
     /// Returns `true` if the index was set.
     ///
@@ -51,12 +196,252 @@ pub trait SharedBitSet<T> {
     /// assert_eq!(bitset.has(42), false);
     /// ```
     fn remove(&self, index: T) -> Option<bool>;
+
+    /// Same as [`insert`](SharedBitSet::insert), which uses `Release`, but with the given
+    /// memory ordering, like [`AtomicUsize::fetch_or`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, SharedBitSet};
+    /// use std::sync::atomic::{AtomicU32, Ordering};
+    ///
+    /// let bitset: [AtomicU32; 4] = Default::default();
+    /// assert_eq!(bitset.insert_with(42, Ordering::SeqCst), Some(false));
+    /// assert_eq!(bitset.insert_with(42, Ordering::Relaxed), Some(true));
+    /// assert_eq!(bitset.insert_with(128, Ordering::SeqCst), None);
+    /// ```
+    fn insert_with(&self, index: T, order: Ordering) -> Option<bool>;
+
+    /// Same as [`remove`](SharedBitSet::remove), which uses `Release`, but with the given
+    /// memory ordering, like [`AtomicUsize::fetch_and`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, SharedBitSet};
+    /// use std::sync::atomic::{AtomicU32, Ordering};
+    ///
+    /// let bitset: [AtomicU32; 4] = Default::default();
+    /// bitset.insert(42);
+    /// assert_eq!(bitset.remove_with(42, Ordering::AcqRel), Some(true));
+    /// assert!(!bitset.has(42));
+    /// ```
+    fn remove_with(&self, index: T, order: Ordering) -> Option<bool>;
+
+    /// Inserts the index into the set, like [`insert`](SharedBitSet::insert),
+    /// but also returns the whole slot as it was before the insertion.
+    ///
+    /// This lets callers piggyback extra logic (e.g. detecting that the slot just became full)
+    /// without a second load.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::SharedBitSet;
+    /// use std::sync::atomic::AtomicU32;
+    ///
+    /// let bitset: [AtomicU32; 2] = Default::default();
+    /// bitset.insert(32);
+    ///
+    /// assert_eq!(bitset.insert_returning_word(33), Some((false, 0b01)));
+    /// assert_eq!(bitset.insert_returning_word(33), Some((true, 0b11)));
+    /// assert_eq!(bitset.insert_returning_word(64), None);
+    /// ```
+    fn insert_returning_word(&self, index: T) -> Option<(bool, T)>;
+
+    /// Inserts the index into the set, and reports how the slot holding it changed.
+    ///
+    /// Useful to incrementally maintain higher-level structures, like a summary of full slots.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{SharedBitSet, SlotTransition};
+    /// use std::sync::atomic::AtomicU32;
+    ///
+    /// let bitset: [AtomicU32; 1] = [AtomicU32::new(u32::MAX >> 1)];
+    ///
+    /// assert_eq!(bitset.insert_transition(0), Some(SlotTransition::Unchanged));
+    /// assert_eq!(bitset.insert_transition(31), Some(SlotTransition::Filled));
+    /// ```
+    fn insert_transition(&self, index: T) -> Option<SlotTransition>;
+
+    /// Removes the index from the set, and reports how the slot holding it changed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{SharedBitSet, SlotTransition};
+    /// use std::sync::atomic::AtomicU32;
+    ///
+    /// let bitset: [AtomicU32; 1] = Default::default();
+    /// bitset.insert(1);
+    /// bitset.insert(2);
+    ///
+    /// assert_eq!(bitset.remove_transition(1), Some(SlotTransition::Changed));
+    /// assert_eq!(bitset.remove_transition(1), Some(SlotTransition::Unchanged));
+    /// assert_eq!(bitset.remove_transition(2), Some(SlotTransition::Emptied));
+    /// ```
+    fn remove_transition(&self, index: T) -> Option<SlotTransition>;
+
+    /// Atomically removes the lowest index from the set, and returns it.
+    ///
+    /// Each index is returned to exactly one caller, so multiple consumers can claim work
+    /// from the same set, in priority order (lowest index first).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::SharedBitSet;
+    /// use std::sync::atomic::AtomicU32;
+    ///
+    /// let ready: [AtomicU32; 2] = Default::default();
+    /// ready.insert(40);
+    /// ready.insert(3);
+    ///
+    /// assert_eq!(ready.take_lowest(), Some(3));
+    /// assert_eq!(ready.take_lowest(), Some(40));
+    /// assert_eq!(ready.take_lowest(), None);
+    /// ```
+    fn take_lowest(&self) -> Option<T>;
+
+    /// Atomically removes the highest index from the set, and returns it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::SharedBitSet;
+    /// use std::sync::atomic::AtomicU32;
+    ///
+    /// let ready: [AtomicU32; 2] = Default::default();
+    /// ready.insert(40);
+    /// ready.insert(3);
+    ///
+    /// assert_eq!(ready.take_highest(), Some(40));
+    /// assert_eq!(ready.take_highest(), Some(3));
+    /// assert_eq!(ready.take_highest(), None);
+    /// ```
+    fn take_highest(&self) -> Option<T>;
+
+    /// Removes every index for which `predicate` returns `true`.
+    /// Only indices present in the set are visited.
+    ///
+    /// Returns the number of indices removed by this call.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{SharedBitSet, BitSet};
+    /// use std::sync::atomic::AtomicU32;
+    ///
+    /// let bitset: [AtomicU32; 4] = Default::default();
+    /// bitset.insert(1);
+    /// bitset.insert(2);
+    /// bitset.insert(3);
+    ///
+    /// assert_eq!(bitset.clear_where(|index| index != 2), 2);
+    /// assert_eq!(bitset.size(), 1);
+    /// ```
+    fn clear_where(&self, predicate: impl FnMut(T) -> bool) -> T;
+
+    /// Merges `words` into the slots starting at the bit `offset`, according to `policy`.
+    ///
+    /// Each slot is updated atomically, but the fragment as a whole is not.
+    ///
+    /// Returns `None` if the set cannot hold the fragment. Nothing is written in that case.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is not a multiple of the number of bits of a slot.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{SharedBitSet, BitSet, MergePolicy};
+    /// use std::sync::atomic::AtomicU32;
+    ///
+    /// let bitset: [AtomicU32; 4] = Default::default();
+    /// bitset.insert(33);
+    ///
+    /// bitset.import_at(32, &[0b1, 0b1], MergePolicy::Or).unwrap();
+    /// assert!(bitset.has(32) && bitset.has(33) && bitset.has(64));
+    ///
+    /// bitset.import_at(32, &[0b10], MergePolicy::Overwrite).unwrap();
+    /// assert_eq!(bitset.size(), 2);
+    ///
+    /// assert_eq!(bitset.import_at(96, &[0, 0], MergePolicy::Overwrite), None);
+    /// ```
+    fn import_at(&self, offset: T, words: &[T], policy: MergePolicy) -> Option<()>;
+
+    /// Inserts every index yielded by `iter`, and returns how many of them were newly inserted.
+    ///
+    /// Consecutive indices that fall in the same slot are inserted with a single atomic operation,
+    /// so sorted input is the cheapest.
+    ///
+    /// Stops at the first index that the set cannot hold, and returns `Err((inserted, index))`,
+    /// where `inserted` counts the indices newly inserted before it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{SharedBitSet, BitSet};
+    /// use std::sync::atomic::AtomicU32;
+    ///
+    /// let bitset: [AtomicU32; 2] = Default::default();
+    /// bitset.insert(3);
+    ///
+    /// assert_eq!(bitset.insert_iter([1, 2, 3, 40]), Ok(3));
+    /// assert_eq!(bitset.insert_iter([5, 64, 6]), Err((1, 64)));
+    /// assert!(bitset.has(5) && !bitset.has(6));
+    /// ```
+    fn insert_iter(&self, iter: impl IntoIterator<Item = T>) -> Result<T, (T, T)>
+    where
+        T: Word,
+    {
+        let mut inserted = 0_u128;
+        for index in iter {
+            match self.insert(index) {
+                Some(had) => inserted += u128::from(!had),
+                None => return Err((T::saturating_from_u128(inserted), index)),
+            }
+        }
+        Ok(T::saturating_from_u128(inserted))
+    }
+
+    /// Returns an iterator that atomically removes the values of the set as it yields them,
+    /// in ascending order.
+    ///
+    /// Each slot is emptied with a single `fetch_and` when the iterator reaches it, so multiple
+    /// consumers can drain the same set concurrently, and each value is yielded exactly once.
+    /// Values inserted behind the iterator are left for the next drain.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use index_set::{SharedBitSet, BitSet};
+    /// use std::sync::atomic::AtomicU32;
+    ///
+    /// let pending: [AtomicU32; 4] = Default::default();
+    /// pending.insert(40);
+    /// pending.insert(3);
+    ///
+    /// let work: Vec<_> = pending.drain().collect();
+    /// assert_eq!(work, [3, 40]);
+    /// assert!(BitSet::is_empty(&pending[..]));
+    /// ```
+    fn drain(&self) -> Self::Drain<'_>;
 }
 
 impl<Set, T> SharedBitSet<T> for &Set
 where
     Set: SharedBitSet<T> + ?Sized,
 {
+    type Drain<'a>
+        = Set::Drain<'a>
+    where
+        Self: 'a;
+
     #[inline]
     fn clear(&self) {
         SharedBitSet::clear(*self);
@@ -71,46 +456,257 @@ where
     fn remove(&self, index: T) -> Option<bool> {
         SharedBitSet::remove(*self, index)
     }
+
+    #[inline]
+    fn insert_with(&self, index: T, order: Ordering) -> Option<bool> {
+        SharedBitSet::insert_with(*self, index, order)
+    }
+
+    #[inline]
+    fn remove_with(&self, index: T, order: Ordering) -> Option<bool> {
+        SharedBitSet::remove_with(*self, index, order)
+    }
+
+    #[inline]
+    fn insert_returning_word(&self, index: T) -> Option<(bool, T)> {
+        SharedBitSet::insert_returning_word(*self, index)
+    }
+
+    #[inline]
+    fn insert_transition(&self, index: T) -> Option<SlotTransition> {
+        SharedBitSet::insert_transition(*self, index)
+    }
+
+    #[inline]
+    fn remove_transition(&self, index: T) -> Option<SlotTransition> {
+        SharedBitSet::remove_transition(*self, index)
+    }
+
+    #[inline]
+    fn take_lowest(&self) -> Option<T> {
+        SharedBitSet::take_lowest(*self)
+    }
+
+    #[inline]
+    fn take_highest(&self) -> Option<T> {
+        SharedBitSet::take_highest(*self)
+    }
+
+    #[inline]
+    fn clear_where(&self, predicate: impl FnMut(T) -> bool) -> T {
+        SharedBitSet::clear_where(*self, predicate)
+    }
+
+    #[inline]
+    fn import_at(&self, offset: T, words: &[T], policy: MergePolicy) -> Option<()> {
+        SharedBitSet::import_at(*self, offset, words, policy)
+    }
+
+    #[inline]
+    fn insert_iter(&self, iter: impl IntoIterator<Item = T>) -> Result<T, (T, T)>
+    where
+        T: Word,
+    {
+        SharedBitSet::insert_iter(*self, iter)
+    }
+
+    #[inline]
+    fn drain(&self) -> Self::Drain<'_> {
+        SharedBitSet::drain(*self)
+    }
 }
 
-macro_rules! impl_shared_bit_set {
-    [$($ty:tt for $target: ty)*] => {$(
-        impl SharedBitSet<$ty> for [$target] {
-            fn clear(&self) {
-                for slot in self.iter() {
-                    slot.store(0, Ordering::Release);
-                }
-            }
+/// How an insertion or removal changed the slot holding the index,
+/// see [`SharedBitSet::insert_transition`] and [`SharedBitSet::remove_transition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotTransition {
+    /// The bit already had the requested value.
+    Unchanged,
+    /// The bit was flipped, without filling or emptying the slot.
+    Changed,
+    /// The bit was inserted, and the slot is now full.
+    Filled,
+    /// The bit was removed, and the slot is now empty.
+    Emptied,
+}
+
+impl<S: AtomicSlot> SharedBitSet<S::Word> for [S] {
+    type Drain<'a>
+        = crate::SharedDrain<'a, S>
+    where
+        S: 'a;
+
+    fn clear(&self) {
+        for slot in self.iter() {
+            slot.store(S::Word::ZERO, Ordering::Release);
+        }
+    }
+
+    #[inline]
+    fn insert(&self, index: S::Word) -> Option<bool> {
+        self.insert_with(index, Ordering::Release)
+    }
+
+    #[inline]
+    fn remove(&self, index: S::Word) -> Option<bool> {
+        self.remove_with(index, Ordering::Release)
+    }
+
+    #[inline]
+    fn insert_with(&self, index: S::Word, order: Ordering) -> Option<bool> {
+        let (slot_idx, bit) = index.split_index()?;
+        let mask = S::Word::bit(bit);
+
+        let slot = self.get(slot_idx)?.fetch_or(mask, order);
+
+        Some(slot.and(mask) != S::Word::ZERO)
+    }
+
+    #[inline]
+    fn remove_with(&self, index: S::Word, order: Ordering) -> Option<bool> {
+        let (slot_idx, bit) = index.split_index()?;
+        let mask = S::Word::bit(bit);
+
+        let slot = self.get(slot_idx)?.fetch_and(mask.not(), order);
+
+        Some(slot.and(mask) != S::Word::ZERO)
+    }
+
+    #[inline]
+    fn insert_returning_word(&self, index: S::Word) -> Option<(bool, S::Word)> {
+        let (slot_idx, bit) = index.split_index()?;
+        let mask = S::Word::bit(bit);
+
+        let slot = self.get(slot_idx)?.fetch_or(mask, Ordering::Release);
+
+        Some((slot.and(mask) != S::Word::ZERO, slot))
+    }
+
+    #[inline]
+    fn insert_transition(&self, index: S::Word) -> Option<SlotTransition> {
+        let (_, bit) = index.split_index()?;
+        let (had, slot) = self.insert_returning_word(index)?;
+        Some(match had {
+            true => SlotTransition::Unchanged,
+            false if slot.or(S::Word::bit(bit)) == S::Word::MAX => SlotTransition::Filled,
+            false => SlotTransition::Changed,
+        })
+    }
+
+    #[inline]
+    fn remove_transition(&self, index: S::Word) -> Option<SlotTransition> {
+        let (slot_idx, bit) = index.split_index()?;
+        let mask = S::Word::bit(bit);
+
+        let slot = self.get(slot_idx)?.fetch_and(mask.not(), Ordering::Release);
+
+        Some(match slot.and(mask) != S::Word::ZERO {
+            false => SlotTransition::Unchanged,
+            true if slot.without_bit(bit) == S::Word::ZERO => SlotTransition::Emptied,
+            true => SlotTransition::Changed,
+        })
+    }
 
-            #[inline]
-            fn insert(&self, index: $ty) -> Option<bool> {
-                let slot_idx = usize::try_from(index / $ty::BITS as $ty).ok()?;
-                let mask = 1 << (index % $ty::BITS as $ty);
+    fn take_lowest(&self) -> Option<S::Word> {
+        let slots = util::addressable(self);
+        slots.iter().enumerate().find_map(|(slot_idx, slot)| {
+            let old = slot
+                .fetch_update(|curr| {
+                    (curr != S::Word::ZERO).then(|| curr.without_bit(curr.trailing_zeros()))
+                })
+                .ok()?;
+            Some(S::Word::from_index(slot_idx, old.trailing_zeros()))
+        })
+    }
 
-                let slot = self
-                    .get(slot_idx)?
-                    .fetch_or(mask, Ordering::Release);
+    fn take_highest(&self) -> Option<S::Word> {
+        let highest = |word: S::Word| S::Word::BITS - 1 - word.leading_zeros();
+        let slots = util::addressable(self);
+        slots.iter().enumerate().rev().find_map(|(slot_idx, slot)| {
+            let old = slot
+                .fetch_update(|curr| {
+                    (curr != S::Word::ZERO).then(|| curr.without_bit(highest(curr)))
+                })
+                .ok()?;
+            Some(S::Word::from_index(slot_idx, highest(old)))
+        })
+    }
 
-                Some(slot & mask != 0)
+    fn clear_where(&self, mut predicate: impl FnMut(S::Word) -> bool) -> S::Word {
+        let mut cleared = 0_usize;
+        for (slot_idx, slot) in util::addressable(self).iter().enumerate() {
+            let mut bits = slot.load();
+            let mut mask = S::Word::ZERO;
+            while bits != S::Word::ZERO {
+                let bit = bits.trailing_zeros();
+                bits = bits.without_bit(bit);
+                if predicate(S::Word::from_index(slot_idx, bit)) {
+                    mask = mask.or(S::Word::bit(bit));
+                }
             }
+            if mask != S::Word::ZERO {
+                // only count the bits that weren't concurrently removed
+                let old = slot.fetch_and(mask.not(), Ordering::Release);
+                cleared += old.and(mask).count_ones() as usize;
+            }
+        }
+        S::Word::saturating_from_u128(cleared as u128)
+    }
+
+    fn import_at(&self, offset: S::Word, words: &[S::Word], policy: MergePolicy) -> Option<()> {
+        assert!(
+            offset.to_u128().is_multiple_of(S::Word::BITS as u128),
+            "offset must be a multiple of {} bits",
+            S::Word::BITS
+        );
+        let (slot_idx, _) = offset.split_index()?;
+        let slots = self.get(slot_idx..slot_idx.checked_add(words.len())?)?;
 
-            #[inline]
-            fn remove(&self, index: $ty) -> Option<bool> {
-                let slot_idx = usize::try_from(index / $ty::BITS as $ty).ok()?;
-                let mask = 1 << (index % $ty::BITS as $ty);
+        for (slot, &word) in slots.iter().zip(words) {
+            match policy {
+                MergePolicy::Overwrite => slot.store(word, Ordering::Release),
+                MergePolicy::Or => _ = slot.fetch_or(word, Ordering::Release),
+                MergePolicy::And => _ = slot.fetch_and(word, Ordering::Release),
+            }
+        }
+        Some(())
+    }
 
-                let slot = self
-                    .get(slot_idx)?
-                    .fetch_and(!mask, Ordering::Release);
+    fn insert_iter(
+        &self,
+        iter: impl IntoIterator<Item = S::Word>,
+    ) -> Result<S::Word, (S::Word, S::Word)> {
+        // sets the bits of `mask` in the given slot, and counts the newly set ones
+        let flush = |pending: Option<(usize, S::Word)>| match pending {
+            Some((slot_idx, mask)) => {
+                let old = self[slot_idx].fetch_or(mask, Ordering::Release);
+                mask.and(old.not()).count_ones() as usize
+            }
+            None => 0,
+        };
+        let count = |inserted: usize| S::Word::saturating_from_u128(inserted as u128);
+        let mut inserted = 0;
+        // slot being gathered, and the bits to set in it
+        let mut pending = None;
+        for index in iter {
+            let split = index
+                .split_index()
+                .filter(|&(slot_idx, _)| slot_idx < self.len());
 
-                Some(slot & mask != 0)
+            let Some((slot_idx, bit)) = split else {
+                return Err((count(inserted + flush(pending)), index));
+            };
+            let mask = S::Word::bit(bit);
+            match &mut pending {
+                Some((pending_idx, bits)) if *pending_idx == slot_idx => *bits = bits.or(mask),
+                _ => inserted += flush(pending.replace((slot_idx, mask))),
             }
         }
-    )*};
-}
+        Ok(count(inserted + flush(pending)))
+    }
 
-impl_shared_bit_set! {
-    u32 for AtomicU32
-    u64 for AtomicU64
-    usize for AtomicUsize
+    #[inline]
+    fn drain(&self) -> Self::Drain<'_> {
+        crate::SharedDrain::new(util::addressable(self))
+    }
 }
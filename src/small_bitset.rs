@@ -0,0 +1,252 @@
+use crate::*;
+
+/// A growable bitset that keeps up to `INLINE_WORDS` slots inline,
+/// and only moves them to the heap once a larger value is inserted.
+///
+/// Handy when most sets only hold small values, but a few grow huge.
+/// Like `Vec<usize>`, [`insert`](BitSetMut::insert) grows the set instead of failing.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{BitSet, BitSetMut, SmallBitSet};
+///
+/// let mut bitset: SmallBitSet<4> = SmallBitSet::new();
+/// bitset.insert(200).unwrap();
+/// assert!(!bitset.spilled());
+///
+/// bitset.insert(10_000).unwrap();
+/// assert!(bitset.spilled());
+/// assert!(bitset.has(200) && bitset.has(10_000));
+/// assert_eq!(bitset.size(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmallBitSet<const INLINE_WORDS: usize> {
+    storage: Storage<INLINE_WORDS>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Storage<const N: usize> {
+    Inline([usize; N]),
+    Heap(Vec<usize>),
+}
+
+impl<const INLINE_WORDS: usize> SmallBitSet<INLINE_WORDS> {
+    /// Creates an empty set, without allocating.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            storage: Storage::Inline([0; INLINE_WORDS]),
+        }
+    }
+
+    /// Returns `true` if the slots were moved to the heap.
+    #[inline]
+    pub fn spilled(&self) -> bool {
+        matches!(self.storage, Storage::Heap(_))
+    }
+
+    // makes room for at least `len` slots, spilling to the heap if needed
+    fn grow(&mut self, len: usize) {
+        match &mut self.storage {
+            Storage::Inline(slots) if len > INLINE_WORDS => {
+                let mut heap = Vec::with_capacity(len);
+                heap.extend_from_slice(slots);
+                heap.resize(len, 0);
+                self.storage = Storage::Heap(heap);
+            }
+            Storage::Heap(slots) if len > slots.len() => slots.resize(len, 0),
+            _ => {}
+        }
+    }
+}
+
+impl<const INLINE_WORDS: usize> Default for SmallBitSet<INLINE_WORDS> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const INLINE_WORDS: usize> FromIterator<usize> for SmallBitSet<INLINE_WORDS> {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<const INLINE_WORDS: usize> Extend<usize> for SmallBitSet<INLINE_WORDS> {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for value in iter {
+            // grows instead of failing
+            let _ = BitSetMut::insert(self, value);
+        }
+    }
+}
+
+impl<const INLINE_WORDS: usize> std::ops::Deref for SmallBitSet<INLINE_WORDS> {
+    type Target = [usize];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        match &self.storage {
+            Storage::Inline(slots) => slots,
+            Storage::Heap(slots) => slots,
+        }
+    }
+}
+
+impl<const INLINE_WORDS: usize> std::ops::DerefMut for SmallBitSet<INLINE_WORDS> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match &mut self.storage {
+            Storage::Inline(slots) => slots,
+            Storage::Heap(slots) => slots,
+        }
+    }
+}
+
+impl<const INLINE_WORDS: usize> BitSet<usize> for SmallBitSet<INLINE_WORDS> {
+    type Ones<'a> = Ones<'a, usize>;
+    type Zeros<'a> = Zeros<'a, usize>;
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        BitSet::capacity(&self[..])
+    }
+
+    #[inline]
+    fn has(&self, index: usize) -> bool {
+        BitSet::has(&self[..], index)
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        BitSet::<usize>::is_empty(&self[..])
+    }
+
+    #[inline]
+    fn size(&self) -> usize {
+        BitSet::size(&self[..])
+    }
+
+    #[inline]
+    fn for_each_one(&self, f: impl FnMut(usize)) {
+        BitSet::for_each_one(&self[..], f)
+    }
+
+    #[inline]
+    fn first_zero(&self) -> Option<usize> {
+        BitSet::first_zero(&self[..])
+    }
+
+    #[inline]
+    fn free_count(&self) -> usize {
+        BitSet::free_count(&self[..])
+    }
+
+    #[inline]
+    fn remaining_in(&self, range: impl RangeBounds<usize>) -> usize {
+        BitSet::remaining_in(&self[..], range)
+    }
+
+    #[inline]
+    fn count_in_range(&self, range: impl RangeBounds<usize>) -> usize {
+        BitSet::count_in_range(&self[..], range)
+    }
+
+    #[inline]
+    fn load_factor(&self) -> f64 {
+        BitSet::<usize>::load_factor(&self[..])
+    }
+
+    #[inline]
+    fn estimate_load_factor(&self, samples: usize) -> f64 {
+        BitSet::<usize>::estimate_load_factor(&self[..], samples)
+    }
+
+    #[inline]
+    fn estimate_size(&self, confidence: f64) -> SizeEstimate {
+        BitSet::<usize>::estimate_size(&self[..], confidence)
+    }
+
+    #[inline]
+    fn iter_ones_in(&self, range: impl RangeBounds<usize>) -> Self::Ones<'_> {
+        BitSet::iter_ones_in(&self[..], range)
+    }
+
+    #[inline]
+    fn iter_zeros_in(&self, range: impl RangeBounds<usize>) -> Self::Zeros<'_> {
+        BitSet::iter_zeros_in(&self[..], range)
+    }
+
+    #[inline]
+    fn has_all(&self, values: &[usize]) -> bool {
+        BitSet::has_all(&self[..], values)
+    }
+
+    #[inline]
+    fn has_any(&self, values: &[usize]) -> bool {
+        BitSet::has_any(&self[..], values)
+    }
+
+    #[inline]
+    fn gather(&self, values: &[usize], out: &mut [bool]) {
+        BitSet::gather(&self[..], values, out)
+    }
+}
+
+impl<const INLINE_WORDS: usize> BitSetMut<usize> for SmallBitSet<INLINE_WORDS> {
+    type Drain<'a> = Drain<'a, usize>;
+
+    #[inline]
+    fn clear(&mut self) {
+        BitSetMut::clear(&mut self[..])
+    }
+
+    fn insert(&mut self, index: usize) -> Result<bool, usize> {
+        match BitSetMut::insert(&mut self[..], index) {
+            Ok(has) => Ok(has),
+            Err(slot_index) => {
+                self.grow(slot_index + 1);
+                BitSetMut::insert(&mut self[..], index)
+            }
+        }
+    }
+
+    #[inline]
+    fn remove(&mut self, index: usize) -> Option<bool> {
+        BitSetMut::remove(&mut self[..], index)
+    }
+
+    #[inline]
+    fn clear_where(&mut self, predicate: impl FnMut(usize) -> bool) -> usize {
+        BitSetMut::clear_where(&mut self[..], predicate)
+    }
+
+    fn import_at(
+        &mut self,
+        offset: usize,
+        words: &[usize],
+        policy: MergePolicy,
+    ) -> Result<(), usize> {
+        match BitSetMut::import_at(&mut self[..], offset, words, policy) {
+            Ok(()) => Ok(()),
+            Err(slot_index) => {
+                self.grow(slot_index + 1);
+                BitSetMut::import_at(&mut self[..], offset, words, policy)
+            }
+        }
+    }
+
+    #[inline]
+    fn complement_in_place(&mut self) -> bool {
+        BitSetMut::complement_in_place(&mut self[..])
+    }
+
+    #[inline]
+    fn drain(&mut self) -> Self::Drain<'_> {
+        BitSetMut::drain(&mut self[..])
+    }
+}
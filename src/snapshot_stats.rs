@@ -0,0 +1,16 @@
+/// Statistics of a set computed in a single pass, see
+/// [`BitSet::snapshot_stats`](crate::BitSet::snapshot_stats).
+///
+/// Every field is derived from the same loads of the slots, so the numbers are consistent
+/// with each other even while a shared set is being modified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SnapshotStats<T> {
+    /// The number of values.
+    pub size: T,
+    /// The lowest value, or `None` if the set was empty.
+    pub first: Option<T>,
+    /// The highest value, or `None` if the set was empty.
+    pub last: Option<T>,
+    /// The [fingerprint](crate::BitSet::fingerprint) of the values.
+    pub fingerprint: u64,
+}
@@ -0,0 +1,72 @@
+use crate::*;
+
+/// `POOLS` independent identifier pools of `SLOTS_PER_POOL` slots each, laid out back to back
+/// in a single array, without any dynamic allocation.
+///
+/// Each pool allocates its identifiers from `0`, like its own [`AtomicBitSet`].
+/// Use [`slot_count`] to size the pools in bits (stable const generics can't compute
+/// the number of slots from a number of bits).
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{slot_count, BitSet, StaticPools};
+///
+/// // 4 pools of 256 identifiers
+/// static POOLS: StaticPools<4, { slot_count::from_bits(256) }> = StaticPools::new();
+///
+/// assert_eq!(POOLS.set_next_free_bit(0), Some(0));
+/// assert_eq!(POOLS.set_next_free_bit(0), Some(1));
+/// assert_eq!(POOLS.set_next_free_bit(3), Some(0));
+///
+/// assert_eq!(POOLS.pool(0).size(), 2);
+/// assert_eq!(POOLS.as_slice().len(), 4 * slot_count::from_bits(256));
+/// ```
+pub struct StaticPools<const POOLS: usize, const SLOTS_PER_POOL: usize> {
+    slots: [[AtomicUsize; SLOTS_PER_POOL]; POOLS],
+    // per pool, used for optimizing the search to find the next free bit
+    rotations: [AtomicUsize; POOLS],
+}
+
+impl<const POOLS: usize, const SLOTS_PER_POOL: usize> StaticPools<POOLS, SLOTS_PER_POOL> {
+    /// The number of identifiers of each pool.
+    pub const BITS_PER_POOL: usize = SLOTS_PER_POOL * usize::BITS as usize;
+
+    /// Creates empty pools.
+    #[inline]
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            slots: [const { [const { AtomicUsize::new(0) }; SLOTS_PER_POOL] }; POOLS],
+            rotations: [const { AtomicUsize::new(0) }; POOLS],
+        }
+    }
+
+    /// Returns the slots of the given pool, to query or update it as a [`SharedBitSet`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pool >= POOLS`.
+    #[inline]
+    pub const fn pool(&self, pool: usize) -> &[AtomicUsize] {
+        &self.slots[pool]
+    }
+
+    /// Returns the slots of every pool, one pool after another.
+    #[inline]
+    pub const fn as_slice(&self) -> &[AtomicUsize] {
+        self.slots.as_flattened()
+    }
+
+    /// Atomically finds a free identifier in the given pool, sets it, and returns it.
+    ///
+    /// Returns `None` if the pool is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pool >= POOLS`.
+    #[inline]
+    pub fn set_next_free_bit(&self, pool: usize) -> Option<usize> {
+        util::set_next_free_bit(&self.slots[pool], &self.rotations[pool])
+    }
+}
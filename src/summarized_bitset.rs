@@ -0,0 +1,250 @@
+use crate::*;
+
+/// A set of `usize` slots that keeps two summary bitmaps up to date on every mutation:
+/// which slots are full, and which slots are non-empty.
+///
+/// Finding the first non-full or non-empty slot then scans one bit per slot, instead of
+/// one word per slot. [`first_zero`](BitSet::first_zero) and [`is_empty`](BitSet::is_empty)
+/// use the summaries as well.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{BitSet, BitSetMut, SummarizedBitSet};
+///
+/// let mut bitset = SummarizedBitSet::new(4);
+/// for value in 0..usize::BITS as usize {
+///     bitset.insert(value).unwrap();
+/// }
+/// assert!(bitset.is_word_full(0));
+/// assert_eq!(bitset.first_nonfull_word(), Some(1));
+/// assert_eq!(bitset.first_zero(), Some(usize::BITS as usize));
+///
+/// bitset.clear_where(|value| value < 10);
+/// assert_eq!(bitset.first_nonfull_word(), Some(0));
+/// assert_eq!(bitset.first_nonempty_word(), Some(0));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SummarizedBitSet {
+    slots: Box<[usize]>,
+    // one bit per slot
+    full: Box<[usize]>,
+    nonempty: Box<[usize]>,
+}
+
+impl SummarizedBitSet {
+    /// Creates an empty set of `slots` slots.
+    pub fn new(slots: usize) -> Self {
+        let summary = || vec![0; slot_count::from_bits(slots)].into_boxed_slice();
+        Self {
+            slots: vec![0; slots].into(),
+            full: summary(),
+            nonempty: summary(),
+        }
+    }
+
+    /// Returns `true` if every bit of the given slot is set.
+    ///
+    /// Returns `false` if the slot is out of range.
+    #[inline]
+    pub fn is_word_full(&self, slot_idx: usize) -> bool {
+        slot_idx < self.slots.len() && BitSet::has(&self.full[..], slot_idx)
+    }
+
+    /// Returns `true` if any bit of the given slot is set.
+    ///
+    /// Returns `false` if the slot is out of range.
+    #[inline]
+    pub fn is_word_nonempty(&self, slot_idx: usize) -> bool {
+        slot_idx < self.slots.len() && BitSet::has(&self.nonempty[..], slot_idx)
+    }
+
+    /// Returns the index of the first slot with an unset bit.
+    #[inline]
+    pub fn first_nonfull_word(&self) -> Option<usize> {
+        BitSet::first_zero(&self.full[..]).filter(|&slot_idx| slot_idx < self.slots.len())
+    }
+
+    /// Returns the index of the first slot with a set bit.
+    #[inline]
+    pub fn first_nonempty_word(&self) -> Option<usize> {
+        BitSet::iter_ones(&self.nonempty[..]).next()
+    }
+
+    // updates the summaries of the given slot
+    fn refresh(&mut self, slot_idx: usize) {
+        let slot = self.slots[slot_idx];
+        let _ = match slot == usize::MAX {
+            true => BitSetMut::insert(&mut self.full[..], slot_idx).ok(),
+            false => BitSetMut::remove(&mut self.full[..], slot_idx),
+        };
+        let _ = match slot != 0 {
+            true => BitSetMut::insert(&mut self.nonempty[..], slot_idx).ok(),
+            false => BitSetMut::remove(&mut self.nonempty[..], slot_idx),
+        };
+    }
+
+    // updates the summaries of every slot
+    fn refresh_all(&mut self) {
+        for slot_idx in 0..self.slots.len() {
+            self.refresh(slot_idx);
+        }
+    }
+}
+
+impl std::ops::Deref for SummarizedBitSet {
+    type Target = [usize];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.slots
+    }
+}
+
+impl BitSet<usize> for SummarizedBitSet {
+    type Ones<'a> = Ones<'a, usize>;
+    type Zeros<'a> = Zeros<'a, usize>;
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        BitSet::capacity(&self.slots[..])
+    }
+
+    #[inline]
+    fn has(&self, index: usize) -> bool {
+        BitSet::has(&self.slots[..], index)
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.first_nonempty_word().is_none()
+    }
+
+    #[inline]
+    fn size(&self) -> usize {
+        BitSet::size(&self.slots[..])
+    }
+
+    #[inline]
+    fn for_each_one(&self, f: impl FnMut(usize)) {
+        BitSet::for_each_one(&self.slots[..], f)
+    }
+
+    fn first_zero(&self) -> Option<usize> {
+        let slot_idx = self.first_nonfull_word()?;
+        let bit = (!self.slots[slot_idx]).trailing_zeros() as usize;
+        Some(slot_idx * usize::BITS as usize + bit)
+    }
+
+    #[inline]
+    fn free_count(&self) -> usize {
+        BitSet::free_count(&self.slots[..])
+    }
+
+    #[inline]
+    fn remaining_in(&self, range: impl RangeBounds<usize>) -> usize {
+        BitSet::remaining_in(&self.slots[..], range)
+    }
+
+    #[inline]
+    fn count_in_range(&self, range: impl RangeBounds<usize>) -> usize {
+        BitSet::count_in_range(&self.slots[..], range)
+    }
+
+    #[inline]
+    fn load_factor(&self) -> f64 {
+        BitSet::<usize>::load_factor(&self.slots[..])
+    }
+
+    #[inline]
+    fn estimate_load_factor(&self, samples: usize) -> f64 {
+        BitSet::<usize>::estimate_load_factor(&self.slots[..], samples)
+    }
+
+    #[inline]
+    fn estimate_size(&self, confidence: f64) -> SizeEstimate {
+        BitSet::<usize>::estimate_size(&self.slots[..], confidence)
+    }
+
+    #[inline]
+    fn iter_ones_in(&self, range: impl RangeBounds<usize>) -> Self::Ones<'_> {
+        BitSet::iter_ones_in(&self.slots[..], range)
+    }
+
+    #[inline]
+    fn iter_zeros_in(&self, range: impl RangeBounds<usize>) -> Self::Zeros<'_> {
+        BitSet::iter_zeros_in(&self.slots[..], range)
+    }
+
+    #[inline]
+    fn has_all(&self, values: &[usize]) -> bool {
+        BitSet::has_all(&self.slots[..], values)
+    }
+
+    #[inline]
+    fn has_any(&self, values: &[usize]) -> bool {
+        BitSet::has_any(&self.slots[..], values)
+    }
+
+    #[inline]
+    fn gather(&self, values: &[usize], out: &mut [bool]) {
+        BitSet::gather(&self.slots[..], values, out)
+    }
+}
+
+impl BitSetMut<usize> for SummarizedBitSet {
+    type Drain<'a> = Drain<'a, usize>;
+
+    fn clear(&mut self) {
+        BitSetMut::clear(&mut self.slots[..]);
+        BitSetMut::clear(&mut self.full[..]);
+        BitSetMut::clear(&mut self.nonempty[..]);
+    }
+
+    #[inline]
+    fn insert(&mut self, index: usize) -> Result<bool, usize> {
+        let had = BitSetMut::insert(&mut self.slots[..], index)?;
+        self.refresh(index / usize::BITS as usize);
+        Ok(had)
+    }
+
+    #[inline]
+    fn remove(&mut self, index: usize) -> Option<bool> {
+        let had = BitSetMut::remove(&mut self.slots[..], index)?;
+        self.refresh(index / usize::BITS as usize);
+        Some(had)
+    }
+
+    fn clear_where(&mut self, predicate: impl FnMut(usize) -> bool) -> usize {
+        let cleared = BitSetMut::clear_where(&mut self.slots[..], predicate);
+        self.refresh_all();
+        cleared
+    }
+
+    fn import_at(
+        &mut self,
+        offset: usize,
+        words: &[usize],
+        policy: MergePolicy,
+    ) -> Result<(), usize> {
+        BitSetMut::import_at(&mut self.slots[..], offset, words, policy)?;
+        let start = offset / usize::BITS as usize;
+        for slot_idx in start..start + words.len() {
+            self.refresh(slot_idx);
+        }
+        Ok(())
+    }
+
+    fn complement_in_place(&mut self) -> bool {
+        BitSetMut::complement_in_place(&mut self.slots[..]);
+        self.refresh_all();
+        true
+    }
+
+    fn drain(&mut self) -> Self::Drain<'_> {
+        // the set is empty once the iterator is dropped
+        BitSetMut::clear(&mut self.full[..]);
+        BitSetMut::clear(&mut self.nonempty[..]);
+        BitSetMut::drain(&mut self.slots[..])
+    }
+}
@@ -0,0 +1,159 @@
+use crate::*;
+use std::time::{Duration, Instant};
+
+/// An [`AtomicBitSet`] wrapper that records when each identifier was last inserted or removed.
+///
+/// [`least_recently_used_free`](TimedBitSet::least_recently_used_free) then prefers the
+/// identifiers that have been free the longest, so an identifier isn't reused while
+/// downstream caches may still hold it. Available with the `timestamps` feature.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::TimedBitSet;
+///
+/// let ids: TimedBitSet<1> = TimedBitSet::new();
+/// let a = ids.least_recently_used_free().unwrap();
+/// let b = ids.least_recently_used_free().unwrap();
+/// assert_eq!((a, b), (0, 1));
+///
+/// ids.remove(a);
+/// // `a` was just freed, so every never-used identifier is colder
+/// assert_eq!(ids.least_recently_used_free(), Some(2));
+/// assert!(ids.last_used(a).is_some());
+/// ```
+pub struct TimedBitSet<const N: usize> {
+    bitset: AtomicBitSet<N>,
+    // nanoseconds since `epoch` plus one, of the last change of each identifier, or zero if never changed
+    stamps: Box<[AtomicU64]>,
+    epoch: Instant,
+}
+
+impl<const N: usize> TimedBitSet<N> {
+    /// Creates a new, empty `TimedBitSet`, where no identifier was ever used.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            bitset: AtomicBitSet::new(),
+            stamps: (0..N * usize::BITS as usize)
+                .map(|_| AtomicU64::new(0))
+                .collect(),
+            epoch: Instant::now(),
+        }
+    }
+
+    /// Returns the underlying bitset.
+    ///
+    /// Changes made through it directly are not timestamped.
+    #[inline]
+    pub fn bitset(&self) -> &AtomicBitSet<N> {
+        &self.bitset
+    }
+
+    /// Inserts the identifier, recording the time if it wasn't in the set.
+    ///
+    /// Returns `Some(true)` if it was already in the set, or `None` if it is out of range.
+    pub fn insert(&self, index: usize) -> Option<bool> {
+        let had = SharedBitSet::insert(&self.bitset[..], index)?;
+        if !had {
+            self.stamp(index);
+        }
+        Some(had)
+    }
+
+    /// Removes the identifier, recording the time if it was in the set.
+    ///
+    /// Returns `Some(true)` if it was in the set, or `None` if it is out of range.
+    pub fn remove(&self, index: usize) -> Option<bool> {
+        let had = SharedBitSet::remove(&self.bitset[..], index)?;
+        if had {
+            self.stamp(index);
+        }
+        Some(had)
+    }
+
+    /// Returns when the identifier was last inserted, removed or touched,
+    /// or `None` if it never was, or is out of range.
+    pub fn last_used(&self, index: usize) -> Option<Instant> {
+        match self.stamps.get(index)?.load(Ordering::Acquire) {
+            0 => None,
+            stamp => Some(self.epoch + Duration::from_nanos(stamp - 1)),
+        }
+    }
+
+    /// Inserts the free identifier that was used the least recently
+    /// (never used identifiers first, lowest first), and returns it.
+    ///
+    /// Every free identifier is inspected, so this is `O(capacity)`.
+    /// Returns `None` if the set is full.
+    pub fn least_recently_used_free(&self) -> Option<usize> {
+        loop {
+            let coldest = self
+                .bitset
+                .iter_zeros()
+                .min_by_key(|&index| self.stamps[index].load(Ordering::Acquire))?;
+
+            // another thread may have taken it in the meantime
+            if self.insert(coldest) == Some(false) {
+                return Some(coldest);
+            }
+        }
+    }
+
+    /// Records the current time as the last use of the identifier, if it is in the set.
+    ///
+    /// Keeps an identifier alive for [`reap_idle`](TimedBitSet::reap_idle),
+    /// e.g. on every request of a session. Returns `false` if it isn't in the set.
+    pub fn touch(&self, index: usize) -> bool {
+        let used = BitSet::has(&self.bitset[..], index);
+        if used {
+            self.stamp(index);
+        }
+        used
+    }
+
+    /// Removes the identifiers that weren't inserted or [touched](TimedBitSet::touch)
+    /// for longer than `older_than`, and returns them in ascending order.
+    ///
+    /// An identifier touched concurrently, after it was found idle, is still removed.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::TimedBitSet;
+    /// use std::time::Duration;
+    ///
+    /// let sessions: TimedBitSet<1> = TimedBitSet::new();
+    /// sessions.insert(3);
+    /// sessions.insert(7);
+    ///
+    /// std::thread::sleep(Duration::from_millis(20));
+    /// sessions.touch(7);
+    ///
+    /// assert_eq!(sessions.reap_idle(Duration::from_millis(10)), [3]);
+    /// assert_eq!(sessions.insert(7), Some(true));
+    /// ```
+    pub fn reap_idle(&self, older_than: Duration) -> Vec<usize> {
+        let Some(cutoff) = self.epoch.elapsed().checked_sub(older_than) else {
+            return Vec::new();
+        };
+        // stamps are offset by one, see `stamp`
+        let cutoff = cutoff.as_nanos().min(u64::MAX as u128 - 1) as u64 + 1;
+
+        let mut reaped = Vec::new();
+        for index in self.bitset.iter_ones() {
+            if self.stamps[index].load(Ordering::Acquire) < cutoff
+                && self.remove(index) == Some(true)
+            {
+                reaped.push(index);
+            }
+        }
+        reaped
+    }
+
+    // records the current time as the last use of `index`
+    fn stamp(&self, index: usize) {
+        let nanos = self.epoch.elapsed().as_nanos().min(u64::MAX as u128 - 1) as u64;
+        self.stamps[index].store(nanos + 1, Ordering::Release);
+    }
+}
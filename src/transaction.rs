@@ -0,0 +1,120 @@
+use crate::*;
+
+/// Stages insertions and removals across several shared bitsets,
+/// and applies them all or none of them.
+///
+/// Useful for keeping related bitsets (by user, by shard, global, ...) consistent.
+///
+/// A transaction is not isolated: concurrent readers may observe it partially applied,
+/// or partially rolled back.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{BitSet, SharedBitSet, Transaction};
+/// use std::sync::atomic::AtomicUsize;
+///
+/// let by_user: [AtomicUsize; 1] = Default::default();
+/// let global: [AtomicUsize; 1] = Default::default();
+/// global.insert(7);
+///
+/// let mut tx = Transaction::new();
+/// tx.insert(&by_user[..], 7).insert(&global[..], 7);
+///
+/// // `7` is already in `global`, so nothing is applied
+/// assert_eq!(tx.commit(), Err(1));
+/// assert!(!by_user.has(7));
+/// ```
+pub struct Transaction<'a, S: ?Sized, T> {
+    operations: Vec<(&'a S, Operation, T)>,
+}
+
+#[derive(Clone, Copy)]
+enum Operation {
+    Insert,
+    Remove,
+}
+
+impl<'a, S, T> Transaction<'a, S, T>
+where
+    S: SharedBitSet<T> + ?Sized,
+    T: Copy,
+{
+    /// Creates an empty transaction.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            operations: Vec::new(),
+        }
+    }
+
+    /// Stages the insertion of `index` into `set`.
+    ///
+    /// The commit fails if `index` is already in `set`.
+    #[inline]
+    pub fn insert(&mut self, set: &'a S, index: T) -> &mut Self {
+        self.operations.push((set, Operation::Insert, index));
+        self
+    }
+
+    /// Stages the removal of `index` from `set`.
+    ///
+    /// The commit fails if `index` is not in `set`.
+    #[inline]
+    pub fn remove(&mut self, set: &'a S, index: T) -> &mut Self {
+        self.operations.push((set, Operation::Remove, index));
+        self
+    }
+
+    /// Applies the staged operations in order.
+    ///
+    /// Returns `Err(usize)` with the position of the first operation that failed
+    /// (because the index was out of range, or already had the requested value),
+    /// after rolling back the operations applied before it.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use index_set::{BitSet, SharedBitSet, Transaction};
+    /// use std::sync::atomic::AtomicUsize;
+    ///
+    /// let by_shard: [AtomicUsize; 1] = Default::default();
+    /// let global: [AtomicUsize; 1] = Default::default();
+    /// by_shard.insert(3);
+    ///
+    /// let mut tx = Transaction::new();
+    /// tx.remove(&by_shard[..], 3).insert(&global[..], 3);
+    ///
+    /// assert_eq!(tx.commit(), Ok(()));
+    /// assert!(!by_shard.has(3) && global.has(3));
+    /// ```
+    pub fn commit(self) -> Result<(), usize> {
+        for (position, &(set, operation, index)) in self.operations.iter().enumerate() {
+            let applied = match operation {
+                Operation::Insert => set.insert(index) == Some(false),
+                Operation::Remove => set.remove(index) == Some(true),
+            };
+            if !applied {
+                for &(set, operation, index) in self.operations[..position].iter().rev() {
+                    match operation {
+                        Operation::Insert => set.remove(index),
+                        Operation::Remove => set.insert(index),
+                    };
+                }
+                return Err(position);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S, T> Default for Transaction<'_, S, T>
+where
+    S: SharedBitSet<T> + ?Sized,
+    T: Copy,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
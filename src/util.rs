@@ -0,0 +1,656 @@
+use crate::*;
+
+/// Iterates over `slice` starting at index `n`, wrapping around to the beginning.
+///
+/// # Panics
+///
+/// Panics if `n > slice.len()`.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::util;
+///
+/// let items: Vec<_> = util::rotate_left(&[1, 2, 3, 4], 1).copied().collect();
+/// assert_eq!(items, [2, 3, 4, 1]);
+/// ```
+pub fn rotate_left<T>(slice: &[T], n: usize) -> impl Iterator<Item = &T> {
+    let (left, right) = slice.split_at(n);
+    right.iter().chain(left)
+}
+
+/// Splits `index` into the index of its slot and the position of its bit in that slot,
+/// for slots of `slot_bits` bits.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::util;
+///
+/// assert_eq!(util::split_index(70, u32::BITS), (2, 6));
+/// assert_eq!(util::split_index(70, u64::BITS), (1, 6));
+/// ```
+#[inline]
+pub const fn split_index(index: usize, slot_bits: u32) -> (usize, u32) {
+    (
+        index / slot_bits as usize,
+        (index % slot_bits as usize) as u32,
+    )
+}
+
+/// Returns a slot mask with the bits in `start..end` set.
+///
+/// # Panics
+///
+/// Panics if `start > end` or `end > usize::BITS`.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::util;
+///
+/// assert_eq!(util::bit_mask(1, 4), 0b1110);
+/// assert_eq!(util::bit_mask(0, usize::BITS), usize::MAX);
+/// assert_eq!(util::bit_mask(3, 3), 0);
+/// ```
+#[inline]
+pub const fn bit_mask(start: u32, end: u32) -> usize {
+    assert!(start <= end && end <= usize::BITS);
+    if start == end {
+        return 0;
+    }
+    (usize::MAX >> (usize::BITS - (end - start))) << start
+}
+
+/// Splits `slots` into chunks of `chunk_bits` bits, each paired with the index of its first bit.
+///
+/// Lets gigantic sets be written to a socket or a file incrementally, without building
+/// one huge buffer. The last chunk may be shorter.
+///
+/// # Panics
+///
+/// Panics if `chunk_bits` is zero or not a multiple of the number of bits of a slot.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::util;
+///
+/// let slots = [1_u32, 2, 3, 4, 5];
+/// let chunks: Vec<_> = util::export_chunks(&slots, 64).collect();
+/// assert_eq!(chunks, [(0, &[1, 2][..]), (64, &[3, 4]), (128, &[5])]);
+/// ```
+pub fn export_chunks<T: Word>(
+    slots: &[T],
+    chunk_bits: usize,
+) -> impl Iterator<Item = (usize, &[T])> {
+    let slot_bits = T::BITS as usize;
+    assert!(
+        chunk_bits != 0 && chunk_bits.is_multiple_of(slot_bits),
+        "chunk size must be a non-zero multiple of {slot_bits} bits"
+    );
+    slots
+        .chunks(chunk_bits / slot_bits)
+        .enumerate()
+        .map(move |(i, chunk)| (i * chunk_bits, chunk))
+}
+
+/// Returns an iterator over the non-zero words of `slots`, each paired with the index of its slot.
+///
+/// Works with plain and atomic slots alike (each atomic slot is loaded once, with `Acquire` ordering),
+/// so SIMD kernels and serializers can process the raw words without re-deriving the slot math.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{util, SharedBitSet};
+/// use std::sync::atomic::AtomicU32;
+///
+/// let plain = [0_u32, 5, 0, 8];
+/// assert_eq!(util::slots(&plain).collect::<Vec<_>>(), [(1, 5), (3, 8)]);
+///
+/// let atomic: [AtomicU32; 4] = Default::default();
+/// atomic.insert(70);
+/// assert_eq!(util::slots(&atomic).collect::<Vec<_>>(), [(2, 1 << 6)]);
+/// ```
+pub fn slots<S: Slot>(slots: &[S]) -> impl Iterator<Item = (usize, S::Word)> {
+    slots
+        .iter()
+        .map(Slot::load)
+        .enumerate()
+        .filter(|&(_, word)| word != S::Word::ZERO)
+}
+
+/// Returns an iterator over the values that were added to or removed from `old`
+/// to get `new`, in ascending order.
+///
+/// The slices may have different lengths: the missing slots of the shorter one are
+/// treated as empty. Atomic slots are loaded once each, with `Acquire` ordering.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{util, Change};
+///
+/// let snapshot = [0b0110_u32];
+/// let current = [0b0011_u32, 1];
+///
+/// let changes: Vec<_> = util::diff(&snapshot, &current).collect();
+/// assert_eq!(changes, [Change::Added(0), Change::Removed(2), Change::Added(32)]);
+/// ```
+#[inline]
+pub fn diff<'a, S: Slot>(old: &'a [S], new: &'a [S]) -> Diff<'a, S> {
+    Diff::new(addressable(old), addressable(new))
+}
+
+/// Returns an iterator over the values of any of `sets`, in ascending order and without
+/// duplicates, e.g. to walk the union of per-shard sets without building it.
+///
+/// The sets may have different lengths. Atomic slots are loaded once each, with `Acquire` ordering.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::util;
+///
+/// let shards: [&[u32]; 3] = [&[0b0101], &[0b0011, 1], &[]];
+/// let union: Vec<_> = util::union_iter(&shards).collect();
+/// assert_eq!(union, [0, 1, 2, 32]);
+/// ```
+#[inline]
+pub fn union_iter<'a, S: Slot>(sets: &'a [&'a [S]]) -> UnionIter<'a, S> {
+    UnionIter::new(sets)
+}
+
+/// Returns the number of values in both `a` and `b`, without building their intersection.
+///
+/// Sums the popcount of `a & b` slot by slot. The slices may have different lengths and
+/// slot types of the same width, e.g. a snapshot against a shared set. Atomic slots are
+/// loaded once each, with `Acquire` ordering.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::util;
+/// use std::sync::atomic::AtomicU64;
+///
+/// let a = [0b0111_u64, 1];
+/// let b = [AtomicU64::new(0b1110)];
+/// assert_eq!(util::intersection_len(&a, &b), 2);
+/// ```
+#[inline]
+pub fn intersection_len<A: Slot, B: Slot<Word = A::Word>>(a: &[A], b: &[B]) -> A::Word {
+    count_pairs(a, b, Word::and)
+}
+
+/// Returns the number of values in `a` or `b`, without building their union.
+///
+/// Sums the popcount of `a | b` slot by slot, like [`intersection_len`].
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::util;
+///
+/// let a = [0b0111_u64, 1];
+/// let b = [0b1110_u64];
+/// assert_eq!(util::union_len(&a, &b), 5);
+/// ```
+#[inline]
+pub fn union_len<A: Slot, B: Slot<Word = A::Word>>(a: &[A], b: &[B]) -> A::Word {
+    count_pairs(a, b, Word::or)
+}
+
+/// Returns the number of values in `a` but not in `b`, without building their difference.
+///
+/// Sums the popcount of `a & !b` slot by slot, like [`intersection_len`].
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::util;
+///
+/// let a = [0b0111_u64, 1];
+/// let b = [0b1110_u64];
+/// assert_eq!(util::difference_len(&a, &b), 2);
+/// assert_eq!(util::difference_len(&b, &a), 1);
+/// ```
+#[inline]
+pub fn difference_len<A: Slot, B: Slot<Word = A::Word>>(a: &[A], b: &[B]) -> A::Word {
+    count_pairs(a, b, |a, b| a.and(b.not()))
+}
+
+/// Returns the number of values in exactly one of `a` and `b`, i.e. the Hamming distance
+/// between their bits.
+///
+/// Sums the popcount of `a ^ b` slot by slot, like [`intersection_len`]. The missing slots
+/// of the shorter slice are treated as empty.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::util;
+///
+/// let a = [0b0111_u64, 1];
+/// let b = [0b1110_u64];
+/// assert_eq!(util::hamming_distance(&a, &b), 3);
+/// assert_eq!(util::hamming_distance(&a, &a), 0);
+/// ```
+#[inline]
+pub fn hamming_distance<A: Slot, B: Slot<Word = A::Word>>(a: &[A], b: &[B]) -> A::Word {
+    count_pairs(a, b, Word::xor)
+}
+
+// sums the popcount of `op` applied to each pair of slots, the missing slots being empty
+fn count_pairs<A: Slot, B: Slot<Word = A::Word>>(
+    a: &[A],
+    b: &[B],
+    op: impl Fn(A::Word, A::Word) -> A::Word,
+) -> A::Word {
+    let count: u128 = (0..a.len().max(b.len()))
+        .map(|i| {
+            let a = a.get(i).map_or(A::Word::ZERO, Slot::load);
+            let b = b.get(i).map_or(A::Word::ZERO, Slot::load);
+            op(a, b).count_ones() as u128
+        })
+        .sum();
+    A::Word::saturating_from_u128(count)
+}
+
+/// Returns how many of the first `len` slots of `W` words hold values that fit in a `W`,
+/// e.g. at most 32 slots of `u8`, as the values of the 33rd one would be above `u8::MAX`.
+#[inline]
+pub(crate) fn addressable_len<W: Word>(len: usize) -> usize {
+    let max = W::MAX.to_u128() / W::BITS as u128 + 1;
+    usize::try_from(max).map_or(len, |max| len.min(max))
+}
+
+/// Returns the slots whose values fit in their word type, see [`addressable_len`].
+///
+/// The per-value operations of the slices ignore the slots past them.
+#[inline]
+pub(crate) fn addressable<S: Slot>(slots: &[S]) -> &[S] {
+    &slots[..addressable_len::<S::Word>(slots.len())]
+}
+
+/// Converts `range` to the bit range `start..end`, clamped to `capacity`.
+///
+/// Unbounded ends cover the whole capacity, and reversed ranges are empty.
+pub(crate) fn bit_range<T: Copy>(
+    range: &impl RangeBounds<T>,
+    capacity: u128,
+    to_u128: impl Fn(T) -> u128,
+) -> (u128, u128) {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => to_u128(start),
+        Bound::Excluded(&start) => to_u128(start).saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => to_u128(end).saturating_add(1),
+        Bound::Excluded(&end) => to_u128(end),
+        Bound::Unbounded => capacity,
+    };
+    (start, end.min(capacity))
+}
+
+/// Looks up each of `indices` in `slots`, yielding `None` for the ones out of range.
+///
+/// Consecutive indices that fall in the same slot share a single load.
+pub(crate) fn lookup<'a, S: Slot>(
+    slots: &'a [S],
+    indices: &'a [S::Word],
+) -> impl Iterator<Item = Option<bool>> + 'a {
+    let bits = S::Word::BITS as u128;
+    let mut loaded: Option<(usize, S::Word)> = None;
+    indices.iter().map(move |index| {
+        let index = index.to_u128();
+        let slot_idx = usize::try_from(index / bits).ok()?;
+        let word = match loaded {
+            Some((loaded_idx, word)) if loaded_idx == slot_idx => word,
+            _ => {
+                let word = slots.get(slot_idx)?.load();
+                loaded = Some((slot_idx, word));
+                word
+            }
+        };
+        let bit = (index % bits) as u32;
+        Some(word.and(S::Word::mask(bit, bit + 1)) != S::Word::ZERO)
+    })
+}
+
+/// Splits the bit range `start..end` into per-slot ranges, for slots of `slot_bits` bits.
+///
+/// Yields `(slot_idx, start_bit, end_bit)`, where `start_bit < end_bit <= slot_bits`.
+pub(crate) fn slot_ranges(
+    mut start: u128,
+    end: u128,
+    slot_bits: u32,
+) -> impl Iterator<Item = (usize, u32, u32)> {
+    let bits = slot_bits as u128;
+    core::iter::from_fn(move || {
+        if start >= end {
+            return None;
+        }
+        let slot_idx = start / bits;
+        let slot_start = slot_idx * bits;
+        let slot_end = slot_start.saturating_add(bits).min(end);
+
+        let range = (start - slot_start) as u32..(slot_end - slot_start) as u32;
+        start = slot_end;
+        Some((usize::try_from(slot_idx).ok()?, range.start, range.end))
+    })
+}
+
+/// Returns the indices of `samples` slots, evenly spread among `len` slots.
+/// Every slot is returned if `samples >= len`.
+///
+/// The slots are split into `samples` buckets, and a pseudo-random slot is picked from each bucket,
+/// so that periodic patterns in the data don't bias the sample.
+pub(crate) fn sample_slots(len: usize, samples: usize) -> impl Iterator<Item = usize> {
+    let samples = samples.min(len);
+    let bucket_start = move |i: usize| (i as u128 * len as u128 / samples as u128) as usize;
+    (0..samples).map(move |i| {
+        let (start, end) = (bucket_start(i), bucket_start(i + 1));
+        start + (splitmix64(i as u64) % (end - start) as u64) as usize
+    })
+}
+
+/// A fast, deterministic 64-bit mixing function.
+#[inline]
+pub(crate) const fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Hashes a single value of a set, see [`BitSet::fingerprint`].
+///
+/// A fingerprint is the XOR of the hashes of its values, so it can be updated
+/// one value at a time.
+#[inline]
+pub(crate) const fn hash_index(index: u128) -> u64 {
+    splitmix64(index as u64 ^ splitmix64((index >> 64) as u64))
+}
+
+/// Finds the lowest free bit in `slots`, and sets it.
+pub(crate) fn set_lowest_free_bit(slots: &[AtomicUsize]) -> Option<usize> {
+    for (slot_idx, slot) in slots.iter().enumerate() {
+        let available_slot = slot.fetch_update(Ordering::AcqRel, Ordering::Acquire, |curr| {
+            if curr == usize::MAX {
+                return None;
+            }
+            Some(curr | (1 << (!curr).trailing_zeros()))
+        });
+
+        if let Ok(curr) = available_slot {
+            let next_available_bit = (!curr).trailing_zeros() as usize;
+            return Some(slot_idx * usize::BITS as usize + next_available_bit);
+        }
+    }
+    None
+}
+
+/// Same as [`set_lowest_free_bit`], but gives up after `max_attempts` failed compare-and-swaps.
+pub(crate) fn set_lowest_free_bit_bounded(
+    slots: &[AtomicUsize],
+    max_attempts: usize,
+) -> Result<usize, AllocError> {
+    set_free_bit_bounded(slots.iter().enumerate(), max_attempts)
+}
+
+/// Same as [`set_next_free_bit`], but gives up after `max_attempts` failed compare-and-swaps.
+pub(crate) fn set_next_free_bit_bounded(
+    slots: &[AtomicUsize],
+    rotation: &AtomicUsize,
+    max_attempts: usize,
+) -> Result<usize, AllocError> {
+    let skip = rotation.load(Ordering::Relaxed);
+    let rotated = (skip..slots.len())
+        .chain(0..skip)
+        .map(|slot_idx| (slot_idx, &slots[slot_idx]));
+    let index = set_free_bit_bounded(rotated, max_attempts)?;
+
+    let slot_idx = index / usize::BITS as usize;
+    if skip != slot_idx {
+        rotation.store(slot_idx, Ordering::Relaxed);
+    }
+    Ok(index)
+}
+
+// sets the lowest free bit of the first slot of `slots` that has one,
+// giving up after `max_failures` failed compare-and-swaps
+fn set_free_bit_bounded<'a>(
+    slots: impl Iterator<Item = (usize, &'a AtomicUsize)>,
+    max_failures: usize,
+) -> Result<usize, AllocError> {
+    let mut failures = 0;
+    for (slot_idx, slot) in slots {
+        let mut curr = slot.load(Ordering::Acquire);
+        while curr != usize::MAX {
+            let bit = (!curr).trailing_zeros();
+            // the strong variant doesn't fail spuriously, so every failure is due to contention
+            match slot.compare_exchange(
+                curr,
+                curr | (1 << bit),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Ok(slot_idx * usize::BITS as usize + bit as usize),
+                Err(_) if failures == max_failures => return Err(AllocError::Contended),
+                Err(actual) => {
+                    failures += 1;
+                    curr = actual;
+                }
+            }
+        }
+    }
+    Err(AllocError::Full)
+}
+
+/// Finds the next free bit in `slots`, starting the search from the slot pointed by `rotation`.
+pub(crate) fn set_next_free_bit(slots: &[AtomicUsize], rotation: &AtomicUsize) -> Option<usize> {
+    // rotate the slots to find the next free id
+    let skip = rotation.load(Ordering::Relaxed);
+    let mut slot_idx = skip;
+
+    for slot in rotate_left(slots, skip) {
+        let available_slot = slot.fetch_update(Ordering::AcqRel, Ordering::Acquire, |curr| {
+            // slot is full
+            if curr == usize::MAX {
+                return None;
+            }
+            let next_available_bit = (!curr).trailing_zeros() as usize;
+            Some(curr | (1 << next_available_bit))
+        });
+
+        if let Ok(curr) = available_slot {
+            if skip != slot_idx {
+                rotation.store(slot_idx, Ordering::Relaxed);
+            }
+            let next_available_bit = (!curr).trailing_zeros() as usize;
+            return Some(slot_idx * usize::BITS as usize + next_available_bit);
+        }
+
+        slot_idx += 1;
+        if slot_idx >= slots.len() {
+            slot_idx = 0;
+        }
+    }
+    None
+}
+
+/// Atomically sets a free bit of `slots` chosen uniformly at random, and returns its index.
+///
+/// Picks the rank of the bit among the free bits of a snapshot, and retries from a new
+/// snapshot if its slot changed in the meantime.
+#[cfg(feature = "rand")]
+pub(crate) fn set_random_free_bit(
+    slots: &[AtomicUsize],
+    rng: &mut impl rand::Rng,
+) -> Option<usize> {
+    'retry: loop {
+        let free: usize = scan(slots)
+            .map(|slot| slot.load(Ordering::Acquire).count_zeros() as usize)
+            .sum();
+        if free == 0 {
+            return None;
+        }
+        let mut rank = rng.gen_range(0..free);
+        for (slot_idx, slot) in slots.iter().enumerate() {
+            let curr = slot.load(Ordering::Acquire);
+            let count = curr.count_zeros() as usize;
+            if rank >= count {
+                rank -= count;
+                continue;
+            }
+            let mut zeros = !curr;
+            for _ in 0..rank {
+                zeros &= zeros - 1;
+            }
+            let bit = zeros.trailing_zeros() as usize;
+            let claimed =
+                slot.compare_exchange(curr, curr | 1 << bit, Ordering::AcqRel, Ordering::Acquire);
+            match claimed {
+                Ok(_) => return Some(slot_idx * usize::BITS as usize + bit),
+                Err(_) => continue 'retry,
+            }
+        }
+        // the slots were filled concurrently
+    }
+}
+
+/// Iterates over the slots of `slice` for a full scan.
+///
+/// With the `prefetch` feature, the cache lines a few steps ahead of the current slot
+/// are prefetched, which helps memory-latency-bound scans of very large sets.
+#[cfg(feature = "prefetch")]
+#[inline]
+pub(crate) fn scan<T>(slice: &[T]) -> impl Iterator<Item = &T> {
+    // how many cache lines ahead to prefetch
+    const DISTANCE: usize = 8;
+    let per_line = (64 / size_of::<T>()).max(1);
+
+    slice.iter().enumerate().map(move |(i, slot)| {
+        if i % per_line == 0
+            && let Some(ahead) = slice.get(i + DISTANCE * per_line)
+        {
+            prefetch(ahead);
+        }
+        slot
+    })
+}
+
+#[cfg(not(feature = "prefetch"))]
+#[inline]
+pub(crate) fn scan<T>(slice: &[T]) -> impl Iterator<Item = &T> {
+    slice.iter()
+}
+
+#[cfg(feature = "prefetch")]
+#[inline(always)]
+fn prefetch<T>(_ptr: &T) {
+    #[cfg(target_arch = "x86_64")]
+    // SAFETY: prefetching is only a hint and never faults, and `_ptr` is a valid reference.
+    unsafe {
+        use core::arch::x86_64::{_MM_HINT_T0, _mm_prefetch};
+        _mm_prefetch::<_MM_HINT_T0>((_ptr as *const T).cast());
+    }
+}
+
+/// Atomically sets the free bit at the next position of the permutation of `slots` seeded
+/// by `seed`, and moves `step` past it.
+///
+/// See [`AtomicBitSet::set_next_free_bit_shuffled`].
+pub(crate) fn set_next_free_bit_shuffled(
+    slots: &[AtomicUsize],
+    step: &AtomicUsize,
+    seed: u64,
+) -> Option<usize> {
+    let capacity = slots.len() * usize::BITS as usize;
+    for _ in 0..capacity {
+        let (Ok(position) | Err(position)) =
+            step.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |step| {
+                Some((step + 1) % capacity)
+            });
+        let index = shuffle(position % capacity, capacity, seed);
+        let (slot_idx, bit) = split_index(index, usize::BITS);
+        let bit = 1 << bit;
+        // skips the taken bits without writing to their slot
+        if slots[slot_idx].load(Ordering::Relaxed) & bit == 0
+            && slots[slot_idx].fetch_or(bit, Ordering::AcqRel) & bit == 0
+        {
+            return Some(index);
+        }
+    }
+    None
+}
+
+/// Maps `position` to its place in a permutation of `0..capacity` seeded by `seed`.
+///
+/// Applies a few rounds of bijections on the smallest power of two that holds `capacity`,
+/// and walks the cycle until the result is below `capacity`.
+fn shuffle(mut position: usize, capacity: usize, seed: u64) -> usize {
+    let mask = capacity.next_power_of_two() - 1;
+    let shift = mask.count_ones() / 2 + 1;
+    loop {
+        for round in 0..4 {
+            let key = splitmix64(seed ^ round);
+            position = position.wrapping_mul(key as usize | 1) & mask;
+            position ^= position >> shift;
+            position = position.wrapping_add((key >> 32) as usize) & mask;
+        }
+        if position < capacity {
+            return position;
+        }
+    }
+}
+
+/// Atomically sets the next free bit at or after `cursor`, wrapping around, and moves `cursor` past it.
+///
+/// See [`AtomicBitSet::set_next_free_bit_cyclic`].
+pub(crate) fn set_next_free_bit_cyclic(
+    slots: &[AtomicUsize],
+    cursor: &AtomicUsize,
+) -> Option<usize> {
+    let n = slots.len();
+    let capacity = n * usize::BITS as usize;
+    if capacity == 0 {
+        return None;
+    }
+    let start = cursor.load(Ordering::Relaxed) % capacity;
+    let (start_slot, start_bit) = split_index(start, usize::BITS);
+    let start_mask = bit_mask(start_bit, usize::BITS);
+
+    // The starting slot is visited twice: first the bits at or after the cursor,
+    // and after wrapping around, the bits before it.
+    for step in 0..=n {
+        let slot_idx = (start_slot + step) % n;
+        let mask = match step {
+            0 => start_mask,
+            _ if step == n => !start_mask,
+            _ => usize::MAX,
+        };
+        let available_slot =
+            slots[slot_idx].fetch_update(Ordering::AcqRel, Ordering::Acquire, |curr| {
+                let free = !curr & mask;
+                if free == 0 {
+                    return None;
+                }
+                Some(curr | (1 << free.trailing_zeros()))
+            });
+
+        if let Ok(curr) = available_slot {
+            let index = slot_idx * usize::BITS as usize + (!curr & mask).trailing_zeros() as usize;
+            let next = (index + 1) % capacity;
+            let distance = |pos: usize| (pos + capacity - start) % capacity;
+            // Never move the cursor backwards if a concurrent call already advanced it further.
+            let _ = cursor.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |curr| {
+                (distance(next) > distance(curr % capacity)).then_some(next)
+            });
+            return Some(index);
+        }
+    }
+    None
+}
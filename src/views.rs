@@ -0,0 +1,240 @@
+use crate::*;
+
+/// A view of the values of either of two sets, without copying them.
+///
+/// Lookups are combined on the fly, so composed sets can be passed to the APIs taking
+/// a [`BitSet`] without temporary allocations. The capacity is the larger of the two.
+///
+/// The estimates combine the estimates of both sets, assuming they are independent.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{BitSet, UnionView};
+///
+/// let reserved: [u64; 1] = [0b0011];
+/// let allocated: [u64; 2] = [0b0110, 0b1];
+///
+/// let taken = UnionView(&reserved[..], &allocated[..]);
+/// assert_eq!(taken.capacity(), 128);
+/// assert_eq!(taken.iter_ones().collect::<Vec<_>>(), [0, 1, 2, 64]);
+/// assert_eq!(taken.first_zero(), Some(3));
+/// assert_eq!(taken.size(), 4);
+/// ```
+#[derive(Debug)]
+pub struct UnionView<'a, A: ?Sized, B: ?Sized>(pub &'a A, pub &'a B);
+
+/// A view of the values of both of two sets, without copying them.
+///
+/// Lookups are combined on the fly, so composed sets can be passed to the APIs taking
+/// a [`BitSet`] without temporary allocations. The capacity is the smaller of the two.
+///
+/// The estimates combine the estimates of both sets, assuming they are independent.
+///
+/// ## Examples
+///
+/// ```rust
+/// use index_set::{BitSet, IntersectionView};
+///
+/// let allocated: [u64; 2] = [0b0111, 0b1];
+/// let permitted: [u64; 1] = [0b0110];
+///
+/// let usable = IntersectionView(&allocated[..], &permitted[..]);
+/// assert_eq!(usable.capacity(), 64);
+/// assert_eq!(usable.iter_ones().collect::<Vec<_>>(), [1, 2]);
+/// assert_eq!(usable.first_zero(), Some(0));
+/// assert!(!usable.has(64));
+/// ```
+#[derive(Debug)]
+pub struct IntersectionView<'a, A: ?Sized, B: ?Sized>(pub &'a A, pub &'a B);
+
+macro_rules! impl_copy {
+    ($($view: ident),*) => {$(
+        impl<A: ?Sized, B: ?Sized> Clone for $view<'_, A, B> {
+            #[inline]
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<A: ?Sized, B: ?Sized> Copy for $view<'_, A, B> {}
+    )*};
+}
+
+impl_copy! {
+    UnionView, IntersectionView
+}
+
+impl<'a, T, A, B> BitSet<T> for UnionView<'a, A, B>
+where
+    T: Word,
+    A: BitSet<T> + ?Sized,
+    B: BitSet<T> + ?Sized,
+{
+    type Ones<'b>
+        = Merged<A::Ones<'a>, B::Ones<'a>>
+    where
+        Self: 'b;
+    type Zeros<'b>
+        = std::iter::Chain<Filtered<'a, A::Zeros<'a>, B>, B::Zeros<'a>>
+    where
+        Self: 'b;
+
+    #[inline]
+    fn capacity(&self) -> T {
+        let capacity = self.0.capacity().to_u128().max(self.1.capacity().to_u128());
+        T::from_u128(capacity)
+    }
+
+    #[inline]
+    fn has(&self, index: T) -> bool {
+        self.0.has(index) || self.1.has(index)
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.0.is_empty() && self.1.is_empty()
+    }
+
+    fn size(&self) -> T {
+        let only_b = Filtered::new(self.1.iter_ones(), self.0, false).count();
+        T::saturating_from_u128(self.0.size().to_u128() + only_b as u128)
+    }
+
+    fn estimate_load_factor(&self, samples: usize) -> f64 {
+        let capacity = self.capacity().to_u128() as f64;
+        if capacity == 0.0 {
+            return 0.0;
+        }
+        // the load factors over the capacity of the union
+        let a = self.0.estimate_load_factor(samples) * self.0.capacity().to_u128() as f64;
+        let b = self.1.estimate_load_factor(samples) * self.1.capacity().to_u128() as f64;
+        let (a, b) = (a / capacity, b / capacity);
+        a + b - a * b
+    }
+
+    fn estimate_size(&self, confidence: f64) -> SizeEstimate {
+        let capacity = self.capacity().to_u128() as f64;
+        let (a, b) = (
+            self.0.estimate_size(confidence),
+            self.1.estimate_size(confidence),
+        );
+        if capacity == 0.0 {
+            return SizeEstimate {
+                size: 0.0,
+                margin: 0.0,
+            };
+        }
+        SizeEstimate {
+            size: a.size + b.size - a.size * b.size / capacity,
+            margin: a.margin + b.margin,
+        }
+    }
+
+    #[inline]
+    fn iter_ones_in(&self, range: impl RangeBounds<T>) -> Self::Ones<'_> {
+        let range = clamp(&range, self.capacity());
+        Merged::new(
+            self.0.iter_ones_in(range.clone()),
+            self.1.iter_ones_in(range),
+        )
+    }
+
+    fn iter_zeros_in(&self, range: impl RangeBounds<T>) -> Self::Zeros<'_> {
+        let range = clamp(&range, self.capacity());
+        // past the capacity of `a`, only the values of `b` are left
+        let a_capacity = self.0.capacity().to_u128().max(range.start.to_u128());
+        let b_range = T::from_u128(a_capacity.min(range.end.to_u128()))..range.end;
+        Filtered::new(self.0.iter_zeros_in(range), self.1, false)
+            .chain(self.1.iter_zeros_in(b_range))
+    }
+}
+
+impl<'a, T, A, B> BitSet<T> for IntersectionView<'a, A, B>
+where
+    T: Word,
+    A: BitSet<T> + ?Sized,
+    B: BitSet<T> + ?Sized,
+{
+    type Ones<'b>
+        = Filtered<'a, A::Ones<'a>, B>
+    where
+        Self: 'b;
+    type Zeros<'b>
+        = Merged<A::Zeros<'a>, B::Zeros<'a>>
+    where
+        Self: 'b;
+
+    #[inline]
+    fn capacity(&self) -> T {
+        let capacity = self.0.capacity().to_u128().min(self.1.capacity().to_u128());
+        T::from_u128(capacity)
+    }
+
+    #[inline]
+    fn has(&self, index: T) -> bool {
+        self.0.has(index) && self.1.has(index)
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.iter_ones().next().is_none()
+    }
+
+    #[inline]
+    fn size(&self) -> T {
+        T::saturating_from_u128(self.iter_ones().count() as u128)
+    }
+
+    fn estimate_load_factor(&self, samples: usize) -> f64 {
+        if self.capacity() == T::ZERO {
+            return 0.0;
+        }
+        self.0.estimate_load_factor(samples) * self.1.estimate_load_factor(samples)
+    }
+
+    fn estimate_size(&self, confidence: f64) -> SizeEstimate {
+        let capacity = self.capacity().to_u128() as f64;
+        if capacity == 0.0 {
+            return SizeEstimate {
+                size: 0.0,
+                margin: 0.0,
+            };
+        }
+        let (a, b) = (
+            self.0.estimate_size(confidence),
+            self.1.estimate_size(confidence),
+        );
+        let a_capacity = self.0.capacity().to_u128() as f64;
+        let b_capacity = self.1.capacity().to_u128() as f64;
+        // the densities of both sets, and their margins
+        let (a_load, b_load) = (a.size / a_capacity, b.size / b_capacity);
+        let (a_margin, b_margin) = (a.margin / a_capacity, b.margin / b_capacity);
+        SizeEstimate {
+            size: a_load * b_load * capacity,
+            margin: (a_margin * b_load + b_margin * a_load) * capacity,
+        }
+    }
+
+    #[inline]
+    fn iter_ones_in(&self, range: impl RangeBounds<T>) -> Self::Ones<'_> {
+        let range = clamp(&range, self.capacity());
+        Filtered::new(self.0.iter_ones_in(range), self.1, true)
+    }
+
+    #[inline]
+    fn iter_zeros_in(&self, range: impl RangeBounds<T>) -> Self::Zeros<'_> {
+        let range = clamp(&range, self.capacity());
+        Merged::new(
+            self.0.iter_zeros_in(range.clone()),
+            self.1.iter_zeros_in(range),
+        )
+    }
+}
+
+// clamps `range` to `capacity`
+fn clamp<T: Word>(range: &impl RangeBounds<T>, capacity: T) -> Range<T> {
+    let (start, end) = util::bit_range(range, capacity.to_u128(), T::to_u128);
+    // reversed ranges are empty
+    T::from_u128(start.min(end))..T::from_u128(end)
+}
@@ -0,0 +1,238 @@
+use crate::*;
+
+/// An unsigned integer used as the storage unit of a bit set.
+///
+/// This trait is sealed: it can't be implemented outside of this crate.
+pub trait Word: Copy + Eq + sealed::Sealed {
+    #[doc(hidden)]
+    const BITS: u32;
+    #[doc(hidden)]
+    const ZERO: Self;
+    #[doc(hidden)]
+    const MAX: Self;
+
+    #[doc(hidden)]
+    fn trailing_zeros(self) -> u32;
+    #[doc(hidden)]
+    fn leading_zeros(self) -> u32;
+    #[doc(hidden)]
+    fn and(self, other: Self) -> Self;
+    #[doc(hidden)]
+    fn or(self, other: Self) -> Self;
+    #[doc(hidden)]
+    fn xor(self, other: Self) -> Self;
+    #[doc(hidden)]
+    fn not(self) -> Self;
+    #[doc(hidden)]
+    fn count_ones(self) -> u32;
+    /// Returns a word with only the given bit set.
+    #[doc(hidden)]
+    fn bit(bit: u32) -> Self;
+    #[doc(hidden)]
+    fn without_bit(self, bit: u32) -> Self;
+    /// Returns a word with the bits in `start..end` set, where `start < end <= BITS`.
+    #[doc(hidden)]
+    fn mask(start: u32, end: u32) -> Self;
+    /// Returns the value of the given bit of the given slot.
+    #[doc(hidden)]
+    fn from_index(slot_idx: usize, bit: u32) -> Self;
+    /// Splits a value into the index of its slot and its bit, the inverse of `from_index`.
+    ///
+    /// Returns `None` if the index of the slot doesn't fit in a `usize`.
+    #[doc(hidden)]
+    fn split_index(self) -> Option<(usize, u32)>;
+    #[doc(hidden)]
+    fn to_u128(self) -> u128;
+    /// Truncates `value` to the width of the word.
+    #[doc(hidden)]
+    fn from_u128(value: u128) -> Self;
+    /// Converts `value` to the word, saturating at [`MAX`](Word::MAX).
+    #[doc(hidden)]
+    fn saturating_from_u128(value: u128) -> Self;
+}
+
+/// A slot of a bit set, that holds a [`Word`], possibly behind an atomic.
+///
+/// This trait is sealed: it can't be implemented outside of this crate.
+pub trait Slot: sealed::Sealed {
+    /// The word stored in this slot.
+    type Word: Word;
+
+    #[doc(hidden)]
+    fn load(&self) -> Self::Word;
+}
+
+/// A [`Slot`] that can be updated through a shared reference, i.e. an atomic.
+///
+/// This trait is sealed: it can't be implemented outside of this crate.
+pub trait AtomicSlot: Slot {
+    #[doc(hidden)]
+    fn store(&self, word: Self::Word, order: Ordering);
+    #[doc(hidden)]
+    fn fetch_or(&self, word: Self::Word, order: Ordering) -> Self::Word;
+    #[doc(hidden)]
+    fn fetch_and(&self, word: Self::Word, order: Ordering) -> Self::Word;
+    /// Same as `fetch_update` of the atomic types, with `AcqRel` and `Acquire` orderings.
+    #[doc(hidden)]
+    fn fetch_update(
+        &self,
+        f: impl FnMut(Self::Word) -> Option<Self::Word>,
+    ) -> Result<Self::Word, Self::Word>;
+
+    /// Atomically replaces the word with zero, and returns the previous word.
+    #[doc(hidden)]
+    #[inline]
+    fn take(&self) -> Self::Word {
+        self.fetch_and(Self::Word::ZERO, Ordering::AcqRel)
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_word {
+    [$($ty:tt),*] => {$(
+        impl sealed::Sealed for $ty {}
+
+        impl Word for $ty {
+            const BITS: u32 = $ty::BITS;
+            const ZERO: Self = 0;
+            const MAX: Self = $ty::MAX;
+
+            #[inline]
+            fn trailing_zeros(self) -> u32 {
+                self.trailing_zeros()
+            }
+
+            #[inline]
+            fn leading_zeros(self) -> u32 {
+                self.leading_zeros()
+            }
+
+            #[inline]
+            fn and(self, other: Self) -> Self {
+                self & other
+            }
+
+            #[inline]
+            fn or(self, other: Self) -> Self {
+                self | other
+            }
+
+            #[inline]
+            fn xor(self, other: Self) -> Self {
+                self ^ other
+            }
+
+            #[inline]
+            fn not(self) -> Self {
+                !self
+            }
+
+            #[inline]
+            fn count_ones(self) -> u32 {
+                self.count_ones()
+            }
+
+            #[inline]
+            fn bit(bit: u32) -> Self {
+                1 << bit
+            }
+
+            #[inline]
+            fn without_bit(self, bit: u32) -> Self {
+                self & !(1 << bit)
+            }
+
+            #[inline]
+            fn mask(start: u32, end: u32) -> Self {
+                $ty::MAX >> ($ty::BITS - (end - start)) << start
+            }
+
+            #[inline]
+            fn from_index(slot_idx: usize, bit: u32) -> Self {
+                slot_idx as $ty * $ty::BITS as $ty + bit as $ty
+            }
+
+            #[inline]
+            fn split_index(self) -> Option<(usize, u32)> {
+                let slot_idx = usize::try_from(self / $ty::BITS as $ty).ok()?;
+                Some((slot_idx, (self % $ty::BITS as $ty) as u32))
+            }
+
+            #[inline]
+            fn to_u128(self) -> u128 {
+                self as u128
+            }
+
+            #[inline]
+            fn from_u128(value: u128) -> Self {
+                value as $ty
+            }
+
+            #[inline]
+            fn saturating_from_u128(value: u128) -> Self {
+                $ty::try_from(value).unwrap_or($ty::MAX)
+            }
+        }
+
+        impl Slot for $ty {
+            type Word = $ty;
+
+            #[inline]
+            fn load(&self) -> $ty {
+                *self
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_atomic_slot {
+    [$($ty:tt for $target: ty)*] => {$(
+        impl sealed::Sealed for $target {}
+
+        impl Slot for $target {
+            type Word = $ty;
+
+            #[inline]
+            fn load(&self) -> $ty {
+                <$target>::load(self, Ordering::Acquire)
+            }
+        }
+
+        impl AtomicSlot for $target {
+            #[inline]
+            fn store(&self, word: $ty, order: Ordering) {
+                <$target>::store(self, word, order)
+            }
+
+            #[inline]
+            fn fetch_or(&self, word: $ty, order: Ordering) -> $ty {
+                <$target>::fetch_or(self, word, order)
+            }
+
+            #[inline]
+            fn fetch_and(&self, word: $ty, order: Ordering) -> $ty {
+                <$target>::fetch_and(self, word, order)
+            }
+
+            #[inline]
+            fn fetch_update(&self, f: impl FnMut($ty) -> Option<$ty>) -> Result<$ty, $ty> {
+                <$target>::fetch_update(self, Ordering::AcqRel, Ordering::Acquire, f)
+            }
+        }
+    )*};
+}
+
+impl_word! {
+    u8, u16, u32, u64, usize, u128
+}
+
+impl_atomic_slot! {
+    u8 for AtomicU8
+    u16 for AtomicU16
+    u32 for AtomicU32
+    u64 for AtomicU64
+    usize for AtomicUsize
+}
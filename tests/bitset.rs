@@ -1,7 +1,11 @@
-use index_set::{AtomicBitSet, BitSet, SharedBitSet, slot_count};
+use index_set::{
+    AtomicBitSet, BitSet, Complement, Expr, IntersectionView, MaxOccupancy, SharedBitSet,
+    UnionView, slot_count,
+};
 use std::sync::atomic::AtomicU32;
 
 #[test]
+#[allow(clippy::bool_assert_comparison, clippy::redundant_pattern_matching)]
 fn test_id_set() {
     let bitset: AtomicBitSet<{ slot_count::from_bits(128) }> = AtomicBitSet::new();
 
@@ -40,3 +44,610 @@ fn test_prev_value() {
 
     assert!(bitset.insert(65).is_none());
 }
+
+#[test]
+fn test_cyclic_wrap_around() {
+    let bitset: AtomicBitSet<{ slot_count::from_bits(128) }> = AtomicBitSet::new();
+
+    for expected in 0..128 {
+        assert_eq!(bitset.set_next_free_bit_cyclic(), Some(expected));
+    }
+    assert_eq!(bitset.set_next_free_bit_cyclic(), None);
+
+    bitset.remove(5);
+    bitset.remove(100);
+    assert_eq!(bitset.set_next_free_bit_cyclic(), Some(5));
+    assert_eq!(bitset.set_next_free_bit_cyclic(), Some(100));
+}
+
+#[test]
+fn test_fair_pool_handoff_order() {
+    use index_set::FairPool;
+    use std::sync::Arc;
+
+    let pool: Arc<FairPool<1>> = Arc::new(FairPool::new(2));
+    while pool.try_acquire().is_some() {}
+
+    let mut waiters = Vec::new();
+    for expected_waiters in 1..=2 {
+        let pool_ref = pool.clone();
+        waiters.push(std::thread::spawn(move || pool_ref.acquire()));
+        while pool.waiters() != expected_waiters {
+            std::thread::yield_now();
+        }
+    }
+    // the queue is full
+    assert_eq!(pool.acquire(), None);
+
+    pool.release(7);
+    pool.release(9);
+    let ids: Vec<_> = waiters.into_iter().map(|w| w.join().unwrap()).collect();
+    assert_eq!(ids, [Some(7), Some(9)]);
+
+    // no waiters left, so the identifier is freed
+    pool.release(7);
+    assert_eq!(pool.try_acquire(), Some(7));
+}
+
+#[test]
+fn test_iter_ones_in_chunks() {
+    use index_set::ChunkedBitSet;
+
+    let bits = usize::BITS as usize;
+    let a: AtomicBitSet<1> = AtomicBitSet::new();
+    let b: AtomicBitSet<2> = AtomicBitSet::new();
+    let set = ChunkedBitSet::new([&a[..], &b[..]]);
+
+    let values = [0, bits - 1, bits, 2 * bits + 5, 3 * bits - 1];
+    for value in values {
+        set.insert(value);
+    }
+    assert!(set.iter_ones_in(..).eq(values));
+    assert!(
+        set.iter_ones_in(1..=2 * bits + 5)
+            .eq(values[1..4].iter().copied())
+    );
+    assert!(set.iter_ones_in(bits + 1..2 * bits + 5).eq([]));
+
+    let zeros = (0..set.capacity()).filter(|&index| !set.has(index));
+    assert!(set.iter_zeros().eq(zeros));
+    assert!(set.iter_zeros_in(bits - 1..=bits).eq([]));
+}
+
+#[test]
+fn test_import_shard_exports() {
+    use index_set::{ChunkedBitSet, MergePolicy};
+
+    let bits = usize::BITS as usize;
+    let shard_a: AtomicBitSet<2> = AtomicBitSet::new();
+    let shard_b: AtomicBitSet<2> = AtomicBitSet::new();
+    shard_a.insert(3);
+    shard_b.insert(bits + 7);
+
+    // the global view spans two banks, so the second shard straddles them
+    let low: AtomicBitSet<3> = AtomicBitSet::new();
+    let high: AtomicBitSet<1> = AtomicBitSet::new();
+    let global = ChunkedBitSet::new([&low[..], &high[..]]);
+
+    for (shard_offset, shard) in [(0, &shard_a), (2 * bits, &shard_b)] {
+        for (offset, words) in shard.freeze().export_chunks(bits) {
+            global
+                .import_at(shard_offset + offset, words, MergePolicy::Or)
+                .unwrap();
+        }
+    }
+    assert!(global.iter_ones_in(..).eq([3, 3 * bits + 7]));
+    assert!(high.has(7));
+
+    assert_eq!(
+        global.import_at(3 * bits, &[0, 0], MergePolicy::Overwrite),
+        None
+    );
+    assert!(high.has(7));
+}
+
+#[test]
+fn test_fingerprint_maintained_across_threads() {
+    use index_set::{FingerprintedBitSet, MergePolicy};
+
+    let replica: AtomicBitSet<8> = AtomicBitSet::new();
+    let bitset = FingerprintedBitSet::new(&replica[..]);
+    std::thread::scope(|s| {
+        for t in 0..4 {
+            let bitset = &bitset;
+            s.spawn(move || {
+                for i in 0..1000 {
+                    let index = (i * 7 + t * 13) % 512;
+                    if i % 3 == 0 {
+                        bitset.remove(index);
+                    } else {
+                        bitset.insert(index);
+                    }
+                }
+            });
+        }
+    });
+    assert_eq!(bitset.fingerprint(), replica.fingerprint());
+
+    bitset.import_at(64, &[usize::MAX, 0b1010], MergePolicy::Overwrite);
+    bitset.clear_where(|index| index % 5 == 0);
+    assert_eq!(bitset.fingerprint(), replica.fingerprint());
+
+    bitset.clear();
+    assert_eq!(bitset.fingerprint(), 0);
+}
+
+#[test]
+fn test_double_ended_iteration() {
+    use index_set::ChunkedBitSet;
+
+    // takes from both ends in turn, and checks against the expected values
+    fn check(mut iter: impl DoubleEndedIterator<Item = usize>, expected: &[usize]) {
+        let (mut front, mut back) = (0, expected.len());
+        for step in 0.. {
+            let value = if step % 3 == 0 {
+                iter.next_back()
+            } else {
+                iter.next()
+            };
+            if front == back {
+                assert_eq!(value, None);
+                assert_eq!(iter.next(), None);
+                return;
+            }
+            if step % 3 == 0 {
+                back -= 1;
+                assert_eq!(value, Some(expected[back]));
+            } else {
+                assert_eq!(value, Some(expected[front]));
+                front += 1;
+            }
+        }
+    }
+
+    let bits = usize::BITS as usize;
+    let a: AtomicBitSet<1> = AtomicBitSet::new();
+    let b: AtomicBitSet<2> = AtomicBitSet::new();
+    let set = ChunkedBitSet::new([&a[..], &b[..]]);
+    let values = [0, 5, bits - 1, bits, bits + 3, 2 * bits + 5, 3 * bits - 1];
+    for value in values {
+        set.insert(value);
+    }
+    check(set.iter_ones(), &values);
+    check(set.iter_ones_in(5..=2 * bits + 5), &values[1..6]);
+    check(b.iter_ones().map(|value| value + bits), &values[3..]);
+
+    let zeros: Vec<_> = (bits - 8..bits + 8)
+        .filter(|&index| !set.has(index))
+        .collect();
+    check(set.iter_zeros_in(bits - 8..bits + 8), &zeros);
+}
+
+#[test]
+fn test_concurrent_drain_yields_each_value_once() {
+    let pending: AtomicBitSet<16> = AtomicBitSet::new();
+    let capacity = pending.capacity();
+    for index in (0..capacity).step_by(3) {
+        pending.insert(index);
+    }
+
+    let mut drained: Vec<usize> = std::thread::scope(|s| {
+        let consumers: Vec<_> = (0..4)
+            .map(|_| s.spawn(|| pending.drain().collect::<Vec<_>>()))
+            .collect();
+        consumers
+            .into_iter()
+            .flat_map(|consumer| consumer.join().unwrap())
+            .collect()
+    });
+    drained.sort_unstable();
+    assert!(drained.iter().copied().eq((0..capacity).step_by(3)));
+    assert!(BitSet::is_empty(&pending[..]));
+}
+
+#[test]
+fn test_every_width_gets_the_same_apis() {
+    use index_set::BitSetMut;
+    use std::sync::atomic::{AtomicU8, AtomicU16};
+
+    let mut plain = [0_u8; 4];
+    plain.insert(9).unwrap();
+    plain.insert(31).unwrap();
+    assert!(plain.iter_ones().eq([9, 31]));
+    assert_eq!(plain.first_zero(), Some(0));
+    assert_eq!(plain.drain().count(), 2);
+
+    let shared: [AtomicU16; 2] = Default::default();
+    assert_eq!(shared.insert(17), Some(false));
+    assert_eq!(shared.insert(32), None);
+    assert_eq!(shared.take_highest(), Some(17));
+    assert!(BitSet::is_empty(&shared[..]));
+
+    let bytes: [AtomicU8; 1] = Default::default();
+    assert_eq!(bytes.insert_iter(0..8), Ok(8));
+    assert_eq!(bytes.first_zero(), None);
+}
+
+#[test]
+fn test_bounded_allocation_never_duplicates() {
+    let bitset: AtomicBitSet<4> = AtomicBitSet::with_shuffle_seed(7);
+
+    let mut allocated: Vec<usize> = std::thread::scope(|s| {
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                s.spawn(|| {
+                    (0..bitset.capacity())
+                        .filter_map(|_| bitset.try_set_lowest_free_bit(1))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+    let count = allocated.len();
+    allocated.sort_unstable();
+    allocated.dedup();
+    assert_eq!(allocated.len(), count);
+    assert_eq!(bitset.size(), count);
+}
+
+#[test]
+fn test_complement_mirrors_the_set() {
+    let bitset: &[u64] = &[0b1011, u64::MAX];
+    let complement = Complement::within(bitset, 100);
+
+    assert_eq!(complement.capacity(), 100);
+    assert_eq!(complement.size(), 64 - 3);
+    assert!(
+        complement
+            .iter_ones()
+            .eq((0..64).filter(|&i| !bitset.has(i)))
+    );
+    assert!(complement.iter_zeros().eq(bitset.iter_ones_in(..100)));
+    assert_eq!(complement.count_in_range(..8), 5);
+    assert_eq!(complement.remaining_in(60..), 36);
+    assert!(
+        complement
+            .iter_ones()
+            .rev()
+            .eq(bitset.iter_zeros_in(..100).rev())
+    );
+}
+
+#[test]
+fn test_views_match_the_combined_lookups() {
+    let a: &[u32] = &[0b1011_0110, 0x8000_0001, 7];
+    let b: &[u32] = &[0b0110_1100, 0x0001_0001];
+    let union = UnionView(a, b);
+    let intersection = IntersectionView(a, b);
+
+    let (ones, zeros): (Vec<u32>, Vec<u32>) = (0..96).partition(|&i| a.has(i) || b.has(i));
+    assert!(union.iter_ones().eq(ones));
+    assert!(union.iter_zeros().eq(zeros));
+
+    let (ones, zeros): (Vec<u32>, Vec<u32>) = (0..64).partition(|&i| a.has(i) && b.has(i));
+    assert!(intersection.iter_ones().eq(ones));
+    assert!(intersection.iter_zeros().rev().eq(zeros.into_iter().rev()));
+
+    let ones: Vec<u32> = union.iter_ones_in(5..70).collect();
+    assert!(union.iter_ones_in(5..70).rev().eq(ones.into_iter().rev()));
+    let mut ones = union.iter_ones();
+    assert_eq!(
+        (ones.next(), ones.next_back(), ones.next()),
+        (Some(1), Some(66), Some(2))
+    );
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn test_sampling_reaches_every_value() {
+    use rand::{SeedableRng, rngs::SmallRng};
+
+    let mut rng = SmallRng::seed_from_u64(1);
+    let bitset: &[u32] = &[0x8000_0001, 0, 0b110];
+    let mut counts = [0; 96];
+    for _ in 0..4000 {
+        counts[bitset.sample_one(&mut rng).unwrap() as usize] += 1;
+    }
+    for (value, count) in counts.into_iter().enumerate() {
+        match bitset.has(value as u32) {
+            true => assert!((800..1200).contains(&count), "{value}: {count}"),
+            false => assert_eq!(count, 0),
+        }
+    }
+    assert_eq!(bitset.sample(&mut rng, 4), [0, 31, 65, 66]);
+}
+
+#[test]
+fn test_shuffled_allocation_covers_the_set_once() {
+    let bitset: AtomicBitSet<4> = AtomicBitSet::new();
+
+    let mut allocated: Vec<usize> = std::thread::scope(|s| {
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                s.spawn(|| {
+                    std::iter::from_fn(|| bitset.set_next_free_bit_shuffled()).collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+    assert_eq!(bitset.free_count(), 0);
+    allocated.sort_unstable();
+    assert!(allocated.into_iter().eq(0..bitset.capacity()));
+
+    let fresh: AtomicBitSet<4> = AtomicBitSet::with_shuffle_seed(7);
+    let first: Vec<_> = (0..8)
+        .filter_map(|_| fresh.set_next_free_bit_shuffled())
+        .collect();
+    assert!(first.windows(2).any(|pair| pair[1] != pair[0] + 1));
+}
+
+#[test]
+fn test_expr_matches_the_per_value_lookups() {
+    let a: &[u32] = &[0b1011_0110, 0x8000_0001, 7];
+    let b: &[u32] = &[0b0110_1100, 0x0001_0001];
+    let c: &[u32] = &[0b0000_1111];
+
+    let expr = Expr::set(a).xor(b).or(!Expr::set(c).and(b)).and_not(a);
+    let mut out = [0_u32; 4];
+    expr.evaluate_into(&mut out);
+    for value in 0..128 {
+        let (a, b, c) = (a.has(value), b.has(value), c.has(value));
+        let expected = ((a ^ b) | !(c & b)) & !a;
+        assert_eq!(out.has(value), expected, "{value}");
+    }
+}
+
+#[test]
+fn test_max_occupancy_holds_under_contention() {
+    let bitset: AtomicBitSet<4> = AtomicBitSet::new();
+    let policy = MaxOccupancy::new(10);
+
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                for _ in 0..100 {
+                    if let Some(index) = bitset.set_next_free_bit_with(&policy) {
+                        assert!(bitset.release_with(index, &policy));
+                    }
+                }
+                for _ in 0..10 {
+                    bitset.set_next_free_bit_with(&policy);
+                }
+            });
+        }
+    });
+    assert_eq!(bitset.size(), 10);
+    assert_eq!(policy.occupancy(), 10);
+
+    // the allocations that find the set full give their reservation back
+    let full: AtomicBitSet<1> = AtomicBitSet::new();
+    let policy = MaxOccupancy::new(1000);
+    while full.set_next_free_bit_with(&policy).is_some() {}
+    assert_eq!(policy.occupancy(), usize::BITS as usize);
+
+    // releasing a bit that was never admitted doesn't wrap around
+    let policy = MaxOccupancy::new(1);
+    full.release_with(0, &policy);
+    assert_eq!(policy.occupancy(), 0);
+}
+
+#[test]
+fn test_counts_saturate_for_narrow_words() {
+    let mut bitset = [0_u8; 32];
+    assert_eq!(bitset.capacity(), u8::MAX);
+    assert_eq!(bitset.free_count(), u8::MAX);
+    assert!(!bitset.has_strict(5));
+
+    bitset.fill(u8::MAX);
+    assert_eq!(bitset.size(), u8::MAX);
+    assert_eq!(bitset.count_in_range(..), u8::MAX);
+    assert_eq!(
+        index_set::util::hamming_distance(&bitset, &[0_u8; 32]),
+        u8::MAX
+    );
+}
+
+#[test]
+fn test_provided_methods_of_a_minimal_bitset() {
+    // a set of the even values below 16, implementing only the required methods
+    struct Evens;
+
+    impl BitSet<u32> for Evens {
+        type Ones<'a> = std::iter::StepBy<std::ops::Range<u32>>;
+        type Zeros<'a> = std::iter::StepBy<std::ops::Range<u32>>;
+
+        fn capacity(&self) -> u32 {
+            16
+        }
+
+        fn has(&self, index: u32) -> bool {
+            index < 16 && index.is_multiple_of(2)
+        }
+
+        fn is_empty(&self) -> bool {
+            false
+        }
+
+        fn size(&self) -> u32 {
+            8
+        }
+
+        fn iter_ones_in(&self, range: impl std::ops::RangeBounds<u32>) -> Self::Ones<'_> {
+            let (start, end) = bounds(range);
+            (start.next_multiple_of(2)..end).step_by(2)
+        }
+
+        fn iter_zeros_in(&self, range: impl std::ops::RangeBounds<u32>) -> Self::Zeros<'_> {
+            let (start, end) = bounds(range);
+            (start | 1..end).step_by(2)
+        }
+    }
+
+    fn bounds(range: impl std::ops::RangeBounds<u32>) -> (u32, u32) {
+        use std::ops::Bound;
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => 16,
+        };
+        (start, end.min(16))
+    }
+
+    assert_eq!(Evens.first_zero(), Some(1));
+    assert_eq!(Evens.free_count(), 8);
+    assert_eq!(Evens.count_in_range(4..10), 3);
+    assert_eq!(Evens.remaining_in(4..10), 3);
+    assert_eq!(Evens.load_factor(), 0.5);
+    assert_eq!(Evens.estimate_size(0.95).size, 8.0);
+    assert!(Evens.has_all(&[0, 2, 14]) && !Evens.has_any(&[1, 16]));
+
+    let mut values = Vec::new();
+    Evens.for_each_one(|value| values.push(value));
+    assert_eq!(values, [0, 2, 4, 6, 8, 10, 12, 14]);
+}
+
+#[test]
+fn test_subset_and_intersection_across_backends() {
+    use index_set::{BitSetMut, ReferenceSet};
+    use std::sync::atomic::AtomicUsize;
+
+    let small = [0b0100_usize, 0, 1];
+    let large = [0b0110_usize, 1, 1, 0];
+    let atomic: Vec<AtomicUsize> = large.iter().map(|&word| AtomicUsize::new(word)).collect();
+    let mut reference = ReferenceSet::new(4);
+    for value in large.iter_ones() {
+        reference.insert(value).unwrap();
+    }
+
+    assert!(small.is_subset(&large[..]) && small.is_subset(&atomic[..]));
+    assert!(small.is_subset(&reference));
+    assert!(!large.is_subset(&small[..]) && !atomic.is_subset(&small[..]));
+    // the missing slots of the other set are empty
+    assert!(!small.is_subset(&large[..1]));
+
+    assert!(small.intersects(&atomic[..]) && small.intersects(&reference));
+    assert!(!small.intersects(&[0b1011_usize][..]));
+    assert!(small.is_disjoint(&[0b1011_usize, usize::MAX][..]));
+}
+
+#[test]
+fn test_namespace_quota_holds_under_contention() {
+    use index_set::NamespacedIdSet;
+
+    let ids: NamespacedIdSet<4, 2> = NamespacedIdSet::new();
+    let (tenant, other) = (ids.namespace(0).unwrap(), ids.namespace(1).unwrap());
+    tenant.set_quota(Some(10));
+
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                for _ in 0..100 {
+                    if let Some(index) = tenant.set_next_free_bit() {
+                        assert_eq!(tenant.remove(index), Some(true));
+                    }
+                }
+                for _ in 0..10 {
+                    tenant.set_next_free_bit();
+                }
+            });
+        }
+    });
+    assert_eq!(tenant.size(), 10);
+    assert_eq!(tenant.quota(), Some(10));
+    // the other namespaces are not limited
+    while other.set_next_free_bit().is_some() {}
+    assert_eq!(other.size(), other.capacity());
+
+    // clearing gives the whole quota back
+    tenant.clear();
+    assert!((0..10).all(|_| tenant.set_next_free_bit().is_some()));
+    assert_eq!(tenant.set_next_free_bit(), None);
+
+    tenant.set_quota(None);
+    assert!(tenant.set_next_free_bit().is_some());
+}
+
+#[test]
+fn test_narrow_slots_past_the_word_range_hold_no_values() {
+    use index_set::{BitSetMut, util};
+    use std::sync::atomic::AtomicU8;
+
+    // the values of the slots past the 32nd one don't fit in a `u8`
+    let mut bitset = [0_u8; 40];
+    assert_eq!(bitset.iter_zeros().count(), 256);
+    assert_eq!(bitset.iter_zeros().next_back(), Some(u8::MAX));
+
+    bitset.fill(u8::MAX);
+    assert!(bitset.iter_ones().eq(0..=u8::MAX));
+    let mut count = 0;
+    bitset.for_each_one(|_| count += 1);
+    assert_eq!(count, 256);
+    assert_eq!(bitset.first_zero(), None);
+    assert_eq!(util::diff(&bitset, &[0; 40]).count(), 256);
+
+    assert_eq!(bitset.clear_where(|value| value >= 128), 128);
+    assert_eq!(bitset.drain().count(), 128);
+    assert_eq!(bitset, [0; 40]);
+
+    let shared: Vec<AtomicU8> = (0..40).map(|_| AtomicU8::new(u8::MAX)).collect();
+    assert_eq!(shared.take_highest(), Some(u8::MAX));
+    assert_eq!(shared.take_lowest(), Some(0));
+    assert_eq!(SharedBitSet::drain(&shared[..]).count(), 254);
+}
+
+#[test]
+fn test_swap_contents_loses_no_concurrent_remove() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread::yield_now;
+
+    let (front, back): (AtomicBitSet<1>, AtomicBitSet<1>) =
+        (AtomicBitSet::new(), AtomicBitSet::new());
+    // spinning keeps both threads close, so that the swap often overlaps the modifications
+    let (started, finished) = (AtomicUsize::new(0), AtomicUsize::new(0));
+    let mut outcomes: Vec<(Vec<_>, Vec<_>)> = Vec::new();
+
+    std::thread::scope(|s| {
+        s.spawn(|| {
+            for round in 1..=10_000 {
+                while started.load(Ordering::Acquire) != round {
+                    yield_now();
+                }
+                (0..round % 16).for_each(|_| std::hint::spin_loop());
+                front.insert(1);
+                front.remove(0);
+                finished.store(round, Ordering::Release);
+            }
+        });
+        for round in 1..=10_000 {
+            front.clear();
+            back.clear();
+            back.insert(0);
+            started.store(round, Ordering::Release);
+            front.swap_contents(&back);
+            while finished.load(Ordering::Acquire) != round {
+                yield_now();
+            }
+            outcomes.push((front.iter_ones().collect(), back.iter_ones().collect()));
+        }
+    });
+    // the swap happens before, between, or after the insertion and the removal
+    for outcome in outcomes {
+        assert!(
+            [(vec![0], vec![1]), (vec![], vec![1]), (vec![1], vec![])].contains(&outcome),
+            "{outcome:?}"
+        );
+    }
+}
@@ -1,6 +1,5 @@
 use index_set::{BitSet, BitSetMut};
 
-
 #[test]
 fn test_bitvec() {
     let mut bitset: Vec<u32> = Vec::new();
@@ -10,4 +9,386 @@ fn test_bitvec() {
 
     assert_eq!(BitSetMut::remove(&mut bitset, 42), Some(true));
     assert_eq!(BitSetMut::remove(&mut bitset, 0), Some(false));
-}
\ No newline at end of file
+}
+#[test]
+fn test_remaining_in() {
+    let mut bitset = [0_u16; 4];
+    for index in [0, 15, 16, 40, 63] {
+        bitset.insert(index).unwrap();
+    }
+    assert_eq!(bitset.remaining_in(0..64), 59);
+    assert_eq!(bitset.remaining_in(15..17), 0);
+    assert_eq!(bitset.remaining_in(10..50), 37);
+
+    let mut bitset = [0_u128; 2];
+    bitset.insert(127).unwrap();
+    bitset.insert(128).unwrap();
+    assert_eq!(bitset.remaining_in(100..200), 98);
+    assert_eq!(bitset.free_count(), 254);
+}
+
+#[test]
+fn test_bit_io_round_trip() {
+    use index_set::{BitReader, BitWriter};
+
+    let fields = [
+        (0b1, 1),
+        (0x3ff, 10),
+        (0xdead_beef, 32),
+        (0, 5),
+        (u64::MAX, 64),
+    ];
+    let mut slots = [0_u16; 8];
+
+    let mut writer = BitWriter::new(&mut slots);
+    for (value, bits) in fields {
+        writer.write(value, bits).unwrap();
+    }
+    assert_eq!(writer.remaining(), 128 - 112);
+    assert!(writer.write(0, 17).is_none());
+
+    let mut reader = BitReader::new(&slots);
+    for (value, bits) in fields {
+        assert_eq!(reader.read(bits), Some(value));
+    }
+}
+
+#[test]
+fn test_range_clamping() {
+    use std::ops::Bound;
+
+    let mut bitset = [0_u64; 2];
+    for index in [0, 63, 64, 127] {
+        bitset.insert(index).unwrap();
+    }
+    assert_eq!(bitset.count_in_range(..), 4);
+    assert_eq!(bitset.count_in_range(0..), 4);
+    assert_eq!(bitset.count_in_range(..=63), 2);
+    assert_eq!(bitset.count_in_range(..63), 1);
+    assert_eq!(bitset.count_in_range(63..=64), 2);
+    assert_eq!(
+        bitset.count_in_range((Bound::Excluded(63), Bound::Included(127))),
+        2
+    );
+    // clamped to the capacity
+    assert_eq!(bitset.count_in_range(100..u64::MAX), 1);
+    assert_eq!(bitset.remaining_in(200..), 0);
+    // reversed ranges are empty
+    assert_eq!(
+        bitset.count_in_range((Bound::Excluded(64), Bound::Excluded(64))),
+        0
+    );
+}
+
+#[test]
+fn test_hybrid_matches_dense() {
+    use index_set::HybridBitSet;
+
+    let mut hybrid = HybridBitSet::new();
+    let mut dense: Vec<usize> = Vec::new();
+    let mut state = 1_u64;
+    for step in 0..20_000 {
+        // xorshift, so values cluster at first and spread out later
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let value = (state % (64 + step as u64 / 4)) as usize;
+        if step % 3 == 0 {
+            assert_eq!(hybrid.remove(value), BitSetMut::remove(&mut dense, value));
+        } else {
+            assert_eq!(hybrid.insert(value), BitSetMut::insert(&mut dense, value));
+        }
+    }
+    assert!(hybrid.is_dense());
+    assert_eq!(hybrid.size(), dense.size());
+    assert_eq!(hybrid.first_zero(), BitSet::first_zero(&dense[..]));
+    assert_eq!(
+        hybrid.count_in_range(100..3000),
+        BitSet::count_in_range(&dense[..], 100..3000)
+    );
+    assert!(
+        hybrid
+            .iter_ones_in(50..)
+            .eq(BitSet::iter_ones_in(&dense[..], 50..))
+    );
+
+    hybrid.clear_where(|value| value % 512 != 0);
+    assert!(!hybrid.is_dense());
+    hybrid.insert(0).unwrap();
+    assert_eq!(hybrid.first_zero(), Some(1));
+}
+
+#[test]
+fn test_arrow_validity_round_trip() {
+    use index_set::arrow;
+
+    let len = 290;
+    let buffer: Vec<u8> = (0..37_u32).map(|i| (i * 37 + 11) as u8).collect();
+    let nulls = arrow::null_count(&buffer, len).unwrap();
+
+    let mut narrow = [0_u16; 19];
+    let mut wide = [0_u128; 3];
+    assert_eq!(arrow::read_validity(&buffer, len, &mut narrow), Some(nulls));
+    assert_eq!(arrow::read_validity(&buffer, len, &mut wide), Some(nulls));
+    assert!(
+        narrow
+            .iter_ones()
+            .map(usize::from)
+            .eq(wide.iter_ones().map(|i| i as usize))
+    );
+    assert_eq!(narrow.size() as usize, len - nulls);
+
+    let mut written = vec![0; buffer.len()];
+    assert_eq!(arrow::write_validity(&wide, len, &mut written), Some(nulls));
+    // only the padding of the last byte differs
+    assert_eq!(written[..36], buffer[..36]);
+    assert_eq!(written[36], buffer[36] & 0b11);
+
+    assert_eq!(arrow::read_validity(&buffer, len, &mut [0_u64; 4]), None);
+}
+
+#[test]
+fn test_iter_zeros_sparse_and_dense() {
+    use index_set::HybridBitSet;
+
+    let mut hybrid = HybridBitSet::new();
+    for value in [1, 2, 5, 130, 4000] {
+        hybrid.insert(value).unwrap();
+    }
+    assert!(!hybrid.is_dense());
+    let zeros: Vec<_> = (0..hybrid.capacity()).filter(|&i| !hybrid.has(i)).collect();
+    assert!(hybrid.iter_zeros().eq(zeros.iter().copied()));
+    assert!(hybrid.iter_zeros_in(1..=6).eq([3, 4, 6]));
+
+    for value in 0..150 {
+        hybrid.insert(value).unwrap();
+    }
+    assert!(hybrid.is_dense());
+    assert!(hybrid.iter_zeros_in(140..300).eq(150..300));
+}
+
+#[test]
+fn test_fixed_bitset_stops_at_len() {
+    use index_set::FixedBitSet;
+
+    let mut bitset = FixedBitSet::new(70);
+    assert_eq!(bitset.iter_zeros().count(), 70);
+    assert_eq!(bitset.remaining_in(..), 70);
+
+    for index in 0..70 {
+        bitset.insert(index).unwrap();
+    }
+    assert!(bitset.is_full());
+    assert_eq!(bitset.first_zero(), None);
+    assert_eq!(bitset.free_count(), 0);
+    assert_eq!(bitset.load_factor(), 1.0);
+
+    bitset.complement();
+    assert!(bitset.is_empty());
+    bitset.complement();
+    assert_eq!(bitset.size(), 70);
+}
+
+#[test]
+fn test_collect_grows_to_the_largest_value() {
+    use index_set::{HybridBitSet, SmallBitSet};
+
+    let values = [5, 1000, 64, 5];
+    let small: SmallBitSet<2> = values.into_iter().collect();
+    assert!(small.spilled());
+    let mut hybrid: HybridBitSet = values.into_iter().collect();
+    hybrid.extend(0..10);
+
+    assert!(small.iter_ones().eq([5, 64, 1000]));
+    assert_eq!(hybrid.size(), 12);
+    assert!(hybrid.has(1000));
+}
+
+#[test]
+fn test_u128_slots_match_u64_slots() {
+    use index_set::util;
+
+    let values = [0, 1, 63, 64, 127, 128, 200, 255];
+    let mut wide = [0_u128; 2];
+    let mut narrow = [0_u64; 4];
+    for value in values {
+        wide.insert(value).unwrap();
+        narrow.insert(value as u64).unwrap();
+    }
+
+    let widen = |values: Vec<u64>| values.into_iter().map(u128::from).collect::<Vec<_>>();
+    assert!(wide.iter_ones().eq(widen(narrow.iter_ones().collect())));
+    assert!(
+        wide.iter_ones()
+            .rev()
+            .eq(widen(narrow.iter_ones().rev().collect()))
+    );
+    assert!(
+        wide.iter_zeros_in(60..)
+            .eq(widen(narrow.iter_zeros_in(60..).collect()))
+    );
+    assert_eq!(wide.first_zero(), Some(2));
+    assert_eq!(wide.next_set_bit_after(128), Some(200));
+    assert_eq!(wide.prev_set_bit_before(128), Some(127));
+    assert_eq!(
+        wide.runs().collect::<Vec<_>>(),
+        [0..2, 63..65, 127..129, 200..201, 255..256]
+    );
+    assert_eq!(wide.fingerprint(), narrow.fingerprint());
+
+    let old = wide;
+    wide.remove(64);
+    assert_eq!(util::diff(&old, &wide).count(), 1);
+    assert_eq!(wide.drain().count(), values.len() - 1);
+}
+
+#[test]
+fn test_slots_match_reference_set() {
+    use index_set::{MergePolicy, ReferenceSet};
+
+    let mut fast = [0_usize; 4];
+    let mut reference = ReferenceSet::new(4);
+    let mut seed = 42_u64;
+    let mut next = || {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (seed >> 33) as usize
+    };
+    for _ in 0..2000 {
+        let value = next() % 300;
+        match next() % 4 {
+            0 | 1 => assert_eq!(fast.insert(value), reference.insert(value)),
+            2 => assert_eq!(fast.remove(value), reference.remove(value)),
+            _ => {
+                let words = [next() | next() << 32];
+                let offset = value / 64 * 64;
+                let policy = [MergePolicy::Or, MergePolicy::And][next() % 2];
+                assert_eq!(
+                    fast.import_at(offset, &words, policy),
+                    reference.import_at(offset, &words, policy)
+                );
+            }
+        }
+        assert_eq!(fast.size(), reference.size());
+        assert_eq!(fast.first_zero(), reference.first_zero());
+        assert_eq!(
+            fast.count_in_range(value..),
+            reference.count_in_range(value..)
+        );
+        assert!(
+            fast.iter_zeros_in(..value)
+                .eq(reference.iter_zeros_in(..value))
+        );
+    }
+    assert!(fast.iter_ones().rev().eq(reference.iter_ones().rev()));
+    assert_eq!(fast.estimate_size(0.95), reference.estimate_size(0.95));
+}
+
+#[test]
+fn test_complement_keeps_the_capacity() {
+    use index_set::{HybridBitSet, ReferenceSet};
+
+    // a `Vec` only reads through its slice
+    let mut bitvec: Vec<u64> = vec![0, u64::MAX];
+    bitvec.as_mut_slice().complement();
+    assert_eq!(bitvec, [u64::MAX, 0]);
+
+    let mut hybrid = HybridBitSet::new();
+    hybrid.insert(100).unwrap();
+    let capacity = hybrid.capacity();
+    hybrid.complement();
+    assert_eq!(hybrid.capacity(), capacity);
+    assert_eq!(hybrid.size(), capacity - 1);
+    assert!(!hybrid.has(100));
+
+    let mut dense: HybridBitSet = (0..1000).collect();
+    let capacity = dense.capacity();
+    dense.complement();
+    assert!(dense.iter_ones().eq(1000..capacity));
+
+    let mut reference = ReferenceSet::new(1);
+    reference.insert(0).unwrap();
+    reference.complement();
+    assert!(reference.iter_ones().eq(1..64));
+
+    // boxed sets flip in place too
+    let mut boxed = Box::new(dense);
+    boxed.complement();
+    assert!(boxed.iter_ones().eq(0..1000));
+}
+
+#[test]
+fn test_import_of_no_words_is_a_no_op() {
+    use index_set::{MergePolicy, ReferenceSet};
+
+    let mut empty: [u64; 0] = [];
+    assert_eq!(empty.import_at(0, &[], MergePolicy::Or), Ok(()));
+
+    let mut bitset = vec![0b1_u32];
+    assert_eq!(bitset.import_at(64, &[], MergePolicy::Overwrite), Ok(()));
+    assert_eq!(bitset, [0b1]);
+    assert_eq!(
+        ReferenceSet::new(0).import_at(0, &[], MergePolicy::And),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_set_operations_match_across_backends() {
+    use index_set::ReferenceSet;
+    use std::sync::atomic::AtomicUsize;
+
+    fn combine(set: &[usize], other: &(impl BitSet<usize> + ?Sized)) -> [Vec<usize>; 4] {
+        let (mut and, mut and_not, mut or) = (set.to_vec(), set.to_vec(), set.to_vec());
+        and.intersect_with(other);
+        and_not.difference_with(other);
+        or.union_with(other).unwrap();
+
+        let mut fixed = set.to_vec();
+        assert_eq!(fixed.as_mut_slice().union_with(other), Err(2));
+        [and, and_not, or, fixed]
+    }
+
+    let set = [0b1011_usize, 1];
+    let other = [0b0110_usize, 0, 0b1];
+    let expected = [
+        vec![0b0010, 0],
+        vec![0b1001, 1],
+        vec![0b1111, 1, 1],
+        vec![0b1111, 1],
+    ];
+    // word-wise, from plain and atomic slots
+    assert_eq!(combine(&set, &other[..]), expected);
+    let atomic: Vec<AtomicUsize> = other.iter().map(|&word| AtomicUsize::new(word)).collect();
+    assert_eq!(combine(&set, &atomic[..]), expected);
+
+    // value by value, from a set without slots
+    let mut reference = ReferenceSet::new(3);
+    for value in other.iter_ones() {
+        reference.insert(value).unwrap();
+    }
+    assert_eq!(combine(&set, &reference), expected);
+}
+
+#[test]
+#[cfg(target_pointer_width = "64")]
+fn test_hybrid_holds_the_largest_value() {
+    use index_set::{HybridBitSet, MergePolicy};
+
+    let mut hybrid = HybridBitSet::new();
+    hybrid.insert(usize::MAX).unwrap();
+    hybrid.insert(5).unwrap();
+    assert_eq!(hybrid.capacity(), usize::MAX);
+    assert!(hybrid.has(usize::MAX));
+    assert_eq!(hybrid.remaining_in(..), usize::MAX - 1);
+    let highest_zeros: Vec<_> = hybrid.iter_zeros().rev().take(2).collect();
+    assert_eq!(highest_zeros, [usize::MAX - 1, usize::MAX - 2]);
+
+    // the slots of the dense form can't be allocated, so the set stays sparse
+    assert!(hybrid.try_complement().is_err());
+    assert_eq!(hybrid.import_at(0, &[1], MergePolicy::Or), Err(0));
+    assert!(!hybrid.is_dense());
+    assert!(hybrid.iter_ones().eq([5, usize::MAX]));
+
+    assert_eq!(hybrid.remove(usize::MAX), Some(true));
+    assert_eq!(hybrid.size(), 1);
+}